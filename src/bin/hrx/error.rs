@@ -0,0 +1,139 @@
+use hrx::{HrxDiagnostic, HrxError};
+use std::io::{Error as IoError, Write};
+use std::path::PathBuf;
+
+
+/// Enum representing all possible ways the application can fail.
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O error occured.
+    ///
+    /// This includes higher-level I/O errors like FS ones.
+    Io {
+        /// The file the I/O operation regards.
+        desc: String,
+        /// The failed operation.
+        ///
+        /// This should be lowercase and imperative ("create", "open").
+        op: &'static str,
+        /// The underlying error.
+        err: IoError,
+    },
+    /// The archive or its handling was invalid.
+    Hrx {
+        /// The archive in question.
+        desc: String,
+        /// The underlying error.
+        err: HrxError,
+    },
+    /// The archive had problems, as found by a diagnostic parse.
+    Diagnostics {
+        /// The archive in question.
+        desc: String,
+        /// The problems, never empty.
+        diagnostics: Vec<HrxDiagnostic>,
+    },
+    /// A requested file doesn't exist.
+    FileNotFound {
+        /// What requested the file.
+        who: &'static str,
+        /// The file that should exist.
+        path: PathBuf,
+    },
+    /// An archive entry was of the wrong kind.
+    WrongEntryKind {
+        /// The archive in question.
+        desc: String,
+        /// The offending entry.
+        path: String,
+        /// The expected kind.
+        expected: &'static str,
+    },
+    /// A requested archive entry doesn't exist.
+    EntryNotFound {
+        /// The archive in question.
+        desc: String,
+        /// The entry that should exist.
+        path: String,
+    },
+    /// A merge had conflicts.
+    MergeConflicts {
+        /// The merged archive.
+        desc: String,
+        /// Whether the archive comment conflicted.
+        comment: bool,
+        /// The conflicting entries.
+        paths: Vec<String>,
+    },
+}
+
+
+impl Error {
+    /// Write the error message to the specified output stream.
+    pub fn print_error<W: Write>(&self, err_out: &mut W) {
+        match *self {
+            Error::Io { ref desc, op, ref err } => {
+                // Strip the last 'e', if any, so we get correct inflection for continuous times
+                let op = uppercase_first(if op.ends_with('e') {
+                    &op[..op.len() - 1]
+                } else {
+                    op
+                });
+                writeln!(err_out, "{}ing {} failed: {}.", op, desc, err).unwrap()
+            }
+            Error::Hrx { ref desc, ref err } => writeln!(err_out, "{}: {}", desc, err).unwrap(),
+            Error::Diagnostics { ref desc, ref diagnostics } => {
+                for diag in diagnostics {
+                    writeln!(err_out, "{}:{}", desc, diag).unwrap()
+                }
+            }
+            Error::FileNotFound { who, ref path } => writeln!(err_out, "File {} for {} not found.", path.display(), who).unwrap(),
+            Error::WrongEntryKind { ref desc, ref path, expected } => writeln!(err_out, "{}: entry {} not a {}.", desc, path, expected).unwrap(),
+            Error::EntryNotFound { ref desc, ref path } => writeln!(err_out, "{}: entry {} not found.", desc, path).unwrap(),
+            Error::MergeConflicts { ref desc, comment, ref paths } => {
+                if comment {
+                    writeln!(err_out, "{}: merge conflict in archive comment.", desc).unwrap()
+                }
+                for path in paths {
+                    writeln!(err_out, "{}: merge conflict in entry {}.", desc, path).unwrap()
+                }
+            }
+        }
+    }
+
+    /// Get the executable exit value from an `Error` instance.
+    ///
+    /// Values are documented in `hrx(1)`.
+    pub fn exit_value(&self) -> i32 {
+        match *self {
+            Error::Io { .. } => 1,
+            Error::Hrx { ref err, .. } => hrx_exit_value(err),
+            Error::Diagnostics { ref diagnostics, .. } => hrx_exit_value(&diagnostics[0].error),
+            Error::FileNotFound { .. } => 3,
+            Error::WrongEntryKind { .. } => 4,
+            Error::EntryNotFound { .. } => 6,
+            Error::MergeConflicts { .. } => 7,
+        }
+    }
+}
+
+fn hrx_exit_value(err: &HrxError) -> i32 {
+    match *err {
+        HrxError::NoBoundary |
+        HrxError::Parse(_) |
+        HrxError::DuplicateEntry(_) |
+        HrxError::FileAsDirectory(_, _) => 2,
+        HrxError::BodyContainsBoundary(_) |
+        HrxError::PathEscapesTarget(_) |
+        HrxError::Unrepresentable(_, _) |
+        HrxError::InvalidPathComponent(_, _, _) => 4,
+    }
+}
+
+fn uppercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,42 @@
+extern crate hrx;
+#[macro_use]
+extern crate clap;
+
+mod ops;
+mod error;
+mod options;
+
+pub use self::error::Error;
+pub use self::options::{Subcommand, Options};
+
+use std::process::exit;
+use std::io::stderr;
+
+
+fn main() {
+    let result = actual_main().err().unwrap_or(0);
+    exit(result);
+}
+
+fn actual_main() -> Result<(), i32> {
+    if let Err(err) = result_main() {
+        err.print_error(&mut stderr());
+        Err(err.exit_value())
+    } else {
+        Ok(())
+    }
+}
+
+fn result_main() -> Result<(), Error> {
+    let opts = Options::parse();
+
+    match opts.subcommand {
+        Subcommand::Create { ref archive, ref dir, ref options } => ops::create(archive, dir, options),
+        Subcommand::Extract { ref archive, ref dir, ref options, verbose } => ops::extract(archive, dir, options, verbose),
+        Subcommand::List { ref archive } => ops::list(archive),
+        Subcommand::Cat { ref archive, ref paths } => ops::cat(archive, paths),
+        Subcommand::Validate { ref archives } => ops::validate(archives),
+        Subcommand::Fmt { ref archive, ref output, boundary_length, shortest_boundary } => ops::fmt(archive, output, boundary_length, shortest_boundary),
+        Subcommand::Merge { ref base, ref ours, ref theirs, ref output, ref options } => ops::merge(base, ours, theirs, output, options),
+    }
+}
@@ -0,0 +1,287 @@
+//! The implementations of all subcommands.
+
+
+use hrx::{ExtractOptions, HrxEntryData, MergeOptions, PackOptions, HrxArchive, HrxError, merge_with_options};
+use std::io::{self, ErrorKind as IoErrorKind, Error as IoError, Write, Read};
+use self::super::Error;
+use std::num::NonZeroUsize;
+use std::path::{PathBuf, Path};
+use std::str;
+use std::fs::{self, File};
+
+
+/// Pack the specified directory into an archive, listing skipped files on stderr.
+pub fn create(archive: &Option<PathBuf>, dir: &Path, options: &PackOptions) -> Result<(), Error> {
+    if !dir.is_dir() {
+        return Err(Error::FileNotFound {
+            who: "input directory",
+            path: dir.to_path_buf(),
+        });
+    }
+
+    let (arch, skipped) = HrxArchive::from_dir_with_skipped(dir, options).map_err(|e| compound_error(e, dir.display().to_string(), "pack"))?;
+    for (path, reason) in skipped {
+        eprintln!("Skipped {}: {}", path, reason);
+    }
+
+    write_archive(&arch, archive, false)
+}
+
+/// Extract the specified archive into a directory.
+pub fn extract(archive: &Option<PathBuf>, dir: &Path, options: &ExtractOptions, verbose: bool) -> Result<(), Error> {
+    let arch = read_archive(archive)?;
+    let written = arch.extract_to(dir, options).map_err(|e| compound_error(e, archive_desc(archive), "extract"))?;
+
+    if verbose {
+        for path in written {
+            println!("{}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the paths to all entries in the specified archive, with a trailing slash for directories.
+pub fn list(archive: &Option<PathBuf>) -> Result<(), Error> {
+    let arch = read_archive(archive)?;
+
+    for (path, entry) in arch.entries {
+        match entry.data {
+            HrxEntryData::File { .. } |
+            HrxEntryData::Binary { .. } => println!("{}", path),
+            HrxEntryData::Directory => println!("{}/", path),
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the contents of the specified files in the archive to the standard output.
+pub fn cat(archive: &Option<PathBuf>, paths: &[String]) -> Result<(), Error> {
+    let arch = read_archive(archive)?;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for path in paths {
+        let body = match arch.entries.get(&path[..]).map(|e| &e.data) {
+            Some(&HrxEntryData::File { ref body }) => body.as_ref().map(|b| b.as_bytes()).unwrap_or(b""),
+            Some(&HrxEntryData::Binary { ref body }) => &body[..],
+            Some(&HrxEntryData::Directory) => {
+                return Err(Error::WrongEntryKind {
+                    desc: archive_desc(archive),
+                    path: path.clone(),
+                    expected: "file",
+                })
+            }
+            None => {
+                return Err(Error::EntryNotFound {
+                    desc: archive_desc(archive),
+                    path: path.clone(),
+                })
+            }
+        };
+
+        stdout.write_all(body)
+            .map_err(|e| {
+                Error::Io {
+                    desc: "standard output".to_string(),
+                    op: "write",
+                    err: e,
+                }
+            })?
+    }
+
+    Ok(())
+}
+
+/// Check, whether all the specified archives are valid.
+pub fn validate(archives: &[Option<PathBuf>]) -> Result<(), Error> {
+    for archive in archives {
+        let desc = archive_desc(archive);
+        let body = read_archive_bytes(archive)?;
+
+        let diagnostics = match str::from_utf8(&body) {
+                Ok(body) => HrxArchive::parse_with_diagnostics(body).map(|(_, diagnostics)| diagnostics),
+                // Archives with binary contents can't be recovered from, so only the first problem is reported
+                Err(_) => HrxArchive::from_bytes(&body).map(|_| vec![]),
+            }
+            .map_err(|e| {
+                Error::Hrx {
+                    desc: desc.clone(),
+                    err: e,
+                }
+            })?;
+        if !diagnostics.is_empty() {
+            return Err(Error::Diagnostics {
+                desc: desc,
+                diagnostics: diagnostics,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Reserialise the specified archive, optionally changing the boundary length to the specified or the shortest valid one.
+pub fn fmt(archive: &Option<PathBuf>, output: &Option<PathBuf>, boundary_length: Option<NonZeroUsize>, shortest_boundary: bool) -> Result<(), Error> {
+    let mut arch = read_archive(archive)?;
+
+    if let Some(len) = boundary_length {
+        arch.set_boundary_length(len)
+            .map_err(|e| {
+                Error::Hrx {
+                    desc: archive_desc(archive),
+                    err: e,
+                }
+            })?;
+    }
+
+    write_archive(&arch, output, shortest_boundary)
+}
+
+/// Three-way merge the specified archives, writing the result out even if it has conflicts.
+///
+/// Empty files stand for empty archives, like git passes for the common ancestor of files added on both sides.
+pub fn merge(base: &Option<PathBuf>, ours: &Option<PathBuf>, theirs: &Option<PathBuf>, output: &Option<PathBuf>, options: &MergeOptions) -> Result<(), Error> {
+    let ours = read_archive_or_empty(ours)?;
+    let theirs = read_archive_or_empty(theirs)?;
+    let base = read_archive_or_empty(base)?;
+
+    let merged = merge_with_options(&base, &ours, &theirs, options);
+    write_archive(&merged.archive, output, false)?;
+
+    if !merged.is_clean() {
+        return Err(Error::MergeConflicts {
+            desc: output_desc(output),
+            comment: merged.comment_conflict,
+            paths: merged.conflicts.keys().map(|p| p.to_string()).collect(),
+        });
+    }
+
+    Ok(())
+}
+
+
+fn archive_desc(archive: &Option<PathBuf>) -> String {
+    match archive.as_ref() {
+        Some(path) => path.display().to_string(),
+        None => "<stdin>".to_string(),
+    }
+}
+
+fn output_desc(output: &Option<PathBuf>) -> String {
+    match output.as_ref() {
+        Some(path) => path.display().to_string(),
+        None => "<stdout>".to_string(),
+    }
+}
+
+fn read_archive(archive: &Option<PathBuf>) -> Result<HrxArchive, Error> {
+    let body = read_archive_bytes(archive)?;
+
+    HrxArchive::from_bytes(&body).map_err(|e| {
+        Error::Hrx {
+            desc: archive_desc(archive),
+            err: e,
+        }
+    })
+}
+
+fn read_archive_or_empty(archive: &Option<PathBuf>) -> Result<HrxArchive, Error> {
+    let body = read_archive_bytes(archive)?;
+    if body.is_empty() {
+        return Ok(HrxArchive::new(NonZeroUsize::new(3).unwrap()));
+    }
+
+    HrxArchive::from_bytes(&body).map_err(|e| {
+        Error::Hrx {
+            desc: archive_desc(archive),
+            err: e,
+        }
+    })
+}
+
+fn read_archive_bytes(archive: &Option<PathBuf>) -> Result<Vec<u8>, Error> {
+    let desc = archive_desc(archive);
+
+    let mut body = vec![];
+    match archive.as_ref() {
+        Some(path) => {
+            File::open(path)
+                .and_then(|mut f| f.read_to_end(&mut body))
+                .map_err(|e| if e.kind() == IoErrorKind::NotFound {
+                    Error::FileNotFound {
+                        who: "archive",
+                        path: path.clone(),
+                    }
+                } else {
+                    Error::Io {
+                        desc: desc.clone(),
+                        op: "read",
+                        err: e,
+                    }
+                })?;
+        }
+        None => {
+            io::stdin()
+                .read_to_end(&mut body)
+                .map_err(|e| {
+                    Error::Io {
+                        desc: desc.clone(),
+                        op: "read",
+                        err: e,
+                    }
+                })?;
+        }
+    }
+
+    Ok(body)
+}
+
+/// Write the archive out, with `auto_boundary` specifying whether to use the shortest valid boundary length.
+fn write_archive(arch: &HrxArchive, output: &Option<PathBuf>, auto_boundary: bool) -> Result<(), Error> {
+    let desc = output_desc(output);
+
+    let mut out = vec![];
+    if auto_boundary {
+        arch.serialise_auto(&mut out).map_err(|e| {
+                Error::Io {
+                    desc: desc.clone(),
+                    op: "serialise",
+                    err: e,
+                }
+            })?;
+    } else {
+        arch.serialise(&mut out).map_err(|e| compound_error(e, desc.clone(), "serialise"))?;
+    }
+
+    match output.as_ref() {
+            Some(path) => fs::write(path, out),
+            None => io::stdout().write_all(&out),
+        }
+        .map_err(|e| {
+            Error::Io {
+                desc: desc,
+                op: "write",
+                err: e,
+            }
+        })
+}
+
+fn compound_error(err: Result<HrxError, IoError>, desc: String, op: &'static str) -> Error {
+    match err {
+        Ok(e) => {
+            Error::Hrx {
+                desc: desc,
+                err: e,
+            }
+        }
+        Err(e) => {
+            Error::Io {
+                desc: desc,
+                op: op,
+                err: e,
+            }
+        }
+    }
+}
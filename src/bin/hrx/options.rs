@@ -0,0 +1,257 @@
+//! Option parsing and management.
+//!
+//! Use the `Options::parse()` function to get the program's configuration,
+//! as parsed from the commandline.
+
+
+use hrx::{UnrepresentablePolicy, OverwritePolicy, ExtractOptions, SymlinkPolicy, MergeOptions, BinaryPolicy, PackOptions, PackOrder};
+use clap::{Error as ClapError, AppSettings, SubCommand, ErrorKind, App, Arg};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+
+/// Representation of the application's all configurable values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// What to do.
+    pub subcommand: Subcommand,
+}
+
+/// A single operation to perform.
+///
+/// Archive paths are `None` for the standard input/output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Subcommand {
+    /// Pack a directory into an archive.
+    Create {
+        /// Where to write the archive.
+        archive: Option<PathBuf>,
+        /// The directory to pack.
+        dir: PathBuf,
+        /// How to pack it.
+        options: PackOptions,
+    },
+    /// Extract an archive into a directory.
+    Extract {
+        /// The archive to extract.
+        archive: Option<PathBuf>,
+        /// Where to extract it.
+        dir: PathBuf,
+        /// How to extract it.
+        options: ExtractOptions,
+        /// Whether to print the paths written.
+        verbose: bool,
+    },
+    /// List the archive's entries.
+    List {
+        /// The archive to list.
+        archive: Option<PathBuf>,
+    },
+    /// Write the specified entries' contents to the standard output.
+    Cat {
+        /// The archive to read.
+        archive: Option<PathBuf>,
+        /// The entries to write, in order.
+        paths: Vec<String>,
+    },
+    /// Check the archives for validity.
+    Validate {
+        /// The archives to check.
+        archives: Vec<Option<PathBuf>>,
+    },
+    /// Reserialise an archive in canonical form.
+    Fmt {
+        /// The archive to format.
+        archive: Option<PathBuf>,
+        /// Where to write the result, `Some(archive)` by default.
+        output: Option<PathBuf>,
+        /// The boundary length to use, the current one if `None`.
+        boundary_length: Option<NonZeroUsize>,
+        /// Whether to use the shortest valid boundary length instead.
+        shortest_boundary: bool,
+    },
+    /// Three-way merge archives.
+    Merge {
+        /// The common ancestor.
+        base: Option<PathBuf>,
+        /// Our side.
+        ours: Option<PathBuf>,
+        /// Their side.
+        theirs: Option<PathBuf>,
+        /// Where to write the result, `Some(ours)` by default.
+        output: Option<PathBuf>,
+        /// How to mark conflicts.
+        options: MergeOptions,
+    },
+}
+
+
+impl Options {
+    /// Parse `env`-wide command-line arguments into an `Options` instance
+    pub fn parse() -> Options {
+        let matches = App::new("hrx")
+            .version(crate_version!())
+            .author(env!("CARGO_PKG_AUTHORS"))
+            .about("Rust implementation of the HRX plain text archive format")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .setting(AppSettings::ColoredHelp)
+            .setting(AppSettings::VersionlessSubcommands)
+            .subcommand(SubCommand::with_name("create")
+                .about("Pack a directory into an archive")
+                .args(&[Arg::from_usage("<ARCHIVE> 'Archive to write, - for stdout'"),
+                        Arg::from_usage("<DIR> 'Directory to pack'"),
+                        Arg::from_usage("-i --include [GLOB]... 'Only pack files matching any of these'").number_of_values(1),
+                        Arg::from_usage("-x --exclude [GLOB]... 'Don't pack files matching any of these'").number_of_values(1),
+                        Arg::from_usage("--files-first 'Put files before subdirectories'"),
+                        Arg::from_usage("--unsorted 'Don't sort directory entries'").conflicts_with("files-first"),
+                        Arg::from_usage("-s --symlinks [POLICY] 'What to do with symlinks'")
+                            .possible_values(&["follow", "skip", "error"])
+                            .default_value("error"),
+                        Arg::from_usage("--skip-unrepresentable 'Skip files that can't be represented instead of failing, listing them on stderr'"),
+                        Arg::from_usage("--binary [POLICY] 'What to do with files that aren't UTF-8'")
+                            .possible_values(&["unrepresentable", "raw", "base64"])
+                            .default_value("unrepresentable"),
+                        Arg::from_usage("-b --boundary-length [LEN] 'Minimal boundary length'")
+                            .validator(Options::boundary_length_validator)
+                            .default_value("3")]))
+            .subcommand(SubCommand::with_name("extract")
+                .about("Extract an archive into a directory")
+                .args(&[Arg::from_usage("<ARCHIVE> 'Archive to extract, - for stdin'"),
+                        Arg::from_usage("<DIR> 'Directory to extract to'"),
+                        Arg::from_usage("--overwrite 'Overwrite existing files'"),
+                        Arg::from_usage("--skip-existing 'Leave existing files alone'").conflicts_with("overwrite"),
+                        Arg::from_usage("-n --dry-run 'Don't write anything, implies --verbose'"),
+                        Arg::from_usage("--decode-base64 'Decode files packed with --binary base64'"),
+                        Arg::from_usage("-v --verbose 'Print paths written'")]))
+            .subcommand(SubCommand::with_name("list")
+                .about("List the archive's entries")
+                .arg(Arg::from_usage("<ARCHIVE> 'Archive to list, - for stdin'")))
+            .subcommand(SubCommand::with_name("cat")
+                .about("Write entries' contents to stdout")
+                .args(&[Arg::from_usage("<ARCHIVE> 'Archive to read, - for stdin'"), Arg::from_usage("<PATH>... 'Entries to write'")]))
+            .subcommand(SubCommand::with_name("validate")
+                .about("Check archives for validity")
+                .arg(Arg::from_usage("<ARCHIVE>... 'Archives to check, - for stdin'")))
+            .subcommand(SubCommand::with_name("fmt")
+                .about("Reserialise an archive in canonical form")
+                .args(&[Arg::from_usage("<ARCHIVE> 'Archive to format, - for stdin'"),
+                        Arg::from_usage("-o --output [OUTPUT] 'Where to write the result, - for stdout, in-place by default'"),
+                        Arg::from_usage("-b --boundary-length [LEN] 'Boundary length to use, current one by default'")
+                            .validator(Options::boundary_length_validator),
+                        Arg::from_usage("-s --shortest-boundary 'Use the shortest valid boundary length'").conflicts_with("boundary-length")]))
+            .subcommand(SubCommand::with_name("merge")
+                .about("Three-way merge archives, e.g. as a git merge driver")
+                .args(&[Arg::from_usage("<BASE> 'Common ancestor archive, - for stdin'"),
+                        Arg::from_usage("<OURS> 'Our archive, - for stdin'"),
+                        Arg::from_usage("<THEIRS> 'Their archive, - for stdin'"),
+                        Arg::from_usage("-o --output [OUTPUT] 'Where to write the result, - for stdout, OURS by default'"),
+                        Arg::from_usage("-L --marker-size [SIZE] 'Length of the conflict markers'")
+                            .validator(Options::marker_size_validator)
+                            .default_value("7")]))
+            .get_matches();
+
+        Options {
+            subcommand: match matches.subcommand() {
+                ("create", Some(matches)) => {
+                    Subcommand::Create {
+                        archive: Options::archive_path(matches.value_of("ARCHIVE").unwrap()),
+                        dir: PathBuf::from(matches.value_of("DIR").unwrap()),
+                        options: PackOptions {
+                            include: matches.values_of("include").map(|v| v.map(str::to_string).collect()).unwrap_or_default(),
+                            exclude: matches.values_of("exclude").map(|v| v.map(str::to_string).collect()).unwrap_or_default(),
+                            order: if matches.is_present("files-first") {
+                                PackOrder::FilesFirst
+                            } else if matches.is_present("unsorted") {
+                                PackOrder::Unsorted
+                            } else {
+                                PackOrder::Name
+                            },
+                            symlinks: match matches.value_of("symlinks").unwrap() {
+                                "follow" => SymlinkPolicy::Follow,
+                                "skip" => SymlinkPolicy::Skip,
+                                _ => SymlinkPolicy::Error,
+                            },
+                            unrepresentable: if matches.is_present("skip-unrepresentable") {
+                                UnrepresentablePolicy::Skip
+                            } else {
+                                UnrepresentablePolicy::Error
+                            },
+                            binary: match matches.value_of("binary").unwrap() {
+                                "raw" => BinaryPolicy::Raw,
+                                "base64" => BinaryPolicy::Base64,
+                                _ => BinaryPolicy::Unrepresentable,
+                            },
+                            min_boundary_length: NonZeroUsize::from_str(matches.value_of("boundary-length").unwrap()).unwrap(),
+                        },
+                    }
+                }
+                ("extract", Some(matches)) => {
+                    Subcommand::Extract {
+                        archive: Options::archive_path(matches.value_of("ARCHIVE").unwrap()),
+                        dir: PathBuf::from(matches.value_of("DIR").unwrap()),
+                        options: ExtractOptions {
+                            overwrite: if matches.is_present("overwrite") {
+                                OverwritePolicy::Overwrite
+                            } else if matches.is_present("skip-existing") {
+                                OverwritePolicy::Skip
+                            } else {
+                                OverwritePolicy::Error
+                            },
+                            dry_run: matches.is_present("dry-run"),
+                            decode_base64: matches.is_present("decode-base64"),
+                        },
+                        verbose: matches.is_present("verbose") || matches.is_present("dry-run"),
+                    }
+                }
+                ("list", Some(matches)) => Subcommand::List { archive: Options::archive_path(matches.value_of("ARCHIVE").unwrap()) },
+                ("cat", Some(matches)) => {
+                    Subcommand::Cat {
+                        archive: Options::archive_path(matches.value_of("ARCHIVE").unwrap()),
+                        paths: matches.values_of("PATH").unwrap().map(str::to_string).collect(),
+                    }
+                }
+                ("validate", Some(matches)) => Subcommand::Validate { archives: matches.values_of("ARCHIVE").unwrap().map(Options::archive_path).collect() },
+                ("fmt", Some(matches)) => {
+                    let archive = Options::archive_path(matches.value_of("ARCHIVE").unwrap());
+                    Subcommand::Fmt {
+                        output: matches.value_of("output").map(Options::archive_path).unwrap_or_else(|| archive.clone()),
+                        archive: archive,
+                        boundary_length: matches.value_of("boundary-length").map(|l| NonZeroUsize::from_str(l).unwrap()),
+                        shortest_boundary: matches.is_present("shortest-boundary"),
+                    }
+                }
+                ("merge", Some(matches)) => {
+                    if ["BASE", "OURS", "THEIRS"].iter().filter(|a| matches.value_of(a) == Some("-")).count() > 1 {
+                        ClapError::with_description("Only one of BASE, OURS and THEIRS can be read from stdin", ErrorKind::ArgumentConflict).exit();
+                    }
+
+                    let ours = Options::archive_path(matches.value_of("OURS").unwrap());
+                    Subcommand::Merge {
+                        base: Options::archive_path(matches.value_of("BASE").unwrap()),
+                        theirs: Options::archive_path(matches.value_of("THEIRS").unwrap()),
+                        output: matches.value_of("output").map(Options::archive_path).unwrap_or_else(|| ours.clone()),
+                        ours: ours,
+                        options: MergeOptions {
+                            marker_size: NonZeroUsize::from_str(matches.value_of("marker-size").unwrap()).unwrap().get(),
+                            ..MergeOptions::default()
+                        },
+                    }
+                }
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    fn archive_path(s: &str) -> Option<PathBuf> {
+        if s == "-" { None } else { Some(PathBuf::from(s)) }
+    }
+
+    fn boundary_length_validator(s: String) -> Result<(), String> {
+        NonZeroUsize::from_str(&s).map(|_| ()).map_err(|e| format!("{} is not a valid boundary length: {}", s, e))
+    }
+
+    fn marker_size_validator(s: String) -> Result<(), String> {
+        NonZeroUsize::from_str(&s).map(|_| ()).map_err(|e| format!("{} is not a valid marker size: {}", s, e))
+    }
+}
@@ -0,0 +1,98 @@
+use self::super::parse::{UTF8_EXPECTED, ParseError};
+use self::super::span::Locator;
+use self::super::HrxError;
+use std::{iter, str};
+
+
+/// The entry comment marking files with base64-encoded contents.
+///
+/// Added by packing with [`BinaryPolicy::Base64`](enum.BinaryPolicy.html#variant.Base64),
+/// and recognised by extracting with [`ExtractOptions::decode_base64`](struct.ExtractOptions.html#structfield.decode_base64).
+pub const BASE64_COMMENT: &str = "Content-Transfer-Encoding: base64";
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Per RFC 2045, in characters
+const BASE64_LINE_LENGTH: usize = 76;
+
+
+/// Check whether the specified offset into the data is at the start of an invalid UTF-8 sequence
+pub fn starts_non_utf8(data: &[u8], offset: usize) -> bool {
+    match str::from_utf8(&data[offset..data.len().min(offset + 4)]) {
+        Ok(_) => false,
+        Err(err) => err.valid_up_to() == 0,
+    }
+}
+
+pub fn non_utf8_error(source: &[u8], offset: usize) -> HrxError {
+    let position = Locator::new(source).locate(offset);
+    HrxError::Parse(ParseError {
+        line: position.line,
+        column: position.column,
+        offset: offset,
+        expected: iter::once(UTF8_EXPECTED).collect(),
+    })
+}
+
+
+/// Encode the specified data as base64, in lines of 76 characters, each followed by a newline
+pub fn encode_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4 * (BASE64_LINE_LENGTH + 1) / BASE64_LINE_LENGTH + 1);
+    for (i, chunk) in data.chunks(3).enumerate() {
+        if i != 0 && i % (BASE64_LINE_LENGTH / 4) == 0 {
+            out.push('\n');
+        }
+
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (j, &b)| acc | (b as u32) << (16 - 8 * j));
+        for j in 0..4 {
+            if j <= chunk.len() {
+                out.push(BASE64_ALPHABET[(group >> (18 - 6 * j) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Decode the specified base64, ignoring whitespace, or `None` if it's invalid
+pub fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() / 4 * 3);
+    let mut group = 0u32;
+    let mut bits = 0;
+    let mut digits = 0;
+    let mut padding = 0;
+
+    for c in data.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let val = match c {
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            _ if padding != 0 => return None,
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        digits += 1;
+        group = group << 6 | val as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((group >> bits) as u8);
+            group &= (1 << bits) - 1;
+        }
+    }
+
+    // A lone digit doesn't make a byte, and padding, if any, needs to complete the last group
+    if bits >= 6 || padding > 2 || (padding != 0 && (digits + padding) % 4 != 0) {
+        None
+    } else {
+        Some(out)
+    }
+}
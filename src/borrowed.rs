@@ -0,0 +1,288 @@
+use self::super::{HrxArchiveSpans, HrxEntrySpans, ParseOptions, HrxEntryData, HrxArchive, HrxEntry, HrxError, HrxPath, HrxSpan, parse};
+use self::super::binary::{non_utf8_error, starts_non_utf8};
+use self::super::span::Locator;
+use linked_hash_map::LinkedHashMap;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::str;
+
+
+/// A read-only view of a Human-Readable Archive, borrowing its paths, comments, and bodies from the data it was parsed from.
+///
+/// Parsing one only allocates the entry map itself, so it's preferable to [`HrxArchive`](struct.HrxArchive.html)
+/// when just reading large archives; it can be turned into one via [`into_owned()`](#method.into_owned) when needed.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxEntryDataRef, HrxArchiveRef, HrxEntryRef, HrxArchive};
+/// # use std::str::FromStr;
+/// let input_text = "<===> input.scss
+/// ul { li { list-style: none; } }
+///
+/// <===>
+/// Generated files
+/// <===> out/
+/// ";
+///
+/// let archive = HrxArchiveRef::parse(input_text).unwrap();
+/// assert_eq!(archive.comment(), None);
+/// assert_eq!(archive.boundary_length().get(), 3);
+/// assert_eq!(archive.get("input.scss"),
+///            Some(&HrxEntryRef {
+///                comment: None,
+///                data: HrxEntryDataRef::File { body: Some("ul { li { list-style: none; } }\n") },
+///            }));
+/// assert_eq!(archive.get("out"),
+///            Some(&HrxEntryRef {
+///                comment: Some("Generated files"),
+///                data: HrxEntryDataRef::Directory,
+///            }));
+/// assert_eq!(archive.entries().keys().collect::<Vec<_>>(), vec![&"input.scss", &"out"]);
+///
+/// assert_eq!(archive.into_owned(), HrxArchive::from_str(input_text).unwrap());
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxArchiveRef<'a> {
+    comment: Option<&'a str>,
+    entries: LinkedHashMap<&'a str, HrxEntryRef<'a>>,
+    boundary_length: NonZeroUsize,
+    source: &'a [u8],
+}
+
+/// A single entry in a borrowed archive, consisting of an optional comment and some data.
+///
+/// The borrowed counterpart to [`HrxEntry`](struct.HrxEntry.html).
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxEntryRef<'a> {
+    /// Some optional metadata.
+    pub comment: Option<&'a str>,
+    /// The specific entry data.
+    pub data: HrxEntryDataRef<'a>,
+}
+
+/// Some variant of a borrowed entry's contained data.
+///
+/// The borrowed counterpart to [`HrxEntryData`](enum.HrxEntryData.html).
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HrxEntryDataRef<'a> {
+    /// File with some optional contents.
+    File { body: Option<&'a str>, },
+    /// File whose contents aren't valid UTF-8.
+    ///
+    /// Only produced by [`HrxArchiveRef::from_bytes()`](struct.HrxArchiveRef.html#method.from_bytes).
+    Binary { body: &'a [u8], },
+    /// Bodyless directory.
+    Directory,
+}
+
+
+impl<'a> HrxArchiveRef<'a> {
+    /// Parse the specified string into an archive borrowing from it.
+    ///
+    /// Performs the same validation as [`HrxArchive::from_str()`](struct.HrxArchive.html#impl-FromStr) and returns the same errors.
+    pub fn parse(s: &'a str) -> Result<HrxArchiveRef<'a>, HrxError> {
+        let width = parse::discover_first_boundary_length(s).ok_or(HrxError::NoBoundary)?;
+        let (comment, entries, boundary_length) = parse::archive_ref(s, width)?;
+
+        Ok(HrxArchiveRef {
+            comment: comment,
+            entries: parse::reduce_raw_entry_refs_and_validate_directory_tree(entries)?,
+            boundary_length: boundary_length,
+            source: s.as_bytes(),
+        })
+    }
+
+    /// Parse the specified data, which may contain file bodies which aren't valid UTF-8, into an archive borrowing from it.
+    ///
+    /// Only the parts found in the archive structure are checked for UTF-8 validity, each one separately, as they're reached;
+    /// bodies which aren't valid become [`HrxEntryDataRef::Binary`](enum.HrxEntryDataRef.html#variant.Binary),
+    /// while invalid comments and paths are errors.
+    ///
+    /// Otherwise performs the same validation as [`HrxArchiveRef::parse()`](#method.parse) and returns the same errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxEntryDataRef, HrxArchiveRef, HrxError};
+    /// let arch = HrxArchiveRef::from_bytes(b"<===> latin-1.txt\nCaf\xE9\n<===> utf-8.txt\nCaf\xC3\xA9\n").unwrap();
+    /// assert_eq!(arch.get("latin-1.txt").unwrap().data, HrxEntryDataRef::Binary { body: b"Caf\xE9" });
+    /// assert_eq!(arch.get("utf-8.txt").unwrap().data, HrxEntryDataRef::File { body: Some("Café\n") });
+    ///
+    /// match HrxArchiveRef::from_bytes(b"<===>\nCaf\xE9\n<===> file.txt\n") {
+    ///     Err(HrxError::Parse(err)) => assert_eq!((err.line, err.column), (2, 4)),
+    ///     err => panic!("{:?}", err),
+    /// }
+    /// ```
+    pub fn from_bytes(data: &'a [u8]) -> Result<HrxArchiveRef<'a>, HrxError> {
+        let width = parse::discover_first_boundary_length(data).ok_or(HrxError::NoBoundary)?;
+        let ((comment, entries), _) = parse::archive_bytes(data, width, &ParseOptions::default()).map_err(|err| if starts_non_utf8(data, err.offset) {
+                non_utf8_error(data, err.offset)
+            } else {
+                err.into()
+            })?;
+
+        let text = |r: Range<usize>| str::from_utf8(&data[r.clone()]).map_err(|err| non_utf8_error(data, r.start + err.valid_up_to()));
+        let entries = entries.into_iter()
+            .map(|entry| {
+                Ok((text(entry.path)?,
+                    HrxEntryRef {
+                        comment: entry.comment.map(&text).transpose()?,
+                        data: match entry.body {
+                            Some(Some(body)) => {
+                                match str::from_utf8(&data[body.clone()]) {
+                                    Ok(body) => HrxEntryDataRef::File { body: Some(body) },
+                                    Err(_) => HrxEntryDataRef::Binary { body: &data[body] },
+                                }
+                            }
+                            Some(None) => HrxEntryDataRef::File { body: None },
+                            None => HrxEntryDataRef::Directory,
+                        },
+                    }))
+            })
+            .collect::<Result<Vec<_>, HrxError>>()?;
+
+        Ok(HrxArchiveRef {
+            comment: comment.map(&text).transpose()?,
+            entries: parse::reduce_raw_entry_refs_and_validate_directory_tree(entries)?,
+            boundary_length: width,
+            source: data,
+        })
+    }
+
+    /// Get the data this archive was parsed from.
+    pub fn source(&self) -> &'a [u8] {
+        self.source
+    }
+
+    /// Get the archive comment, if any.
+    pub fn comment(&self) -> Option<&'a str> {
+        self.comment
+    }
+
+    /// Get all the entries, keyed by their paths, in archive order.
+    pub fn entries(&self) -> &LinkedHashMap<&'a str, HrxEntryRef<'a>> {
+        &self.entries
+    }
+
+    /// Get the entry at the specified path, if any.
+    pub fn get(&self, path: &str) -> Option<&HrxEntryRef<'a>> {
+        self.entries.get(path)
+    }
+
+    /// Get the boundary length, i.e. the amount of `=` characters in the boundary.
+    pub fn boundary_length(&self) -> NonZeroUsize {
+        self.boundary_length
+    }
+
+    /// Get the location of the archive comment in the source, if any.
+    pub fn comment_span(&self) -> Option<HrxSpan> {
+        self.comment.map(|c| Locator::new(self.source).span_of(c))
+    }
+
+    /// Get the locations of the parts of the entry at the specified path in the source, if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxPosition, HrxArchiveRef};
+    /// let arch = HrxArchiveRef::parse("<===> file1\nContents\n<===>\nA comment\n<===>  dir/\n").unwrap();
+    ///
+    /// let file1 = arch.entry_spans("file1").unwrap();
+    /// assert_eq!(file1.boundary_line.start.to_string(), "1:1");
+    /// assert_eq!(&arch.source()[file1.boundary_line.start.offset..file1.boundary_line.end.offset], b"<===> file1");
+    /// assert_eq!(&arch.source()[file1.body.unwrap().start.offset..file1.body.unwrap().end.offset], b"Contents");
+    /// assert_eq!(file1.comment, None);
+    ///
+    /// let dir = arch.entry_spans("dir").unwrap();
+    /// assert_eq!(dir.boundary_line.start,
+    ///            HrxPosition {
+    ///                line: 5,
+    ///                column: 1,
+    ///                offset: 37,
+    ///            });
+    /// assert_eq!(dir.path.start.to_string(), "5:8");
+    /// assert_eq!(dir.boundary_line.end.to_string(), "5:12");
+    /// assert_eq!(dir.comment.unwrap().start.to_string(), "4:1");
+    /// assert_eq!(dir.body, None);
+    ///
+    /// assert_eq!(arch.entry_spans("file2"), None);
+    /// ```
+    pub fn entry_spans(&self, path: &str) -> Option<HrxEntrySpans> {
+        let (path, entry) = self.entries.iter().find(|&(&p, _)| p == path)?;
+        Some(self.entry_spans_impl(&mut Locator::new(self.source), path, entry))
+    }
+
+    /// Get the locations of the archive comment and all entries in the source.
+    pub fn spans(&self) -> HrxArchiveSpans {
+        let mut locator = Locator::new(self.source);
+
+        // The archive comment always comes last, so this moves forward throughout
+        let entries = self.entries.iter().map(|(p, e)| (HrxPath(p.to_string()), self.entry_spans_impl(&mut locator, p, e))).collect();
+        HrxArchiveSpans {
+            comment: self.comment.map(|c| locator.span_of(c)),
+            entries: entries,
+        }
+    }
+
+    fn entry_spans_impl(&self, locator: &mut Locator, path: &str, entry: &HrxEntryRef) -> HrxEntrySpans {
+        let path_start = path.as_ptr() as usize - self.source.as_ptr() as usize;
+        let path_end = path_start + path.len();
+
+        // `boundary " "+ path`
+        let spaces = self.source[..path_start].iter().rev().take_while(|&&b| b == b' ').count();
+        let line_start = path_start - spaces - (self.boundary_length.get() + 2);
+        let line_end = match entry.data {
+            HrxEntryDataRef::File { .. } |
+            HrxEntryDataRef::Binary { .. } => path_end,
+            HrxEntryDataRef::Directory => path_end + 1,
+        };
+
+        HrxEntrySpans {
+            comment: entry.comment.map(|c| locator.span_of(c)),
+            boundary_line: locator.span(line_start, line_end),
+            path: locator.span(path_start, path_end),
+            body: match entry.data {
+                HrxEntryDataRef::File { body } => body.map(|b| locator.span_of(b)),
+                HrxEntryDataRef::Binary { body } => Some(locator.span_of(body)),
+                HrxEntryDataRef::Directory => None,
+            },
+        }
+    }
+
+    /// Copy the contents into an owned, modifiable archive.
+    pub fn into_owned(self) -> HrxArchive {
+        HrxArchive {
+            comment: self.comment.map(str::to_string),
+            entries: self.entries.into_iter().map(|(p, e)| (HrxPath(p.to_string()), e.into_owned())).collect(),
+            boundary_length: self.boundary_length,
+        }
+    }
+}
+
+impl<'a> HrxEntryRef<'a> {
+    /// Copy the contents into an owned entry.
+    pub fn into_owned(self) -> HrxEntry {
+        HrxEntry {
+            comment: self.comment.map(str::to_string),
+            data: self.data.into_owned(),
+        }
+    }
+}
+
+impl<'a> HrxEntryDataRef<'a> {
+    /// Copy the contents into owned entry data.
+    pub fn into_owned(self) -> HrxEntryData {
+        match self {
+            HrxEntryDataRef::File { body } => HrxEntryData::File { body: body.map(str::to_string) },
+            HrxEntryDataRef::Binary { body } => HrxEntryData::Binary { body: body.to_vec() },
+            HrxEntryDataRef::Directory => HrxEntryData::Directory,
+        }
+    }
+}
+
+impl<'a> From<HrxArchiveRef<'a>> for HrxArchive {
+    fn from(r: HrxArchiveRef<'a>) -> HrxArchive {
+        r.into_owned()
+    }
+}
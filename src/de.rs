@@ -0,0 +1,375 @@
+//! HRX as a serde data format, reading side.
+
+
+use serde::de::{self, DeserializeSeed, DeserializeOwned, EnumAccess, Deserialize, Unexpected, MapAccess, SeqAccess, Visitor};
+use self::super::{HrxSerdeError, HrxEntryData, HrxArchive, HrxEntry, HrxTree};
+use serde::de::value::{BorrowedBytesDeserializer, BorrowedStrDeserializer};
+use linked_hash_map::Values;
+use std::str::FromStr;
+
+
+/// Deserialise a value from the specified HRX archive string.
+///
+/// See [`from_archive()`](fn.from_archive.html) for how entries map onto values.
+///
+/// Only available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate serde;
+/// # extern crate hrx;
+/// # use std::collections::BTreeMap;
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Fixture {
+///     input: String,
+///     output: Option<String>,
+///     options: BTreeMap<String, u32>,
+/// }
+///
+/// # fn main() {
+/// let fixture: Fixture = hrx::from_str("<===> input
+/// ul { li { list-style: none; } }
+///
+/// <===> options/precision
+/// 5
+/// ").unwrap();
+/// assert_eq!(fixture,
+///            Fixture {
+///                input: "ul { li { list-style: none; } }\n".to_string(),
+///                output: None,
+///                options: vec![("precision".to_string(), 5)].into_iter().collect(),
+///            });
+/// # }
+/// ```
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, HrxSerdeError> {
+    from_archive(&HrxArchive::from_str(s)?)
+}
+
+/// Deserialise a value from the entries of the specified archive, borrowing from it if possible.
+///
+/// This is the inverse of [`to_archive()`](fn.to_archive.html):
+///
+///   * directories, explicit or implicit, can be deserialised as structs and maps, keyed by their entries' names,
+///     or sequences and tuples of their entries, in archive order,
+///   * files can be deserialised as strings, characters, numbers, and booleans, parsed from their contents,
+///     with surrounding whitespace ignored for the latter three,
+///   * [`Binary`](enum.HrxEntryData.html#variant.Binary) files can only be deserialised as bytes,
+///   * missing entries are `None`, and existing ones are `Some`,
+///   * enum variants are either files containing the variant name, or directories containing a single entry named after it.
+///
+/// Comments are ignored.
+///
+/// Only available with the `serde` feature.
+pub fn from_archive<'a, T: Deserialize<'a>>(archive: &'a HrxArchive) -> Result<T, HrxSerdeError> {
+    T::deserialize(NodeDeserializer { node: &archive.tree() })
+}
+
+
+/// Deserialises a file or a directory, or the whole archive at the root
+struct NodeDeserializer<'a, 't> {
+    node: &'t HrxTree<'a>,
+}
+
+/// Deserialises a file body or an entry name
+struct ScalarDeserializer<'a>(&'a str);
+
+/// The entries of a directory, as a map or as a sequence
+struct DirectoryAccess<'a, 't> {
+    children: Values<'t, &'a str, HrxTree<'a>>,
+    value: Option<&'t HrxTree<'a>>,
+}
+
+/// A directory containing a single entry named after the variant
+struct VariantAccess<'a, 't> {
+    node: &'t HrxTree<'a>,
+}
+
+
+impl<'a, 't> NodeDeserializer<'a, 't> {
+    fn body(&self) -> Option<&'a str> {
+        match self.node.entry() {
+            Some(&HrxEntry { data: HrxEntryData::File { ref body }, .. }) => Some(body.as_ref().map(|b| &b[..]).unwrap_or("")),
+            _ => None,
+        }
+    }
+
+    fn binary(&self) -> Option<&'a [u8]> {
+        match self.node.entry() {
+            Some(&HrxEntry { data: HrxEntryData::Binary { ref body }, .. }) => Some(body),
+            _ => None,
+        }
+    }
+
+    fn directory(&self) -> DirectoryAccess<'a, 't> {
+        DirectoryAccess {
+            children: self.node.children(),
+            value: None,
+        }
+    }
+}
+
+
+macro_rules! forward_to_file {
+    ($($fn:ident,)*) => {
+        $(
+            fn $fn<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+                if let Some(body) = self.binary() {
+                    return BorrowedBytesDeserializer::<HrxSerdeError>::new(body).$fn(visitor);
+                }
+                match self.body() {
+                    Some(body) => ScalarDeserializer(body).$fn(visitor),
+                    None => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    }
+}
+
+impl<'a, 't> de::Deserializer<'a> for NodeDeserializer<'a, 't> {
+    type Error = HrxSerdeError;
+
+    forward_to_file! {
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_unit,
+        deserialize_identifier,
+    }
+
+    fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        if let Some(body) = self.binary() {
+            return visitor.visit_borrowed_bytes(body);
+        }
+        match self.body() {
+            Some(body) => visitor.visit_borrowed_str(body),
+            None => visitor.visit_map(self.directory()),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'a>>(self, _: &'static str, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'a>>(self, _: &'static str, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        if let Some(body) = self.binary() {
+            return visitor.visit_borrowed_bytes(body);
+        }
+        match self.body() {
+            Some(body) => visitor.visit_borrowed_str(body),
+            None => visitor.visit_seq(self.directory()),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'a>>(self, _: usize, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'a>>(self, _: &'static str, _: usize, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'a>>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, HrxSerdeError> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'a>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, HrxSerdeError> {
+        if let Some(body) = self.binary() {
+            return visitor.visit_borrowed_bytes(body);
+        }
+        if let Some(body) = self.body() {
+            return ScalarDeserializer(body).deserialize_enum(name, variants, visitor);
+        }
+
+        let mut children = self.node.children();
+        match (children.next(), children.next()) {
+            (Some(variant), None) => visitor.visit_enum(VariantAccess { node: variant }),
+            _ => Err(de::Error::invalid_length(self.node.children().len(), &"a directory with a single entry named after the variant")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_unit()
+    }
+}
+
+
+macro_rules! deserialize_parsed {
+    ($($fn:ident => $visit:ident,)*) => {
+        $(
+            fn $fn<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+                match self.0.trim().parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(_) => Err(de::Error::invalid_value(Unexpected::Str(self.0), &visitor)),
+                }
+            }
+        )*
+    }
+}
+
+impl<'a> de::Deserializer<'a> for ScalarDeserializer<'a> {
+    type Error = HrxSerdeError;
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    forward_to_deserialize_any! {
+        <V: Visitor<'a>>
+        str string identifier seq tuple tuple_struct map struct
+    }
+
+    fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_char<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        // Whitespace is only trimmed if it isn't the character itself
+        let mut chars = self.0.chars();
+        let mut trimmed = self.0.trim().chars();
+        match ((chars.next(), chars.next()), (trimmed.next(), trimmed.next())) {
+            ((Some(c), None), _) |
+            (_, (Some(c), None)) => visitor.visit_char(c),
+            _ => Err(de::Error::invalid_value(Unexpected::Str(self.0), &visitor)),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_borrowed_bytes(self.0.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_borrowed_bytes(self.0.as_bytes())
+    }
+
+    fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'a>>(self, _: &'static str, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'a>>(self, _: &'static str, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'a>>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.0.trim()))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        visitor.visit_unit()
+    }
+}
+
+
+impl<'a, 't> MapAccess<'a> for DirectoryAccess<'a, 't> {
+    type Error = HrxSerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'a>>(&mut self, seed: K) -> Result<Option<K::Value>, HrxSerdeError> {
+        match self.children.next() {
+            Some(child) => {
+                self.value = Some(child);
+                seed.deserialize(ScalarDeserializer(child.name())).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'a>>(&mut self, seed: V) -> Result<V::Value, HrxSerdeError> {
+        let node = self.value.take().expect("next_value_seed() called before next_key_seed()");
+        seed.deserialize(NodeDeserializer { node: node })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.children.len())
+    }
+}
+
+impl<'a, 't> SeqAccess<'a> for DirectoryAccess<'a, 't> {
+    type Error = HrxSerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'a>>(&mut self, seed: T) -> Result<Option<T::Value>, HrxSerdeError> {
+        match self.children.next() {
+            Some(child) => seed.deserialize(NodeDeserializer { node: child }).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.children.len())
+    }
+}
+
+impl<'a, 't> EnumAccess<'a> for VariantAccess<'a, 't> {
+    type Error = HrxSerdeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'a>>(self, seed: V) -> Result<(V::Value, Self), HrxSerdeError> {
+        Ok((seed.deserialize(BorrowedStrDeserializer::<HrxSerdeError>::new(self.node.name()))?, self))
+    }
+}
+
+impl<'a, 't> de::VariantAccess<'a> for VariantAccess<'a, 't> {
+    type Error = HrxSerdeError;
+
+    fn unit_variant(self) -> Result<(), HrxSerdeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'a>>(self, seed: T) -> Result<T::Value, HrxSerdeError> {
+        seed.deserialize(NodeDeserializer { node: self.node })
+    }
+
+    fn tuple_variant<V: Visitor<'a>>(self, _: usize, visitor: V) -> Result<V::Value, HrxSerdeError> {
+        de::Deserializer::deserialize_seq(NodeDeserializer { node: self.node }, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'a>>(self, _: &'static [&'static str], visitor: V) -> Result<V::Value, HrxSerdeError> {
+        de::Deserializer::deserialize_map(NodeDeserializer { node: self.node }, visitor)
+    }
+}
@@ -0,0 +1,453 @@
+use self::super::{HrxEntryData, HrxArchive, HrxEntry, HrxPath};
+use linked_hash_map::LinkedHashMap;
+use std::ops::{IndexMut, Index, Range};
+use std::fmt::Write;
+use std::{cmp, iter};
+
+
+/// Structural differences between two archives, as returned by [`HrxArchive::diff()`](struct.HrxArchive.html#method.diff).
+///
+/// Only the comments and entries are compared, so archives differing solely in their boundary lengths are considered equal.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxEntryDiff, HrxArchive, HrxChange, HrxPath};
+/// # use std::str::FromStr;
+/// let old = HrxArchive::from_str("<===> input.scss
+/// ul { li { list-style: none; } }
+///
+/// <===> out/
+/// <===>
+/// Generated by hand
+/// <===> script.js
+/// ").unwrap();
+/// let new = HrxArchive::from_str("<====> input.scss
+/// ul { li { list-style: none; margin: 0; } }
+///
+/// <====> out/input.css
+/// ul li { list-style: none; margin: 0; }
+///
+/// <====>
+/// Generated by sassc
+/// <====> script.js
+/// ").unwrap();
+///
+/// let diff = old.diff(&new);
+/// assert!(!diff.is_empty());
+/// assert_eq!(diff.comment, None);
+/// assert_eq!(diff.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["input.scss", "out", "script.js", "out/input.css"]);
+///
+/// let out = HrxPath::from_str("out").unwrap();
+/// assert_eq!(diff.entries[&out], HrxEntryDiff::Removed(old.entries[&out].clone()));
+/// assert_eq!(diff.entries[&HrxPath::from_str("script.js").unwrap()],
+///            HrxEntryDiff::Modified {
+///                comment: Some(HrxChange {
+///                    old: Some("Generated by hand".to_string()),
+///                    new: Some("Generated by sassc".to_string()),
+///                }),
+///                data: None,
+///            });
+///
+/// assert_eq!(diff.unified(0), r#"--- a/input.scss
+/// +++ b/input.scss
+/// @@ -1 +1 @@
+/// -ul { li { list-style: none; } }
+/// +ul { li { list-style: none; margin: 0; } }
+/// --- /dev/null
+/// +++ b/out/input.css
+/// @@ -0,0 +1 @@
+/// +ul li { list-style: none; margin: 0; }
+/// "#);
+///
+/// assert!(old.diff(&old).is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HrxDiff {
+    /// The old and new archive comments, if they differ
+    pub comment: Option<HrxChange<Option<String>>>,
+    /// Differing entries with their paths: removed and modified ones in the old archive's order, followed by added ones in the new archive's order
+    pub entries: LinkedHashMap<HrxPath, HrxEntryDiff>,
+}
+
+/// The difference between two entries with the same path.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HrxEntryDiff {
+    /// The entry exists only in the new archive
+    Added(HrxEntry),
+    /// The entry exists only in the old archive
+    Removed(HrxEntry),
+    /// The entry exists in both archives, but differs
+    Modified {
+        /// The old and new comments, if they differ
+        comment: Option<HrxChange<Option<String>>>,
+        /// The old and new data, if they differ, be it in kind or in contents
+        data: Option<HrxChange<HrxEntryData>>,
+    },
+}
+
+/// An old value and the new one it was changed into.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxChange<T> {
+    /// The value in the old archive
+    pub old: T,
+    /// The value in the new archive
+    pub new: T,
+}
+
+/// A single step in transforming the old lines into the new ones, with the indices into both at that point
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Edit {
+    Equal(usize, usize),
+    Delete(usize, usize),
+    Insert(usize, usize),
+}
+
+/// The furthest x reached on each diagonal k, i.e. where x - y = k, indexed by k
+struct Diagonals {
+    offset: isize,
+    xs: Vec<isize>,
+}
+
+
+pub fn diff_archives(old: &HrxArchive, new: &HrxArchive) -> HrxDiff {
+    let mut entries = LinkedHashMap::new();
+
+    for (path, old_entry) in &old.entries {
+        match new.entries.get(path) {
+            Some(new_entry) => {
+                if old_entry != new_entry {
+                    entries.insert(path.clone(),
+                                   HrxEntryDiff::Modified {
+                                       comment: change(&old_entry.comment, &new_entry.comment),
+                                       data: change(&old_entry.data, &new_entry.data),
+                                   });
+                }
+            }
+            None => {
+                entries.insert(path.clone(), HrxEntryDiff::Removed(old_entry.clone()));
+            }
+        }
+    }
+
+    for (path, new_entry) in &new.entries {
+        if !old.entries.contains_key(path) {
+            entries.insert(path.clone(), HrxEntryDiff::Added(new_entry.clone()));
+        }
+    }
+
+    HrxDiff {
+        comment: change(&old.comment, &new.comment),
+        entries: entries,
+    }
+}
+
+fn change<T: Clone + PartialEq>(old: &T, new: &T) -> Option<HrxChange<T>> {
+    if old != new {
+        Some(HrxChange {
+            old: old.clone(),
+            new: new.clone(),
+        })
+    } else {
+        None
+    }
+}
+
+
+impl HrxDiff {
+    /// Check whether the archives were equal, save for their boundary lengths.
+    pub fn is_empty(&self) -> bool {
+        self.comment.is_none() && self.entries.is_empty()
+    }
+
+    /// Get the [unified diffs](enum.HrxEntryDiff.html#method.unified) of the bodies of all differing entries, in order.
+    pub fn unified(&self, context: usize) -> String {
+        self.entries.iter().filter_map(|(path, entry)| entry.unified(path, context)).collect()
+    }
+}
+
+impl HrxEntryDiff {
+    /// Check whether the entry was changed from a file into a directory or vice versa.
+    pub fn is_kind_change(&self) -> bool {
+        match *self {
+            HrxEntryDiff::Modified { data: Some(ref data), .. } => (data.old == HrxEntryData::Directory) != (data.new == HrxEntryData::Directory),
+            _ => false,
+        }
+    }
+
+    /// Get a line-level unified diff of the file's body, with the specified amount of lines of context around each change.
+    ///
+    /// Directories, be it on one or on both sides, are treated as `/dev/null`.
+    /// If either side is a [`Binary`](enum.HrxEntryData.html#variant.Binary) file, there are no lines to compare,
+    /// so a `Binary files a/path and b/path differ` line is returned instead, like from git.
+    ///
+    /// Returns `None` if the bodies don't differ, e.g. if only the comment does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxArchive, HrxPath};
+    /// # use std::str::FromStr;
+    /// let old = HrxArchive::from_str("<===> list.txt\n1\n2\n3\n4\n5\n6\n7\n8\n9").unwrap();
+    /// let new = HrxArchive::from_str("<===> list.txt\n1\n2\nthree\n4\n5\n6\n7\n8\n9\n").unwrap();
+    ///
+    /// let path = HrxPath::from_str("list.txt").unwrap();
+    /// assert_eq!(old.diff(&new).entries[&path].unified(&path, 1).unwrap(), r#"--- a/list.txt
+    /// +++ b/list.txt
+    /// @@ -2,3 +2,3 @@
+    ///  2
+    /// -3
+    /// +three
+    ///  4
+    /// @@ -8,2 +8,2 @@
+    ///  8
+    /// -9
+    /// \ No newline at end of file
+    /// +9
+    /// "#);
+    /// ```
+    pub fn unified(&self, path: &HrxPath, context: usize) -> Option<String> {
+        let (old, new) = match *self {
+            HrxEntryDiff::Added(ref entry) => (None, Some(&entry.data)),
+            HrxEntryDiff::Removed(ref entry) => (Some(&entry.data), None),
+            HrxEntryDiff::Modified { data: Some(ref data), .. } => (Some(&data.old), Some(&data.new)),
+            HrxEntryDiff::Modified { data: None, .. } => return None,
+        };
+
+        if old.iter().chain(new.iter()).any(|d| matches!(**d, HrxEntryData::Binary { .. })) {
+            let name = |data: Option<&HrxEntryData>, side| match data {
+                Some(&HrxEntryData::Directory) | None => "/dev/null".to_string(),
+                Some(_) => format!("{}/{}", side, path),
+            };
+            return Some(format!("Binary files {} and {} differ\n", name(old, "a"), name(new, "b")));
+        }
+
+        let (old, new) = (old.and_then(data_body), new.and_then(data_body));
+        if old.is_none() && new.is_none() {
+            return None;
+        }
+
+        let old_lines: Vec<_> = old.unwrap_or("").split_inclusive('\n').collect();
+        let new_lines: Vec<_> = new.unwrap_or("").split_inclusive('\n').collect();
+        let edits = diff_lines(&old_lines, &new_lines);
+
+        let mut out = String::new();
+        match old {
+            Some(_) => writeln!(out, "--- a/{}", path).unwrap(),
+            None => out.push_str("--- /dev/null\n"),
+        }
+        match new {
+            Some(_) => writeln!(out, "+++ b/{}", path).unwrap(),
+            None => out.push_str("+++ /dev/null\n"),
+        }
+        write_hunks(&edits, &old_lines, &new_lines, context, &mut out);
+
+        Some(out)
+    }
+}
+
+impl Edit {
+    /// Whether this is a line from the old lines
+    fn in_old(&self) -> bool {
+        !matches!(*self, Edit::Insert(..))
+    }
+
+    /// Whether this is a line from the new lines
+    fn in_new(&self) -> bool {
+        !matches!(*self, Edit::Delete(..))
+    }
+}
+
+impl Diagonals {
+    /// Room for all diagonals reachable in `max_d` rounds, and their neighbours
+    fn new(max_d: usize) -> Diagonals {
+        Diagonals {
+            offset: max_d as isize + 1,
+            xs: vec![0; 2 * max_d + 3],
+        }
+    }
+}
+
+impl Index<isize> for Diagonals {
+    type Output = isize;
+
+    fn index(&self, k: isize) -> &isize {
+        &self.xs[(k + self.offset) as usize]
+    }
+}
+
+impl IndexMut<isize> for Diagonals {
+    fn index_mut(&mut self, k: isize) -> &mut isize {
+        &mut self.xs[(k + self.offset) as usize]
+    }
+}
+
+/// Get the body of a text file, counting an absent one as empty, or `None` for binary files and directories
+fn data_body(data: &HrxEntryData) -> Option<&str> {
+    match *data {
+        HrxEntryData::File { ref body } => Some(body.as_deref().unwrap_or("")),
+        HrxEntryData::Binary { .. } |
+        HrxEntryData::Directory => None,
+    }
+}
+
+/// Find a shortest edit script turning `old` into `new`, per Myers' "An O(ND) Difference Algorithm and Its Variations",
+/// using its linear space refinement, so only O(N + M) memory is needed however different the lines are
+///
+/// Within each run of changes, deletions come before insertions.
+pub fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let max_d = (old.len() + new.len()) / 2 + 1;
+    let mut forward = Diagonals::new(max_d);
+    let mut backward = Diagonals::new(max_d);
+    let mut matches = vec![];
+    match_lines(old, new, 0..old.len(), 0..new.len(), &mut forward, &mut backward, &mut matches);
+
+    let mut edits = vec![];
+    let (mut x, mut y) = (0, 0);
+    for (match_x, match_y) in matches.into_iter().chain(iter::once((old.len(), new.len()))) {
+        edits.extend((x..match_x).map(|x| Edit::Delete(x, y)));
+        edits.extend((y..match_y).map(|y| Edit::Insert(match_x, y)));
+        if match_x < old.len() {
+            edits.push(Edit::Equal(match_x, match_y));
+        }
+
+        x = match_x + 1;
+        y = match_y + 1;
+    }
+    edits
+}
+
+/// Collect the indices of the lines a shortest edit script between the specified ranges keeps, in order
+fn match_lines(old: &[&str], new: &[&str], mut old_range: Range<usize>, mut new_range: Range<usize>, forward: &mut Diagonals, backward: &mut Diagonals,
+               matches: &mut Vec<(usize, usize)>) {
+    while !old_range.is_empty() && !new_range.is_empty() && old[old_range.start] == new[new_range.start] {
+        matches.push((old_range.start, new_range.start));
+        old_range.start += 1;
+        new_range.start += 1;
+    }
+
+    let mut common_suffix = 0;
+    while !old_range.is_empty() && !new_range.is_empty() && old[old_range.end - 1] == new[new_range.end - 1] {
+        old_range.end -= 1;
+        new_range.end -= 1;
+        common_suffix += 1;
+    }
+
+    // With either range empty, what's left is all deletions or all insertions
+    if !old_range.is_empty() && !new_range.is_empty() {
+        let (x, y) = middle_snake(old, new, old_range.clone(), new_range.clone(), forward, backward);
+        match_lines(old, new, old_range.start..x, new_range.start..y, forward, backward, matches);
+        match_lines(old, new, x..old_range.end, y..new_range.end, forward, backward, matches);
+    }
+
+    matches.extend((0..common_suffix).map(|i| (old_range.end + i, new_range.end + i)));
+}
+
+/// Find a point on a shortest edit script between the specified non-empty ranges, about halfway through it,
+/// by searching from both ends at once until the paths overlap
+///
+/// The ranges' first lines, as well as their last ones, mustn't be equal,
+/// so that the point splits the script into two strictly shorter ones.
+fn middle_snake(old: &[&str], new: &[&str], old_range: Range<usize>, new_range: Range<usize>, forward: &mut Diagonals, backward: &mut Diagonals)
+                -> (usize, usize) {
+    let (n, m) = (old_range.len() as isize, new_range.len() as isize);
+    // Diagonal k going forward is diagonal delta - k going backward
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+
+    forward[1] = 0;
+    backward[1] = 0;
+    for d in 0..=(n + m + 1) / 2 {
+        for k in (-d..=d).step_by(2) {
+            let start_x = if k == -d || (k != d && forward[k - 1] < forward[k + 1]) {
+                forward[k + 1]
+            } else {
+                forward[k - 1] + 1
+            };
+            let start_y = start_x - k;
+
+            let (mut x, mut y) = (start_x, start_y);
+            while x < n && y < m && old[old_range.start + x as usize] == new[new_range.start + y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[k] = x;
+
+            if odd && (delta - k).abs() < d && x + backward[delta - k] >= n {
+                return (old_range.start + start_x as usize, new_range.start + start_y as usize);
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[k - 1] < backward[k + 1]) {
+                backward[k + 1]
+            } else {
+                backward[k - 1] + 1
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && old[old_range.end - 1 - x as usize] == new[new_range.end - 1 - y as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[k] = x;
+
+            if !odd && (delta - k).abs() <= d && x + forward[delta - k] >= n {
+                return (old_range.end - x as usize, new_range.end - y as usize);
+            }
+        }
+    }
+
+    unreachable!("forward and backward paths always overlap by the time they're half the edit distance long")
+}
+
+fn write_hunks(edits: &[Edit], old: &[&str], new: &[&str], context: usize, out: &mut String) {
+    let changes: Vec<_> = edits.iter().enumerate().filter(|&(_, e)| !(e.in_old() && e.in_new())).map(|(i, _)| i).collect();
+
+    let mut change_i = 0;
+    while change_i < changes.len() {
+        let start = changes[change_i].saturating_sub(context);
+        let mut end = changes[change_i] + 1;
+        change_i += 1;
+
+        // Merge changes whose contexts would touch or overlap
+        while change_i < changes.len() && changes[change_i] - end <= 2 * context {
+            end = changes[change_i] + 1;
+            change_i += 1;
+        }
+        let end = cmp::min(end + context, edits.len());
+
+        let hunk = &edits[start..end];
+        let (old_start, new_start) = match hunk[0] {
+            Edit::Equal(o, n) | Edit::Delete(o, n) | Edit::Insert(o, n) => (o, n),
+        };
+        let old_len = hunk.iter().filter(|e| e.in_old()).count();
+        let new_len = hunk.iter().filter(|e| e.in_new()).count();
+
+        writeln!(out, "@@ -{} +{} @@", hunk_range(old_start, old_len), hunk_range(new_start, new_len)).unwrap();
+        for edit in hunk {
+            match *edit {
+                Edit::Equal(o, _) => write_line(' ', old[o], out),
+                Edit::Delete(o, _) => write_line('-', old[o], out),
+                Edit::Insert(_, n) => write_line('+', new[n], out),
+            }
+        }
+    }
+}
+
+/// Format a 0-based `start` and `len` the way unified diffs do
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        len => format!("{},{}", start + 1, len),
+    }
+}
+
+fn write_line(prefix: char, line: &str, out: &mut String) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt::{self, Write};
+use self::super::HrxPosition;
 use self::super::parse;
+use std::io::Error as IoError;
 use std::error::Error;
 use lazysort::Sorted;
 
@@ -25,21 +27,29 @@ use lazysort::Sorted;
 /// assert_eq!(err.to_string(), r#"Parse failed at 1:7 [position 6]: expected "\n", or " "."#);
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum HrxError {
     /// No valid HRX boundary found
     NoBoundary,
     /// An error occured during parsing
-    Parse(parse::ParseError),
+    Parse(#[cfg_attr(feature = "serde", serde(with = "self::super::serialisation::parse_error"))] parse::ParseError),
     /// Some `body`s were made to contain the archive boundary. Deserialising the archive wouldn't work as expected
     BodyContainsBoundary(Vec<ErroneousBodyPath>),
     /// Two entries share the same path
     DuplicateEntry(String),
     /// An entry attempted to use a file as a directory
     FileAsDirectory(String, String),
+    /// Writing the entry at the specified path would've escaped the target directory, e.g. via a symlink therein
+    PathEscapesTarget(String),
+    /// The file at the specified filesystem path couldn't be represented in an archive
+    Unrepresentable(String, UnrepresentableReason),
+    /// The specified filesystem path couldn't be converted into an archive path due to the specified component thereof
+    InvalidPathComponent(String, String, InvalidPathReason),
 }
 
 /// A path to a `body` which contains an invalid sequence
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ErroneousBodyPath {
     /// The root archive comment
     RootComment,
@@ -49,6 +59,159 @@ pub enum ErroneousBodyPath {
     EntryData(String),
 }
 
+/// The reason a file couldn't be represented in an archive
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnrepresentableReason {
+    /// The path isn't valid UTF-8
+    NonUtf8Path,
+    /// The path isn't a valid `HrxPath`, e.g. contains a `:` or `\\`
+    InvalidPath,
+    /// The contents aren't valid UTF-8
+    NonUtf8Contents,
+    /// The file is a symlink
+    Symlink,
+    /// The file is neither a regular file, a directory, nor a symlink, e.g. a device or a FIFO
+    SpecialFile,
+}
+
+/// The reason a filesystem path component couldn't be converted into an archive path one, or vice versa
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InvalidPathReason {
+    /// The path has no components at all
+    Empty,
+    /// The component is a Windows path prefix, e.g. a drive letter
+    Prefix,
+    /// The component is the root directory, i.e. the path is absolute
+    RootDir,
+    /// The component is `.`
+    CurDir,
+    /// The component is `..`
+    ParentDir,
+    /// The component isn't valid UTF-8
+    NonUtf8,
+    /// The component contains the specified character, which can't appear in an `HrxPath`,
+    /// or, for `<>"|?*`, in a Windows filesystem path
+    InvalidCharacter(char),
+    /// The component ends in the specified character, which Windows strips from filesystem paths
+    TrailingCharacter(char),
+    /// The component is a Windows reserved device name, like `CON` or `LPT1`, optionally followed by an extension
+    ReservedName,
+}
+
+/// A problem found by [`HrxArchive::parse_with_diagnostics()`](struct.HrxArchive.html#method.parse_with_diagnostics)
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxDiagnostic, HrxPosition, HrxError};
+/// let diag = HrxDiagnostic {
+///     position: HrxPosition {
+///         line: 3,
+///         column: 1,
+///         offset: 20,
+///     },
+///     error: HrxError::DuplicateEntry("file.txt".to_string()),
+/// };
+/// assert_eq!(diag.to_string(), "3:1: Duplicate entry: file.txt");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HrxDiagnostic {
+    /// Where the problem is in the archive source
+    pub position: HrxPosition,
+    /// What the problem is
+    pub error: HrxError,
+}
+
+/// A deviation from the spec accepted by [`HrxArchive::parse_with_warnings()`](struct.HrxArchive.html#method.parse_with_warnings)
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxWarning, HrxPosition, Deviation};
+/// let warn = HrxWarning {
+///     position: HrxPosition {
+///         line: 2,
+///         column: 6,
+///         offset: 22,
+///     },
+///     deviation: Deviation::TabAfterBoundary,
+/// };
+/// assert_eq!(warn.to_string(), "2:6: Tab after boundary");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HrxWarning {
+    /// Where the deviation starts in the archive source
+    pub position: HrxPosition,
+    /// What the deviation is
+    pub deviation: Deviation,
+}
+
+/// A deviation from the spec, each accepted if the corresponding [`Leniency`](struct.Leniency.html) field is set
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Deviation {
+    /// A U+FEFF BYTE ORDER MARK at the start of the archive
+    ByteOrderMark,
+    /// Blank lines before the first boundary
+    LeadingBlankLines,
+    /// Tabs between a boundary and a path
+    TabAfterBoundary,
+    /// Spaces or tabs after a directory's `/`
+    TrailingWhitespaceAfterDirectory,
+}
+
+/// Error returned by the HRX serde data format, i.e. [`to_string()`](fn.to_string.html) and [`from_str()`](fn.from_str.html).
+///
+/// Only available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxSerdeError, HrxError};
+/// assert_eq!(hrx::to_string(&"top-level string"),
+///            Err(HrxSerdeError::Unsupported("a scalar at the top level".to_string())));
+/// assert_eq!(hrx::from_str::<Vec<String>>("not an archive"),
+///            Err(HrxSerdeError::Hrx(HrxError::NoBoundary)));
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HrxSerdeError {
+    /// A `Serialize` or `Deserialize` implementation failed with the specified message
+    Custom(String),
+    /// The archive couldn't be parsed or put together
+    Hrx(HrxError),
+    /// The specified kind of value can't be represented as archive entries
+    Unsupported(String),
+}
+
+/// `IoError` is absolute garbage when it comes to usability when it's a variant
+pub(crate) enum CompoundError {
+    Hrx(HrxError),
+    Io(IoError),
+}
+
+impl From<HrxError> for CompoundError {
+    fn from(hrx: HrxError) -> CompoundError {
+        CompoundError::Hrx(hrx)
+    }
+}
+
+impl From<IoError> for CompoundError {
+    fn from(io: IoError) -> CompoundError {
+        CompoundError::Io(io)
+    }
+}
+
+impl From<CompoundError> for Result<HrxError, IoError> {
+    fn from(ce: CompoundError) -> Result<HrxError, IoError> {
+        match ce {
+            CompoundError::Hrx(hrx) => Ok(hrx),
+            CompoundError::Io(io) => Err(io),
+        }
+    }
+}
+
 
 impl From<parse::ParseError> for HrxError {
     fn from(pe: parse::ParseError) -> HrxError {
@@ -147,12 +310,90 @@ impl fmt::Display for HrxError {
                 fmt.write_str(&file)?;
                 fmt.write_str(" as a directrory.")?;
             }
+            &HrxError::PathEscapesTarget(ref path) => {
+                fmt.write_str("Entry at ")?;
+                fmt.write_str(&path)?;
+                fmt.write_str(" would be written outside the target directory.")?;
+            }
+            &HrxError::Unrepresentable(ref path, ref reason) => {
+                fmt.write_str(&path)?;
+                fmt.write_str(" can't be archived: ")?;
+                reason.fmt(fmt)?;
+                fmt.write_char('.')?;
+            }
+            &HrxError::InvalidPathComponent(ref path, ref component, ref reason) => {
+                write!(fmt, "{} not valid in HRX: component {:?} ", path, component)?;
+                reason.fmt(fmt)?;
+                fmt.write_char('.')?;
+            }
         }
 
         Ok(())
     }
 }
 
+#[cfg(feature = "serde")]
+impl fmt::Display for HrxSerdeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &HrxSerdeError::Custom(ref msg) => fmt.write_str(msg),
+            &HrxSerdeError::Hrx(ref err) => err.fmt(fmt),
+            &HrxSerdeError::Unsupported(ref what) => write!(fmt, "Can't represent {} in an archive.", what),
+        }
+    }
+}
+
+impl fmt::Display for HrxDiagnostic {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}: {}", self.position, self.error)
+    }
+}
+
+impl fmt::Display for HrxWarning {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}: {}", self.position, self.deviation)
+    }
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(match self {
+            &Deviation::ByteOrderMark => "Byte order mark before archive",
+            &Deviation::LeadingBlankLines => "Blank lines before first boundary",
+            &Deviation::TabAfterBoundary => "Tab after boundary",
+            &Deviation::TrailingWhitespaceAfterDirectory => "Whitespace after directory",
+        })
+    }
+}
+
+impl fmt::Display for UnrepresentableReason {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(match self {
+            &UnrepresentableReason::NonUtf8Path => "path not UTF-8",
+            &UnrepresentableReason::InvalidPath => "path not valid in HRX",
+            &UnrepresentableReason::NonUtf8Contents => "contents not UTF-8",
+            &UnrepresentableReason::Symlink => "is a symlink",
+            &UnrepresentableReason::SpecialFile => "not a regular file nor a directory",
+        })
+    }
+}
+
+impl fmt::Display for InvalidPathReason {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &InvalidPathReason::Empty => fmt.write_str("missing, as the path is empty"),
+            &InvalidPathReason::Prefix => fmt.write_str("is a path prefix"),
+            &InvalidPathReason::RootDir => fmt.write_str("is the root directory"),
+            &InvalidPathReason::CurDir => fmt.write_str("refers to the current directory"),
+            &InvalidPathReason::ParentDir => fmt.write_str("refers to the parent directory"),
+            &InvalidPathReason::NonUtf8 => fmt.write_str("not UTF-8"),
+            &InvalidPathReason::InvalidCharacter(c) => write!(fmt, "contains {:?}", c),
+            &InvalidPathReason::TrailingCharacter(c) => write!(fmt, "ends in {:?}", c),
+            &InvalidPathReason::ReservedName => fmt.write_str("is a reserved name"),
+        }
+    }
+}
+
 impl Error for HrxError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
@@ -161,3 +402,34 @@ impl Error for HrxError {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl Error for HrxSerdeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            &HrxSerdeError::Hrx(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<HrxError> for HrxSerdeError {
+    fn from(err: HrxError) -> HrxSerdeError {
+        HrxSerdeError::Hrx(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::ser::Error for HrxSerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> HrxSerdeError {
+        HrxSerdeError::Custom(msg.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::de::Error for HrxSerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> HrxSerdeError {
+        HrxSerdeError::Custom(msg.to_string())
+    }
+}
@@ -0,0 +1,145 @@
+use self::super::{BASE64_COMMENT, HrxEntryData, HrxArchive, HrxError, HrxPath};
+use self::super::binary::decode_base64;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Write};
+use self::super::error::CompoundError;
+use std::path::{PathBuf, Path};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+
+
+/// Configuration for [`HrxArchive::extract_to()`](struct.HrxArchive.html#method.extract_to).
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{ExtractOptions, OverwritePolicy};
+/// assert_eq!(ExtractOptions::default(),
+///            ExtractOptions {
+///                overwrite: OverwritePolicy::Error,
+///                dry_run: false,
+///                decode_base64: false,
+///            });
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExtractOptions {
+    /// What to do with files already present in the target directory.
+    pub overwrite: OverwritePolicy,
+    /// Don't touch the filesystem, only return what would've been written.
+    ///
+    /// Existing files and symlinks are still checked for.
+    pub dry_run: bool,
+    /// Decode the contents of files commented with [`BASE64_COMMENT`](constant.BASE64_COMMENT.html),
+    /// as packed with [`BinaryPolicy::Base64`](enum.BinaryPolicy.html#variant.Base64), instead of writing them out verbatim.
+    pub decode_base64: bool,
+}
+
+/// What to do when a file to be extracted already exists.
+///
+/// Existing directories are always reused.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OverwritePolicy {
+    /// Fail with an `AlreadyExists` I/O error
+    Error,
+    /// Leave the existing file be and don't include it in the manifest
+    Skip,
+    /// Replace the existing file's contents
+    Overwrite,
+}
+
+
+impl Default for ExtractOptions {
+    fn default() -> ExtractOptions {
+        ExtractOptions {
+            overwrite: OverwritePolicy::Error,
+            dry_run: false,
+            decode_base64: false,
+        }
+    }
+}
+
+
+pub fn extract_archive(ar: &HrxArchive, to: &Path, options: &ExtractOptions) -> Result<Vec<PathBuf>, Result<HrxError, IoError>> {
+    Ok(extract_archive_impl(ar, to, options)?)
+}
+
+fn extract_archive_impl(ar: &HrxArchive, to: &Path, options: &ExtractOptions) -> Result<Vec<PathBuf>, CompoundError> {
+    if !options.dry_run {
+        fs::create_dir_all(to)?;
+    }
+
+    let mut manifest = vec![];
+    let mut known_dirs = BTreeSet::new();
+
+    for (path, entry) in &ar.entries {
+        let relative = path.to_path_buf()?;
+        let mut out = to.to_path_buf();
+        let mut components = relative.components().peekable();
+
+        while let Some(component) = components.next() {
+            out.push(component);
+
+            if components.peek().is_some() || entry.data == HrxEntryData::Directory {
+                ensure_directory(&out, path, options, &mut known_dirs, &mut manifest)?;
+            }
+        }
+
+        match entry.data {
+            HrxEntryData::File { ref body } if options.decode_base64 && entry.comment.as_deref() == Some(BASE64_COMMENT) => {
+                let body = decode_base64(body.as_deref().unwrap_or(""))
+                    .ok_or_else(|| IoError::new(IoErrorKind::InvalidData, format!("{} is marked as base64, but isn't valid base64", path)))?;
+                write_file(&out, path, &body, options, &mut manifest)?;
+            }
+            HrxEntryData::File { ref body } => write_file(&out, path, body.as_ref().map(|b| b.as_bytes()).unwrap_or(b""), options, &mut manifest)?,
+            HrxEntryData::Binary { ref body } => write_file(&out, path, body, options, &mut manifest)?,
+            HrxEntryData::Directory => {}
+        }
+    }
+
+    Ok(manifest)
+}
+
+fn ensure_directory(dir: &Path, path: &HrxPath, options: &ExtractOptions, known_dirs: &mut BTreeSet<PathBuf>, manifest: &mut Vec<PathBuf>)
+                    -> Result<(), CompoundError> {
+    if known_dirs.contains(dir) {
+        return Ok(());
+    }
+
+    match fs::symlink_metadata(dir) {
+        Ok(ref meta) if meta.file_type().is_symlink() => return Err(HrxError::PathEscapesTarget(path.to_string()).into()),
+        Ok(ref meta) if meta.is_dir() => {}
+        Ok(_) => return Err(IoError::new(IoErrorKind::AlreadyExists, format!("{} exists and is not a directory", dir.display())).into()),
+        Err(ref err) if err.kind() == IoErrorKind::NotFound => {
+            if !options.dry_run {
+                fs::create_dir(dir)?;
+            }
+            manifest.push(dir.to_path_buf());
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    known_dirs.insert(dir.to_path_buf());
+    Ok(())
+}
+
+fn write_file(file: &Path, path: &HrxPath, body: &[u8], options: &ExtractOptions, manifest: &mut Vec<PathBuf>) -> Result<(), CompoundError> {
+    match fs::symlink_metadata(file) {
+        Ok(ref meta) if meta.file_type().is_symlink() => return Err(HrxError::PathEscapesTarget(path.to_string()).into()),
+        Ok(ref meta) if meta.is_dir() => return Err(IoError::new(IoErrorKind::AlreadyExists, format!("{} exists and is a directory", file.display())).into()),
+        Ok(_) => {
+            match options.overwrite {
+                OverwritePolicy::Error => return Err(IoError::new(IoErrorKind::AlreadyExists, format!("{} already exists", file.display())).into()),
+                OverwritePolicy::Skip => return Ok(()),
+                OverwritePolicy::Overwrite => {}
+            }
+        }
+        Err(ref err) if err.kind() == IoErrorKind::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    if !options.dry_run {
+        File::create(file)?.write_all(body)?;
+    }
+    manifest.push(file.to_path_buf());
+
+    Ok(())
+}
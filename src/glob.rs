@@ -0,0 +1,184 @@
+use self::super::{HrxEntry, HrxPath};
+use linked_hash_map;
+use std::iter;
+
+
+/// A shell-style pattern, matched against `/`-separated paths component-by-component.
+///
+/// Within a component, `*` matches any sequence of characters, `?` matches any single character,
+/// and `[...]` matches a single character from the class (`[!...]` or `[^...]` negate it, `a-z` specifies a range).
+/// A component consisting solely of `**` matches any amount (including zero) of path components.
+///
+/// A `[` without a matching `]` is treated as a literal character, hence every string is a valid pattern.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::Glob;
+/// let glob = Glob::new("out/**/*.[ch]ss");
+/// assert!(glob.matches("out/input.css"));
+/// assert!(glob.matches("out/nested/deeper/input.css"));
+/// assert!(!glob.matches("out/input.scss"));
+/// assert!(!glob.matches("input.css"));
+///
+/// assert!(Glob::new("?at").matches("cat"));
+/// assert!(!Glob::new("[!bc]at").matches("cat"));
+/// assert!(!Glob::new("*").matches("dir/file"));
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Glob {
+    components: Vec<GlobComponent>,
+}
+
+/// An iterator over the entries of an archive matching a glob, in archive order.
+///
+/// Returned by [`HrxArchive::glob()`](struct.HrxArchive.html#method.glob).
+#[derive(Clone)]
+pub struct GlobMatches<'a> {
+    glob: Glob,
+    entries: linked_hash_map::Iter<'a, HrxPath, HrxEntry>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+enum GlobComponent {
+    /// `**`
+    Recursive,
+    Pattern(Vec<GlobToken>),
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+enum GlobToken {
+    Literal(char),
+    /// `?`
+    AnyCharacter,
+    /// `*`
+    AnySequence,
+    /// `[...]`, with negation flag and inclusive ranges
+    Class(bool, Vec<(char, char)>),
+}
+
+
+impl Glob {
+    /// Compile the specified pattern.
+    pub fn new(pattern: &str) -> Glob {
+        Glob {
+            components: pattern.split('/')
+                .map(|comp| if comp == "**" {
+                    GlobComponent::Recursive
+                } else {
+                    GlobComponent::Pattern(parse_component(comp))
+                })
+                .collect(),
+        }
+    }
+
+    /// Check whether the specified `/`-separated path matches this pattern.
+    pub fn matches(&self, path: &str) -> bool {
+        let components: Vec<_> = path.split('/').collect();
+        match_components(&self.components, &components)
+    }
+}
+
+impl<'a> GlobMatches<'a> {
+    pub(crate) fn new(glob: Glob, entries: linked_hash_map::Iter<'a, HrxPath, HrxEntry>) -> GlobMatches<'a> {
+        GlobMatches {
+            glob: glob,
+            entries: entries,
+        }
+    }
+}
+
+impl<'a> Iterator for GlobMatches<'a> {
+    type Item = (&'a HrxPath, &'a HrxEntry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let glob = &self.glob;
+        self.entries.find(|&(p, _)| glob.matches(&p.0))
+    }
+}
+
+
+fn parse_component(comp: &str) -> Vec<GlobToken> {
+    let mut tokens = vec![];
+    let mut chars = comp.chars();
+
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '?' => GlobToken::AnyCharacter,
+            '*' => GlobToken::AnySequence,
+            '[' => {
+                match parse_class(chars.as_str()) {
+                    Some((class, rest)) => {
+                        chars = rest.chars();
+                        class
+                    }
+                    None => GlobToken::Literal('['),
+                }
+            }
+            c => GlobToken::Literal(c),
+        });
+    }
+
+    tokens
+}
+
+/// Parse the inside of a `[...]` class, returning it and the remainder of the component after the closing `]`.
+fn parse_class(from: &str) -> Option<(GlobToken, &str)> {
+    let mut chars = from.chars();
+
+    let negated = from.starts_with('!') || from.starts_with('^');
+    if negated {
+        chars.next();
+    }
+
+    let mut ranges = vec![];
+    let mut first = true;
+    while let Some(c) = chars.next() {
+        if c == ']' && !first {
+            return Some((GlobToken::Class(negated, ranges), chars.as_str()));
+        }
+        first = false;
+
+        let mut lookahead = chars.clone();
+        match (lookahead.next(), lookahead.next()) {
+            (Some('-'), Some(end)) if end != ']' => {
+                chars = lookahead;
+                ranges.push((c, end));
+            }
+            _ => ranges.push((c, c)),
+        }
+    }
+
+    None
+}
+
+fn match_components(pattern: &[GlobComponent], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&GlobComponent::Recursive, rest)) => (0..=path.len()).any(|i| match_components(rest, &path[i..])),
+        Some((&GlobComponent::Pattern(ref tokens), rest)) => !path.is_empty() && match_tokens(tokens, path[0]) && match_components(rest, &path[1..]),
+    }
+}
+
+fn match_tokens(pattern: &[GlobToken], s: &str) -> bool {
+    match pattern.split_first() {
+        None => s.is_empty(),
+        Some((&GlobToken::AnySequence, rest)) => s.char_indices().map(|(i, _)| i).chain(iter::once(s.len())).any(|i| match_tokens(rest, &s[i..])),
+        Some((token, rest)) => {
+            let mut chars = s.chars();
+            match chars.next() {
+                Some(c) if match_token(token, c) => match_tokens(rest, chars.as_str()),
+                _ => false,
+            }
+        }
+    }
+}
+
+fn match_token(token: &GlobToken, c: char) -> bool {
+    match token {
+        &GlobToken::Literal(l) => l == c,
+        &GlobToken::AnyCharacter => true,
+        &GlobToken::AnySequence => true,
+        &GlobToken::Class(negated, ref ranges) => ranges.iter().any(|&(from, to)| from <= c && c <= to) != negated,
+    }
+}
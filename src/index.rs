@@ -0,0 +1,127 @@
+use self::super::{ParseOptions, HrxEntryRef, HrxError, parse};
+use std::collections::{BTreeMap, HashMap};
+use self::super::parse::RawEntry;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+
+
+/// A random-access index of a Human-Readable Archive, recording just where each entry's parts are in the string it was built from.
+///
+/// Building one only parses the boundary lines, each once, skipping from one to the next with a substring search,
+/// and records the comments and bodies between them as byte ranges, which are only borrowed from the source when looked up.
+/// So it's preferable to [`HrxArchiveRef`](struct.HrxArchiveRef.html) when just a handful of entries of a large archive are needed.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxEntryDataRef, HrxEntryRef, HrxIndex, HrxPath};
+/// # use std::str::FromStr;
+/// let index = HrxIndex::build("<===> input.scss
+/// ul { li { list-style: none; } }
+///
+/// <===>
+/// Generated files
+/// <===> out/
+/// <===> out/input.css
+/// ul li { list-style: none; }
+/// ").unwrap();
+///
+/// assert_eq!(index.len(), 3);
+/// assert_eq!(index.paths(), &["input.scss", "out", "out/input.css"]);
+/// assert_eq!(index.get("out/input.css"),
+///            Some(HrxEntryRef {
+///                comment: None,
+///                data: HrxEntryDataRef::File { body: Some("ul li { list-style: none; }\n") },
+///            }));
+/// assert_eq!(index.get(HrxPath::from_str("out").unwrap()),
+///            Some(HrxEntryRef {
+///                comment: Some("Generated files"),
+///                data: HrxEntryDataRef::Directory,
+///            }));
+/// assert_eq!(index.get("out/output.css"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HrxIndex<'a> {
+    source: &'a str,
+    comment: Option<Range<usize>>,
+    paths: Vec<&'a str>,
+    entries: HashMap<&'a str, RawEntry>,
+    boundary_length: NonZeroUsize,
+}
+
+
+impl<'a> HrxIndex<'a> {
+    /// Index the archive in the specified string.
+    ///
+    /// Performs the same validation as [`HrxArchiveRef::parse()`](struct.HrxArchiveRef.html#method.parse) and returns the same errors;
+    /// finding out where an invalid archive fails to parse takes a full parse of it.
+    pub fn build(s: &'a str) -> Result<HrxIndex<'a>, HrxError> {
+        let width = parse::discover_first_boundary_length(s).ok_or(HrxError::NoBoundary)?;
+        let (comment, raw_entries) = match parse::archive_index(s, width) {
+            Some(scanned) => scanned,
+            None => {
+                let (archive, _) = parse::archive_bytes(s.as_bytes(), width, &ParseOptions::default())?;
+                archive
+            }
+        };
+
+        let mut known_paths = BTreeMap::<&str, bool>::new();
+        let mut paths = Vec::with_capacity(raw_entries.len());
+        let mut entries = HashMap::with_capacity(raw_entries.len());
+        for entry in raw_entries {
+            let path = &s[entry.path.clone()];
+            parse::validate_its_directory_tree(path, entry.body.is_none(), &mut known_paths)?;
+
+            paths.push(path);
+            entries.insert(path, entry);
+        }
+
+        Ok(HrxIndex {
+            source: s,
+            comment: comment,
+            paths: paths,
+            entries: entries,
+            boundary_length: width,
+        })
+    }
+
+    /// Get the string this index was built from.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Get the archive comment, if any.
+    pub fn comment(&self) -> Option<&'a str> {
+        self.comment.clone().map(|c| &self.source[c])
+    }
+
+    /// Get the boundary length, i.e. the amount of `=` characters in the boundary.
+    pub fn boundary_length(&self) -> NonZeroUsize {
+        self.boundary_length
+    }
+
+    /// Get the paths of all entries, in archive order.
+    pub fn paths(&self) -> &[&'a str] {
+        &self.paths
+    }
+
+    /// Get the amount of entries in the archive.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Check whether the archive has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Check whether the archive has an entry at the specified path.
+    pub fn contains<P: AsRef<str>>(&self, path: P) -> bool {
+        self.entries.contains_key(path.as_ref())
+    }
+
+    /// Get the entry at the specified path, if any, borrowing its comment and body from the source.
+    pub fn get<P: AsRef<str>>(&self, path: P) -> Option<HrxEntryRef<'a>> {
+        self.entries.get(path.as_ref()).map(|e| e.borrow_from(self.source).1)
+    }
+}
@@ -71,6 +71,15 @@
 //! # Ok::<(), HrxError>(())
 //! ```
 //!
+//! # Features
+//!
+//!   * `tar` – [`HrxArchive::from_tar()`](struct.HrxArchive.html#method.from_tar) and [`to_tar()`](struct.HrxArchive.html#method.to_tar)
+//!   * `zip` – [`HrxArchive::from_zip()`](struct.HrxArchive.html#method.from_zip) and [`to_zip()`](struct.HrxArchive.html#method.to_zip)
+//!   * `serde` – `Serialize` and `Deserialize` implementations for the archive model and errors,
+//!     and HRX itself as a data format, via [`to_string()`](fn.to_string.html) and [`from_str()`](fn.from_str.html)
+//!   * `mmap` – [`HrxArchive::open_mmap()`](struct.HrxArchive.html#method.open_mmap)
+//!   * `cli` – the `hrx` executable, which is otherwise not built, so that the library doesn't depend on `clap`
+//!
 //! # Special thanks
 //!
 //! To all who support further development on [Patreon](https://patreon.com/nabijaczleweli), in particular:
@@ -82,13 +91,72 @@ extern crate linked_hash_map;
 extern crate lazysort;
 #[macro_use]
 extern crate jetscii;
+#[cfg(feature = "tar")]
+extern crate tar;
+#[cfg(feature = "zip")]
+extern crate zip;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
 
 pub mod util;
 pub mod parse;
 
 mod repr;
 mod error;
+mod glob;
+mod pack;
 mod output;
+mod extract;
+mod binary;
+mod parse_options;
+mod borrowed;
+mod index;
+mod span;
+mod reader;
+mod writer;
+mod tree;
+mod diff;
+mod merge;
+#[cfg(feature = "tar")]
+mod tarball;
+#[cfg(feature = "zip")]
+mod zipfile;
+#[cfg(feature = "serde")]
+mod serialisation;
+#[cfg(feature = "serde")]
+mod ser;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "mmap")]
+mod mapped;
 
-pub use self::error::{ErroneousBodyPath, HrxError};
+pub use self::error::{UnrepresentableReason, InvalidPathReason, ErroneousBodyPath, HrxDiagnostic, HrxWarning, HrxError, Deviation};
+pub use self::pack::{UnrepresentablePolicy, SymlinkPolicy, BinaryPolicy, SkippedFiles, PackOptions, PackOrder};
+pub use self::extract::{ExtractOptions, OverwritePolicy};
+pub use self::binary::BASE64_COMMENT;
+pub use self::parse_options::{ParseOptions, LineEndings, Leniency};
 pub use self::repr::{HrxEntryData, HrxArchive, HrxEntry, HrxPath};
+pub use self::reader::HrxReader;
+pub use self::writer::HrxWriter;
+pub use self::tree::{HrxWalkOrder, HrxTree, HrxWalk};
+pub use self::glob::{GlobMatches, Glob};
+pub use self::diff::{HrxEntryDiff, HrxChange, HrxDiff};
+pub use self::merge::{MergeOptions, HrxConflict, HrxMerge, merge_with_options, merge};
+#[cfg(feature = "tar")]
+pub use self::tarball::TarOptions;
+#[cfg(feature = "zip")]
+pub use self::zipfile::ZipOptions;
+#[cfg(feature = "serde")]
+pub use self::error::HrxSerdeError;
+#[cfg(feature = "serde")]
+pub use self::ser::{to_archive, to_string};
+#[cfg(feature = "serde")]
+pub use self::de::{from_archive, from_str};
+#[cfg(feature = "mmap")]
+pub use self::mapped::HrxMmap;
+pub use self::span::{HrxArchiveSpans, HrxEntrySpans, HrxPosition, HrxSpan};
+pub use self::borrowed::{HrxEntryDataRef, HrxArchiveRef, HrxEntryRef};
+pub use self::index::HrxIndex;
@@ -0,0 +1,37 @@
+use self::super::{HrxArchiveRef, HrxError};
+use std::io::Error as IoError;
+use memmap2::Mmap;
+use std::path::Path;
+use std::fs::File;
+
+
+/// A memory-mapped archive file, returned by [`HrxArchive::open_mmap()`](struct.HrxArchive.html#method.open_mmap).
+///
+/// Only available with the `mmap` feature.
+#[derive(Debug)]
+pub struct HrxMmap {
+    map: Mmap,
+}
+
+
+impl HrxMmap {
+    /// Parse the mapped file into an archive borrowing from it.
+    ///
+    /// Equivalent to [`HrxArchiveRef::from_bytes()`](struct.HrxArchiveRef.html#method.from_bytes) on the file's contents.
+    pub fn archive(&self) -> Result<HrxArchiveRef<'_>, HrxError> {
+        HrxArchiveRef::from_bytes(&self.map)
+    }
+
+    /// Get the contents of the mapped file.
+    pub fn data(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+
+/// Safety: as for [`HrxArchive::open_mmap()`](struct.HrxArchive.html#method.open_mmap)
+pub unsafe fn map_file(path: &Path) -> Result<HrxMmap, IoError> {
+    let file = File::open(path)?;
+    let map = Mmap::map(&file)?;
+    Ok(HrxMmap { map: map })
+}
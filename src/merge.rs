@@ -0,0 +1,307 @@
+use self::super::{HrxEntryData, HrxArchive, HrxEntry, HrxError, HrxPath, parse};
+use self::super::diff::{Edit, diff_lines};
+use linked_hash_map::LinkedHashMap;
+use std::collections::BTreeMap;
+
+
+/// Configuration for [`merge_with_options()`](fn.merge_with_options.html).
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::MergeOptions;
+/// assert_eq!(MergeOptions::default(),
+///            MergeOptions {
+///                marker_size: 7,
+///                ours_label: "ours".to_string(),
+///                theirs_label: "theirs".to_string(),
+///            });
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MergeOptions {
+    /// How many characters the `<<<<<<<`, `=======`, and `>>>>>>>` conflict markers consist of.
+    pub marker_size: usize,
+    /// What to put after the `<<<<<<<` marker opening our side of a conflict.
+    pub ours_label: String,
+    /// What to put after the `>>>>>>>` marker closing their side of a conflict.
+    pub theirs_label: String,
+}
+
+/// The result of a three-way merge, as returned by [`merge()`](fn.merge.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HrxMerge {
+    /// The merged archive.
+    ///
+    /// Conflicting comments and file bodies are merged line-by-line, with conflict markers around the clashing lines.
+    /// Otherwise conflicting entries are taken from our side, or from theirs, if we removed them.
+    ///
+    /// The boundary length is kept from our side, unless the merged contents require a longer one.
+    pub archive: HrxArchive,
+    /// Whether the archive comment conflicted
+    pub comment_conflict: bool,
+    /// Conflicting entries with their paths, in merged archive order
+    pub conflicts: LinkedHashMap<HrxPath, HrxConflict>,
+}
+
+/// The versions of a conflicting entry in all three archives.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxConflict {
+    /// The entry in the common ancestor
+    pub base: Option<HrxEntry>,
+    /// The entry on our side
+    pub ours: Option<HrxEntry>,
+    /// The entry on their side
+    pub theirs: Option<HrxEntry>,
+}
+
+
+impl Default for MergeOptions {
+    fn default() -> MergeOptions {
+        MergeOptions {
+            marker_size: 7,
+            ours_label: "ours".to_string(),
+            theirs_label: "theirs".to_string(),
+        }
+    }
+}
+
+impl HrxMerge {
+    /// Check whether the merge had no conflicts.
+    pub fn is_clean(&self) -> bool {
+        !self.comment_conflict && self.conflicts.is_empty()
+    }
+}
+
+
+/// Merge the changes made in `ours` and `theirs` relative to their common ancestor `base`, with the default options.
+///
+/// Entries are merged individually: an entry changed (be it added, removed, or modified) on only one side takes that side's version,
+/// and entries changed on both sides have their comments and file bodies merged line-by-line, like `diff3` does.
+///
+/// Conflicts arise when both sides change the same lines, when one side removes an entry the other modifies,
+/// when one side makes an entry a file and the other a directory,
+/// and when one side uses as a directory a path the other adds a file at.
+///
+/// The merged entries are in our order, followed by ones added only on their side, in their order.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxArchive, merge};
+/// # use std::str::FromStr;
+/// let base = HrxArchive::from_str("<===> colours.txt\nred\ngreen\nblue\n\n<===> sizes.txt\nsmall\n").unwrap();
+/// let ours = HrxArchive::from_str("<===> colours.txt\nred\ngreen\nindigo\n\n<===> sizes.txt\nlarge\n").unwrap();
+/// let theirs = HrxArchive::from_str("<===> colours.txt\nmagenta\ngreen\nblue\n\n<===> sizes.txt\nhuge\n\n<===> new.txt\n").unwrap();
+///
+/// let merged = merge(&base, &ours, &theirs);
+/// assert!(!merged.is_clean());
+/// assert_eq!(merged.conflicts.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["sizes.txt"]);
+///
+/// let mut out = vec![];
+/// merged.archive.serialise(&mut out).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "<===> colours.txt
+/// magenta
+/// green
+/// indigo
+///
+/// <===> sizes.txt
+/// <<<<<<< ours
+/// large
+/// =======
+/// huge
+/// >>>>>>> theirs
+///
+/// <===> new.txt
+/// ");
+/// ```
+pub fn merge(base: &HrxArchive, ours: &HrxArchive, theirs: &HrxArchive) -> HrxMerge {
+    merge_with_options(base, ours, theirs, &MergeOptions::default())
+}
+
+/// Like [`merge()`](fn.merge.html), but with the specified conflict marker configuration.
+pub fn merge_with_options(base: &HrxArchive, ours: &HrxArchive, theirs: &HrxArchive, options: &MergeOptions) -> HrxMerge {
+    let mut archive = HrxArchive::new(ours.boundary_length);
+    let mut conflicts = LinkedHashMap::new();
+
+    let (comment, comment_conflict) = merge_optional_text(base.comment.as_deref(), ours.comment.as_deref(), theirs.comment.as_deref(), options);
+    archive.comment = comment;
+
+    let mut paths = BTreeMap::<String, _>::new();
+    for path in ours.entries.keys().chain(theirs.entries.keys().filter(|p| !ours.entries.contains_key(*p))) {
+        let (b, o, t) = (base.entries.get(path), ours.entries.get(path), theirs.entries.get(path));
+        let (entry, mut conflict) = merge_entry(b, o, t, options);
+
+        if let Some(entry) = entry {
+            match parse::validate_its_directory_tree(&path.0, entry.data == HrxEntryData::Directory, &mut paths) {
+                Ok(()) => {
+                    archive.entries.insert(path.clone(), entry);
+                }
+                // An explicit directory, after the entries inside it, is already there implicitly
+                Err(HrxError::DuplicateEntry(_)) if entry.comment.is_none() && entry.data == HrxEntryData::Directory && paths.get(&path.0[..]) == Some(&true) => {}
+                Err(_) => conflict = true,
+            }
+        }
+
+        if conflict {
+            conflicts.insert(path.clone(),
+                             HrxConflict {
+                                 base: b.cloned(),
+                                 ours: o.cloned(),
+                                 theirs: t.cloned(),
+                             });
+        }
+    }
+
+    archive.boundary_length = archive.minimal_boundary_length_from(ours.boundary_length);
+
+    HrxMerge {
+        archive: archive,
+        comment_conflict: comment_conflict,
+        conflicts: conflicts,
+    }
+}
+
+/// Returns the merged entry, if any, and whether it conflicted
+fn merge_entry(base: Option<&HrxEntry>, ours: Option<&HrxEntry>, theirs: Option<&HrxEntry>, options: &MergeOptions) -> (Option<HrxEntry>, bool) {
+    if ours == theirs || theirs == base {
+        return (ours.cloned(), false);
+    }
+    if ours == base {
+        return (theirs.cloned(), false);
+    }
+
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) => {
+            let (comment, comment_conflict) = merge_optional_text(base.and_then(|b| b.comment.as_deref()),
+                                                                  ours.comment.as_deref(),
+                                                                  theirs.comment.as_deref(),
+                                                                  options);
+            let (data, data_conflict) = match (&ours.data, &theirs.data) {
+                (&HrxEntryData::File { body: ref ours_body }, &HrxEntryData::File { body: ref theirs_body }) => {
+                    let base_body = match base {
+                        Some(&HrxEntry { data: HrxEntryData::File { ref body }, .. }) => body.as_deref(),
+                        _ => None,
+                    };
+                    let (body, conflict) = merge_optional_text(base_body, ours_body.as_deref(), theirs_body.as_deref(), options);
+                    (HrxEntryData::File { body: body }, conflict)
+                }
+                (&HrxEntryData::Directory, &HrxEntryData::Directory) => (HrxEntryData::Directory, false),
+                // Can't put markers between a file and a directory, nor into binary contents
+                _ => (ours.data.clone(), true),
+            };
+
+            (Some(HrxEntry {
+                 comment: comment,
+                 data: data,
+             }),
+             comment_conflict || data_conflict)
+        }
+        // Modified on one side, removed on the other, so keep the modified version
+        (ours, theirs) => (ours.or(theirs).cloned(), true),
+    }
+}
+
+/// Merge comments or bodies, where `None` is equivalent to empty, returning the merged text and whether it conflicted
+fn merge_optional_text(base: Option<&str>, ours: Option<&str>, theirs: Option<&str>, options: &MergeOptions) -> (Option<String>, bool) {
+    if ours == theirs || theirs == base {
+        return (ours.map(str::to_string), false);
+    }
+    if ours == base {
+        return (theirs.map(str::to_string), false);
+    }
+
+    let (merged, conflict) = merge_text(base.unwrap_or(""), ours.unwrap_or(""), theirs.unwrap_or(""), options);
+    (if merged.is_empty() { None } else { Some(merged) }, conflict)
+}
+
+/// Merge the lines of `ours` and `theirs` relative to `base`, returning the result and whether it conflicted.
+///
+/// Base lines kept unchanged on both sides split the texts into chunks,
+/// each of which is taken from the side that changed it, or marked as conflicting if both sides did so differently.
+fn merge_text(base: &str, ours: &str, theirs: &str, options: &MergeOptions) -> (String, bool) {
+    let base: Vec<_> = base.split_inclusive('\n').collect();
+    let ours: Vec<_> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<_> = theirs.split_inclusive('\n').collect();
+
+    let ours_matches = base_matches(&base, &ours);
+    let theirs_matches = base_matches(&base, &theirs);
+
+    let mut out = String::new();
+    let mut conflict = false;
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        // Lines unchanged on both sides
+        while b < base.len() && ours_matches[b] == Some(o) && theirs_matches[b] == Some(t) {
+            out.push_str(base[b]);
+            b += 1;
+            o += 1;
+            t += 1;
+        }
+
+        let next_stable = (b..base.len()).find(|&i| ours_matches[i].is_some() && theirs_matches[i].is_some());
+        let (next_b, next_o, next_t) = match next_stable {
+            Some(i) => (i, ours_matches[i].unwrap(), theirs_matches[i].unwrap()),
+            None => (base.len(), ours.len(), theirs.len()),
+        };
+
+        let (base_chunk, ours_chunk, theirs_chunk) = (&base[b..next_b], &ours[o..next_o], &theirs[t..next_t]);
+        if ours_chunk == theirs_chunk || theirs_chunk == base_chunk {
+            out.extend(ours_chunk.iter().cloned());
+        } else if ours_chunk == base_chunk {
+            out.extend(theirs_chunk.iter().cloned());
+        } else {
+            conflict = true;
+            push_conflict(ours_chunk, theirs_chunk, options, &mut out);
+        }
+
+        if next_stable.is_none() {
+            break;
+        }
+        b = next_b;
+        o = next_o;
+        t = next_t;
+    }
+
+    (out, conflict)
+}
+
+/// For each base line, get the index of the line it's kept as on the specified side, if any
+fn base_matches(base: &[&str], side: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+    for edit in diff_lines(base, side) {
+        if let Edit::Equal(b, s) = edit {
+            matches[b] = Some(s);
+        }
+    }
+    matches
+}
+
+fn push_conflict(ours: &[&str], theirs: &[&str], options: &MergeOptions, out: &mut String) {
+    fn push_lines(lines: &[&str], out: &mut String) {
+        for line in lines {
+            out.push_str(line);
+        }
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+
+    out.extend((0..options.marker_size).map(|_| '<'));
+    out.push(' ');
+    out.push_str(&options.ours_label);
+    out.push('\n');
+    push_lines(ours, out);
+
+    out.extend((0..options.marker_size).map(|_| '='));
+    out.push('\n');
+    push_lines(theirs, out);
+
+    out.extend((0..options.marker_size).map(|_| '>'));
+    out.push(' ');
+    out.push_str(&options.theirs_label);
+    out.push('\n');
+}
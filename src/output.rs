@@ -1,101 +1,143 @@
-use self::super::{HrxEntryData, HrxArchive, HrxError};
+use self::super::error::CompoundError;
+use self::super::{HrxEntryData, LineEndings, HrxArchive, HrxError};
 use std::io::{Error as IoError, Write};
 use self::super::util::boundary_str;
+use std::num::NonZeroUsize;
 
 
-/// `IoError` is absolute garbage when it comes to usability when it's a variant
-enum CompoundError {
-    Hrx(HrxError),
-    Io(IoError),
+/// Writes entries out one-by-one, without any validation
+pub struct Emitter {
+    /// `"\n<===>"`
+    bound: String,
+    /// `"\n"` or `"\r\n"`
+    newline: &'static str,
+    first_bound: bool,
+    ending_newline: bool,
 }
 
-impl From<HrxError> for CompoundError {
-    fn from(hrx: HrxError) -> CompoundError {
-        CompoundError::Hrx(hrx)
-    }
+
+pub fn write_archive<W: Write>(ar: &HrxArchive, into: &mut W) -> Result<(), Result<HrxError, IoError>> {
+    write_archive_with_line_endings(ar, LineEndings::Lf, into)
 }
 
-impl From<IoError> for CompoundError {
-    fn from(io: IoError) -> CompoundError {
-        CompoundError::Io(io)
-    }
+pub fn write_archive_with_line_endings<W: Write>(ar: &HrxArchive, line_endings: LineEndings, into: &mut W) -> Result<(), Result<HrxError, IoError>> {
+    write_archive_impl(ar, line_endings, into)?;
+    Ok(())
 }
 
-impl From<CompoundError> for Result<HrxError, IoError> {
-    fn from(ce: CompoundError) -> Result<HrxError, IoError> {
-        match ce {
-            CompoundError::Hrx(hrx) => Ok(hrx),
-            CompoundError::Io(io) => Err(io),
+/// Write the archive out with the specified boundary length, which the caller guarantees to be valid for it
+pub fn write_archive_with_boundary_length<W: Write>(ar: &HrxArchive, boundary_length: NonZeroUsize, into: &mut W) -> Result<(), IoError> {
+    write_archive_as(ar, boundary_length, LineEndings::Lf, into)
+}
+
+fn write_archive_as<W: Write>(ar: &HrxArchive, boundary_length: NonZeroUsize, line_endings: LineEndings, into: &mut W) -> Result<(), IoError> {
+    let mut emitter = Emitter::with_newline(boundary_length, line_endings.newline());
+
+    for (p, e) in &ar.entries {
+        let comment = e.comment.as_ref().map(|c| line_endings.denormalise(c));
+        match e.data {
+            HrxEntryData::File { ref body } => {
+                let body = body.as_ref().map(|b| line_endings.denormalise(b));
+                emitter.file(&p.0, comment.as_deref(), body.as_ref().map(|b| b.as_bytes()), into)?
+            }
+            HrxEntryData::Binary { ref body } => emitter.file(&p.0, comment.as_deref(), Some(body), into)?,
+            HrxEntryData::Directory => emitter.directory(&p.0, comment.as_deref(), into)?,
         }
     }
+
+    emitter.finish(ar.comment.as_ref().map(|c| line_endings.denormalise(c)).as_deref(), into)
 }
 
+fn write_archive_impl<W: Write>(ar: &HrxArchive, line_endings: LineEndings, into: &mut W) -> Result<(), CompoundError> {
+    ar.validate_content()?;
+    write_archive_as(ar, ar.boundary_length, line_endings, into)?;
 
-pub fn write_archive<W: Write>(ar: &HrxArchive, into: &mut W) -> Result<(), Result<HrxError, IoError>> {
-    write_archive_impl(ar, into)?;
     Ok(())
 }
 
-fn write_archive_impl<W: Write>(ar: &HrxArchive, into: &mut W) -> Result<(), CompoundError> {
-    ar.validate_content()?;
 
-    let bound = boundary_str(ar.boundary_length);
-    let mut first_bound = true;
-    let mut ending_newline = false;
+impl Emitter {
+    pub fn new(boundary_length: NonZeroUsize) -> Emitter {
+        Emitter::with_newline(boundary_length, "\n")
+    }
 
-    for (p, e) in &ar.entries {
-        write_comment(&e.comment, &bound, &mut first_bound, into)?;
+    pub fn with_newline(boundary_length: NonZeroUsize, newline: &'static str) -> Emitter {
+        Emitter {
+            bound: boundary_str(boundary_length),
+            newline: newline,
+            first_bound: true,
+            ending_newline: false,
+        }
+    }
 
-        write_bound(&bound, &mut first_bound, into)?;
-        into.write_all(&[b' '])?;
-        into.write_all(p.0.as_bytes())?;
-        match e.data {
-            HrxEntryData::File { body: None } => {
-                ending_newline = true;
-            }
-            HrxEntryData::File { body: Some(ref body) } if body.is_empty() => {
-                ending_newline = true;
-            }
-            HrxEntryData::File { body: Some(ref body) } => {
-                into.write_all(&[b'\n'])?;
-                into.write_all(body.as_bytes())?;
+    /// `"\n<===>"`
+    pub fn bound(&self) -> &str {
+        &self.bound
+    }
 
-                ending_newline = false;
-            }
-            HrxEntryData::Directory => {
-                into.write_all(&[b'/'])?;
-                ending_newline = true;
+    pub fn file<W: Write>(&mut self, path: &str, comment: Option<&str>, body: Option<&[u8]>, into: &mut W) -> Result<(), IoError> {
+        self.header(path, comment, into)?;
+        match body {
+            Some(body) if !body.is_empty() => {
+                into.write_all(self.newline.as_bytes())?;
+                into.write_all(body)?;
+
+                self.ending_newline = false;
             }
+            _ => self.ending_newline = true,
         }
+
+        Ok(())
     }
 
-    if !write_comment(&ar.comment, &bound, &mut first_bound, into)? && ending_newline {
-        into.write_all(&[b'\n'])?;
+    pub fn directory<W: Write>(&mut self, path: &str, comment: Option<&str>, into: &mut W) -> Result<(), IoError> {
+        self.header(path, comment, into)?;
+        into.write_all(&[b'/'])?;
+        self.ending_newline = true;
+
+        Ok(())
     }
 
-    Ok(())
-}
+    pub fn finish<W: Write>(&mut self, comment: Option<&str>, into: &mut W) -> Result<(), IoError> {
+        if !self.comment(comment, into)? && self.ending_newline {
+            into.write_all(self.newline.as_bytes())?;
+        }
 
-fn write_bound<W: Write>(bound: &str, first_bound: &mut bool, into: &mut W) -> Result<(), CompoundError> {
-    if *first_bound {
-        into.write_all(bound[1..].as_bytes())?;
+        Ok(())
+    }
 
-        *first_bound = false;
-    } else {
-        into.write_all(bound.as_bytes())?;
+    fn header<W: Write>(&mut self, path: &str, comment: Option<&str>, into: &mut W) -> Result<(), IoError> {
+        self.comment(comment, into)?;
+
+        self.boundary(into)?;
+        into.write_all(&[b' '])?;
+        into.write_all(path.as_bytes())?;
+
+        Ok(())
     }
 
-    Ok(())
-}
+    fn boundary<W: Write>(&mut self, into: &mut W) -> Result<(), IoError> {
+        if self.first_bound {
+            into.write_all(self.bound[1..].as_bytes())?;
 
-fn write_comment<W: Write>(comment: &Option<String>, bound: &str, first_bound: &mut bool, into: &mut W) -> Result<bool, CompoundError> {
-    if let Some(cmt) = comment.as_ref() {
-        write_bound(bound, first_bound, into)?;
-        into.write_all(&[b'\n'])?;
-        into.write_all(cmt.as_bytes())?;
+            self.first_bound = false;
+        } else {
+            into.write_all(self.newline.as_bytes())?;
+            into.write_all(self.bound[1..].as_bytes())?;
+        }
+
+        Ok(())
+    }
 
-        Ok(true)
-    } else {
-        Ok(false)
+    fn comment<W: Write>(&mut self, comment: Option<&str>, into: &mut W) -> Result<bool, IoError> {
+        if let Some(cmt) = comment {
+            self.boundary(into)?;
+            into.write_all(self.newline.as_bytes())?;
+            into.write_all(cmt.as_bytes())?;
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
     }
 }
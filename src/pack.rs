@@ -0,0 +1,295 @@
+use self::super::{UnrepresentableReason, InvalidPathReason, BASE64_COMMENT, HrxEntryData, HrxArchive, HrxEntry, HrxError, HrxPath};
+use self::super::binary::encode_base64;
+use std::io::{ErrorKind as IoErrorKind, Error as IoError};
+use self::super::error::CompoundError;
+use std::path::{PathBuf, Path};
+use std::num::NonZeroUsize;
+use self::super::glob::Glob;
+use std::convert::TryFrom;
+use std::fs;
+
+
+/// Configuration for [`HrxArchive::from_dir()`](struct.HrxArchive.html#method.from_dir).
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{UnrepresentablePolicy, SymlinkPolicy, BinaryPolicy, PackOptions, PackOrder};
+/// # use std::num::NonZeroUsize;
+/// assert_eq!(PackOptions::default(),
+///            PackOptions {
+///                include: vec![],
+///                exclude: vec![],
+///                order: PackOrder::Name,
+///                symlinks: SymlinkPolicy::Error,
+///                unrepresentable: UnrepresentablePolicy::Error,
+///                binary: BinaryPolicy::Unrepresentable,
+///                min_boundary_length: NonZeroUsize::new(3).unwrap(),
+///            });
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PackOptions {
+    /// Glob patterns files need to match one of to be included, all files are included if empty.
+    ///
+    /// Directories are always descended into, and are included if empty if they match.
+    ///
+    /// Patterns are matched against the `/`-separated path relative to the root directory;
+    /// `*`, `?` and `[...]` match within a single component, while a `**` component matches any amount thereof.
+    pub include: Vec<String>,
+    /// Glob patterns of files and directories to leave out, takes precedence over `include`.
+    ///
+    /// Excluded directories aren't descended into.
+    pub exclude: Vec<String>,
+    /// In what order to add directory entries.
+    pub order: PackOrder,
+    /// What to do with symlinks.
+    pub symlinks: SymlinkPolicy,
+    /// What to do with files whose paths or contents can't be represented in an archive.
+    pub unrepresentable: UnrepresentablePolicy,
+    /// What to do with files whose contents aren't valid UTF-8.
+    pub binary: BinaryPolicy,
+    /// The shortest boundary length to consider, the actual one will be the shortest one valid for the archive not below this.
+    pub min_boundary_length: NonZeroUsize,
+}
+
+/// Order of entries within each directory.
+///
+/// Since archive entries retain insertion order, this determines the order of entries in the resulting archive.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PackOrder {
+    /// Sort by name
+    Name,
+    /// Sort by name, but put files before subdirectories
+    FilesFirst,
+    /// Use whatever order the filesystem returns, which isn't guaranteed to be stable
+    Unsorted,
+}
+
+/// What to do with symlinks when packing.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SymlinkPolicy {
+    /// Archive the file or directory the symlink points to
+    ///
+    /// Dangling symlinks are handled according to the [`UnrepresentablePolicy`](enum.UnrepresentablePolicy.html)
+    Follow,
+    /// Leave the symlink out
+    Skip,
+    /// Fail with [`HrxError::Unrepresentable`](enum.HrxError.html#variant.Unrepresentable)
+    Error,
+}
+
+/// What to do with files that can't be represented in an archive.
+///
+/// Skipped files are reported alongside the archive by the `*_with_skipped()` constructors,
+/// e.g. [`HrxArchive::from_dir_with_skipped()`](struct.HrxArchive.html#method.from_dir_with_skipped).
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UnrepresentablePolicy {
+    /// Fail with [`HrxError::Unrepresentable`](enum.HrxError.html#variant.Unrepresentable)
+    Error,
+    /// Leave the file out, recording its path and why it was left out
+    Skip,
+}
+
+/// The paths of files skipped as per [`UnrepresentablePolicy::Skip`](enum.UnrepresentablePolicy.html#variant.Skip), each with why, in the order met.
+pub type SkippedFiles = Vec<(String, UnrepresentableReason)>;
+
+/// What to do with files whose contents aren't valid UTF-8 when packing.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BinaryPolicy {
+    /// Treat them like other files that can't be represented, as per [`PackOptions::unrepresentable`](struct.PackOptions.html#structfield.unrepresentable)
+    Unrepresentable,
+    /// Add them as [`Binary`](enum.HrxEntryData.html#variant.Binary) entries, so the serialised archive won't be valid UTF-8 either
+    Raw,
+    /// Add them as files with base64-encoded contents, commented with [`BASE64_COMMENT`](constant.BASE64_COMMENT.html)
+    Base64,
+}
+
+
+impl Default for PackOptions {
+    fn default() -> PackOptions {
+        PackOptions {
+            include: vec![],
+            exclude: vec![],
+            order: PackOrder::Name,
+            symlinks: SymlinkPolicy::Error,
+            unrepresentable: UnrepresentablePolicy::Error,
+            binary: BinaryPolicy::Unrepresentable,
+            min_boundary_length: NonZeroUsize::new(3).unwrap(),
+        }
+    }
+}
+
+impl UnrepresentablePolicy {
+    /// Fail with, or record in `skipped`, the file at the specified path being unrepresentable for the specified reason
+    pub(crate) fn handle(&self, path: String, reason: UnrepresentableReason, skipped: &mut SkippedFiles) -> Result<(), HrxError> {
+        match *self {
+            UnrepresentablePolicy::Error => Err(HrxError::Unrepresentable(path, reason)),
+            UnrepresentablePolicy::Skip => {
+                skipped.push((path, reason));
+                Ok(())
+            }
+        }
+    }
+}
+
+
+struct Packer<'o> {
+    options: &'o PackOptions,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    /// Canonicalised paths to the directories currently being packed, to detect symlink loops
+    ancestors: Vec<PathBuf>,
+    archive: HrxArchive,
+    skipped: SkippedFiles,
+}
+
+
+pub fn pack_directory(root: &Path, options: &PackOptions) -> Result<(HrxArchive, SkippedFiles), Result<HrxError, IoError>> {
+    Ok(pack_directory_impl(root, options)?)
+}
+
+fn pack_directory_impl(root: &Path, options: &PackOptions) -> Result<(HrxArchive, SkippedFiles), CompoundError> {
+    let mut packer = Packer {
+        options: options,
+        include: options.include.iter().map(|p| Glob::new(p)).collect(),
+        exclude: options.exclude.iter().map(|p| Glob::new(p)).collect(),
+        ancestors: vec![],
+        archive: HrxArchive::new(options.min_boundary_length),
+        skipped: vec![],
+    };
+
+    packer.directory(root, None)?;
+
+    let mut archive = packer.archive;
+    archive.boundary_length = archive.minimal_boundary_length_from(options.min_boundary_length);
+    Ok((archive, packer.skipped))
+}
+
+impl<'o> Packer<'o> {
+    /// Returns whether any entries were added.
+    fn directory(&mut self, dir: &Path, prefix: Option<&HrxPath>) -> Result<bool, CompoundError> {
+        let canonical = dir.canonicalize()?;
+        if self.ancestors.contains(&canonical) {
+            return Err(IoError::other(format!("{} is part of a symlink loop", dir.display())).into());
+        }
+        self.ancestors.push(canonical);
+
+        let mut children = vec![];
+        for child in fs::read_dir(dir)? {
+            let child = child?;
+            children.push((child.file_name(), child.path(), child.file_type()?));
+        }
+
+        match self.options.order {
+            PackOrder::Name => children.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0)),
+            PackOrder::FilesFirst => children.sort_by(|lhs, rhs| (lhs.2.is_dir(), &lhs.0).cmp(&(rhs.2.is_dir(), &rhs.0))),
+            PackOrder::Unsorted => {}
+        }
+
+        let mut added = false;
+        for (name, fs_path, mut file_type) in children {
+            let path = match HrxPath::try_from(Path::new(&name)) {
+                Ok(name) => {
+                    match prefix {
+                        Some(prefix) => HrxPath(format!("{}/{}", prefix, name)),
+                        None => name,
+                    }
+                }
+                Err(HrxError::InvalidPathComponent(_, _, InvalidPathReason::NonUtf8)) => {
+                    self.unrepresentable(&fs_path, UnrepresentableReason::NonUtf8Path)?;
+                    continue;
+                }
+                Err(_) => {
+                    self.unrepresentable(&fs_path, UnrepresentableReason::InvalidPath)?;
+                    continue;
+                }
+            };
+
+            if self.exclude.iter().any(|g| g.matches(path.as_ref())) {
+                continue;
+            }
+
+            if file_type.is_symlink() {
+                match self.options.symlinks {
+                    SymlinkPolicy::Follow => {
+                        match fs::metadata(&fs_path) {
+                            Ok(meta) => file_type = meta.file_type(),
+                            Err(ref err) if err.kind() == IoErrorKind::NotFound => {
+                                self.unrepresentable(&fs_path, UnrepresentableReason::Symlink)?;
+                                continue;
+                            }
+                            Err(err) => return Err(err.into()),
+                        }
+                    }
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::Error => return Err(HrxError::Unrepresentable(fs_path.display().to_string(), UnrepresentableReason::Symlink).into()),
+                }
+            }
+
+            if file_type.is_dir() {
+                if self.directory(&fs_path, Some(&path))? {
+                    added = true;
+                } else if self.included(&path) {
+                    self.archive.entries.insert(path,
+                                                HrxEntry {
+                                                    comment: None,
+                                                    data: HrxEntryData::Directory,
+                                                });
+                    added = true;
+                }
+            } else if file_type.is_file() {
+                if self.included(&path) && self.file(&fs_path, path)? {
+                    added = true;
+                }
+            } else {
+                self.unrepresentable(&fs_path, UnrepresentableReason::SpecialFile)?;
+            }
+        }
+
+        self.ancestors.pop();
+        Ok(added)
+    }
+
+    /// Returns whether the entry was added.
+    fn file(&mut self, file: &Path, path: HrxPath) -> Result<bool, CompoundError> {
+        let entry = match String::from_utf8(fs::read(file)?) {
+            Ok(body) => {
+                HrxEntry {
+                    comment: None,
+                    data: HrxEntryData::File { body: if body.is_empty() { None } else { Some(body) } },
+                }
+            }
+            Err(err) => {
+                match self.options.binary {
+                    BinaryPolicy::Unrepresentable => {
+                        self.unrepresentable(file, UnrepresentableReason::NonUtf8Contents)?;
+                        return Ok(false);
+                    }
+                    BinaryPolicy::Raw => {
+                        HrxEntry {
+                            comment: None,
+                            data: HrxEntryData::Binary { body: err.into_bytes() },
+                        }
+                    }
+                    BinaryPolicy::Base64 => {
+                        HrxEntry {
+                            comment: Some(BASE64_COMMENT.to_string()),
+                            data: HrxEntryData::File { body: Some(encode_base64(err.as_bytes())) },
+                        }
+                    }
+                }
+            }
+        };
+
+        self.archive.entries.insert(path, entry);
+        Ok(true)
+    }
+
+    fn included(&self, path: &HrxPath) -> bool {
+        self.include.is_empty() || self.include.iter().any(|g| g.matches(path.as_ref()))
+    }
+
+    fn unrepresentable(&mut self, file: &Path, reason: UnrepresentableReason) -> Result<(), CompoundError> {
+        Ok(self.options.unrepresentable.handle(file.display().to_string(), reason, &mut self.skipped)?)
+    }
+}
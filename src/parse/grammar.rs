@@ -1 +1,626 @@
-include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
+use self::super::super::{HrxEntryDataRef, ParseOptions, HrxEntryRef, Deviation, HrxEntry, Leniency, HrxPath};
+use jetscii::ByteSubstring as SubstringSearcher;
+use std::collections::{BTreeMap, HashSet};
+use self::super::super::util::boundary_str;
+use self::super::super::span::Locator;
+use std::num::NonZeroUsize;
+use std::error::Error;
+use std::{fmt, mem, str};
+use std::ops::Range;
+
+
+static PATH_CHARACTER_EXPECTED: &str = "Any character other than U+0000 through U+001F, U+007F DELETE, U+002F SOLIDUS, U+003A COLON, or U+005C REVERSE SOLIDUS";
+static PATH_COMPONENT_DOTS: &str = "Invalid '.' or '..' path component";
+pub(crate) static UTF8_EXPECTED: &str = "valid UTF-8";
+
+/// Everything that can end up in [`ParseError::expected`](struct.ParseError.html#structfield.expected)
+#[cfg(feature = "serde")]
+pub(crate) static EXPECTED_TOKENS: &[&str] =
+    &["<", "=", ">", " ", "/", "\n", "\r\n", "<character>", PATH_CHARACTER_EXPECTED, PATH_COMPONENT_DOTS, UTF8_EXPECTED];
+
+
+/// HRX parsing error
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ParseError {
+    /// 1-based line # of error
+    pub line: usize,
+    /// 1-based column # of error
+    pub column: usize,
+    /// Byte offset of error
+    pub offset: usize,
+    /// Expected but unmatched rules
+    pub expected: HashSet<&'static str>,
+}
+
+/// Convenience result type
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// The comment, entries, and boundary length of an archive
+type Archive = (Option<String>, Vec<(HrxPath, HrxEntry)>, NonZeroUsize);
+
+/// The comment, entries, and boundary length of an archive, borrowed from the input
+type ArchiveRef<'input> = (Option<&'input str>, Vec<(&'input str, HrxEntryRef<'input>)>, NonZeroUsize);
+
+/// The comment and entries of an archive, as ranges into the input
+pub(crate) type RawArchive = (Option<Range<usize>>, Vec<RawEntry>);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "error at {}:{}: expected ", self.line, self.column)?;
+        if self.expected.is_empty() {
+            fmt.write_str("EOF")?;
+        } else if self.expected.len() == 1 {
+            write!(fmt, "`{}`", self.expected.iter().next().unwrap().escape_default())?;
+        } else {
+            let mut iter = self.expected.iter();
+            write!(fmt, "one of `{}`", iter.next().unwrap().escape_default())?;
+            for elem in iter {
+                write!(fmt, ", `{}`", elem.escape_default())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        "parse error"
+    }
+}
+
+
+/// `entry* comment?`
+///
+/// Note, that this does not perform any content validation beyond grammar.
+pub fn archive(input: &str, boundary_length: NonZeroUsize) -> ParseResult<Archive> {
+    let (comment, entries, boundary_length) = archive_ref(input, boundary_length)?;
+    Ok((comment.map(str::to_string), entries.into_iter().map(|(p, e)| (HrxPath(p.to_string()), e.into_owned())).collect(), boundary_length))
+}
+
+/// `entry* comment?`, borrowing from the input
+///
+/// Note, that this does not perform any content validation beyond grammar.
+pub fn archive_ref(input: &str, boundary_length: NonZeroUsize) -> ParseResult<ArchiveRef<'_>> {
+    let (comment, entries) = Parser::new(input.as_bytes(), boundary_length).run(|p| p.archive(0))?;
+    Ok((comment.map(|c| &input[c]), entries.into_iter().map(|e| e.borrow_from(input)).collect(), boundary_length))
+}
+
+/// `entry* comment?`, as ranges into the input, which needn't be valid UTF-8 outside of paths,
+/// together with the offsets of all deviations accepted as configured, in order
+pub(crate) fn archive_bytes(input: &[u8], boundary_length: NonZeroUsize, options: &ParseOptions) -> ParseResult<(RawArchive, Vec<(usize, Deviation)>)> {
+    let mut parser = Parser::new(input, boundary_length);
+    parser.crlf = options.line_endings.accepts_crlf();
+    parser.leniency = options.lenient;
+    let archive = parser.run(|p| {
+            let pos = p.prologue();
+            p.archive(pos)
+        })?;
+    Ok((archive, parser.deviations.into_iter().collect()))
+}
+
+/// `entry* comment?`, as ranges into the input, found by only parsing the lines starting with `boundary`
+///
+/// `None` if the archive doesn't match, without any details; `archive_bytes()` is needed to find out where and why.
+pub(crate) fn archive_index(input: &str, boundary_length: NonZeroUsize) -> Option<RawArchive> {
+    Parser::new(input.as_bytes(), boundary_length).index()
+}
+
+/// `comment? (file | directory)`
+pub fn entry(input: &str, boundary_length: NonZeroUsize) -> ParseResult<(HrxPath, HrxEntry)> {
+    let (path, entry) = entry_ref(input, boundary_length)?;
+    Ok((HrxPath(path.to_string()), entry.into_owned()))
+}
+
+/// `comment? (file | directory)`, borrowing from the input
+pub fn entry_ref(input: &str, boundary_length: NonZeroUsize) -> ParseResult<(&str, HrxEntryRef<'_>)> {
+    Parser::new(input.as_bytes(), boundary_length).run(|p| p.entry(0)).map(|e| e.borrow_from(input))
+}
+
+/// `boundary newline body`
+pub fn comment(input: &str, boundary_length: NonZeroUsize) -> ParseResult<&str> {
+    Parser::new(input.as_bytes(), boundary_length).run(|p| p.comment(0)).map(|c| &input[c])
+}
+
+/// `boundary " "+ path newline body?`
+pub fn file(input: &str, boundary_length: NonZeroUsize) -> ParseResult<(HrxPath, Option<&str>)> {
+    Parser::new(input.as_bytes(), boundary_length).run(|p| p.file(0)).map(|(p, b)| (HrxPath(input[p].to_string()), b.map(|b| &input[b])))
+}
+
+/// `boundary " "+ path "/" newline+`
+pub fn directory(input: &str, boundary_length: NonZeroUsize) -> ParseResult<HrxPath> {
+    Parser::new(input.as_bytes(), boundary_length).run(|p| p.directory(0)).map(|p| HrxPath(input[p].to_string()))
+}
+
+/// `contents newline`
+///
+/// No newline at the end of the archive (if the archive ends in a body, all trailing newlines are part of that body's contents)
+///
+/// NB: this doesn't explicitly consume a newline as that has been moved down to `contents`.
+pub fn body(input: &str, boundary_length: NonZeroUsize) -> ParseResult<&str> {
+    Parser::new(input.as_bytes(), boundary_length).run(|p| p.contents(0)).map(|b| &input[b])
+}
+
+/// `path-component ("/" path-component)*`
+pub fn path(input: &str, boundary_length: NonZeroUsize) -> ParseResult<HrxPath> {
+    Parser::new(input.as_bytes(), boundary_length).run(|p| p.path(0)).map(|p| HrxPath(input[p].to_string()))
+}
+
+/// `path-character+`
+///
+/// Not equal to `"."` or `".."`
+pub fn path_component(input: &str, boundary_length: NonZeroUsize) -> ParseResult<&str> {
+    Parser::new(input.as_bytes(), boundary_length).run(|p| p.path_component(0)).map(|pc| &input[pc])
+}
+
+
+/// A parsed entry, its parts being ranges into the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RawEntry {
+    pub comment: Option<Range<usize>>,
+    pub path: Range<usize>,
+    /// `None` for directories, otherwise the file body, if any
+    pub body: Option<Option<Range<usize>>>,
+}
+
+impl RawEntry {
+    /// Get the parts of this entry from the input it was parsed from.
+    pub fn borrow_from<'a>(&self, input: &'a str) -> (&'a str, HrxEntryRef<'a>) {
+        (&input[self.path.clone()],
+         HrxEntryRef {
+             comment: self.comment.clone().map(|c| &input[c]),
+             data: match self.body {
+                 Some(ref body) => HrxEntryDataRef::File { body: body.clone().map(|b| &input[b]) },
+                 None => HrxEntryDataRef::Directory,
+             },
+         })
+    }
+}
+
+
+/// What follows the line starting with a `boundary`
+enum BoundaryLine {
+    /// `boundary newline`, followed by a comment
+    Comment,
+    /// `boundary " "+ path newline`, followed by the file's body, if any
+    File(Range<usize>),
+    /// `boundary " "+ path "/" newline+`, followed by the next boundary line or EOF
+    Directory(Range<usize>),
+}
+
+
+/// `Some((position after match, value))` on success, `None` on failure.
+type RuleResult<T> = Option<(usize, T)>;
+
+/// Recursive-descent parser for the grammar in the [module documentation](index.html).
+///
+/// Works on bytes, so that archives with non-UTF-8 bodies can be parsed as well;
+/// all the results are ranges ending and starting on character boundaries.
+///
+/// Failures are tracked like in a PEG parser:
+/// only the furthest position any rule failed at is reported, together with everything expected there,
+/// and failures inside lookaheads aren't recorded at all.
+struct Parser<'input> {
+    input: &'input [u8],
+    boundary_length: NonZeroUsize,
+    /// `newline boundary`, i.e. what terminates `contents`
+    newline_boundary: String,
+    /// Whether `newline` also matches CRLF
+    crlf: bool,
+    /// Which deviations from the grammar to accept
+    leniency: Leniency,
+    /// Accepted deviations by offset;
+    /// keyed so that rules retried at the same position after backtracking don't report them twice
+    deviations: BTreeMap<usize, Deviation>,
+
+    max_err_pos: usize,
+    suppress_fail: usize,
+    expected: HashSet<&'static str>,
+}
+
+impl<'input> Parser<'input> {
+    fn new(input: &'input [u8], boundary_length: NonZeroUsize) -> Parser<'input> {
+        Parser {
+            input: input,
+            boundary_length: boundary_length,
+            newline_boundary: boundary_str(boundary_length),
+            crlf: false,
+            leniency: Leniency::strict(),
+            deviations: BTreeMap::new(),
+
+            max_err_pos: 0,
+            suppress_fail: 0,
+            expected: HashSet::new(),
+        }
+    }
+
+    fn run<T, F: FnOnce(&mut Parser<'input>) -> RuleResult<T>>(&mut self, rule: F) -> ParseResult<T> {
+        if let Some((pos, val)) = rule(self) {
+            if pos == self.input.len() {
+                return Ok(val);
+            }
+        }
+
+        let position = Locator::new(self.input).locate(self.max_err_pos);
+        Err(ParseError {
+            line: position.line,
+            column: position.column,
+            offset: position.offset,
+            expected: mem::take(&mut self.expected),
+        })
+    }
+
+    fn mark_failure<T>(&mut self, pos: usize, expected: &'static str) -> RuleResult<T> {
+        if self.suppress_fail == 0 {
+            if pos > self.max_err_pos {
+                self.max_err_pos = pos;
+                self.expected.clear();
+            }
+            if pos == self.max_err_pos {
+                self.expected.insert(expected);
+            }
+        }
+
+        None
+    }
+
+    fn literal(&mut self, pos: usize, lit: &'static str) -> RuleResult<()> {
+        if self.input[pos..].starts_with(lit.as_bytes()) {
+            Some((pos + lit.len(), ()))
+        } else {
+            self.mark_failure(pos, lit)
+        }
+    }
+
+    fn deviate(&mut self, pos: usize, deviation: Deviation) {
+        self.deviations.insert(pos, deviation);
+    }
+
+    /// Length of the run of spaces and tabs at the specified position
+    fn blanks(&self, pos: usize) -> usize {
+        self.input[pos..].iter().take_while(|&&b| b == b' ' || b == b'\t').count()
+    }
+
+
+    /// What's skipped before `archive`, as far as accepted: a byte order mark, then blank lines
+    fn prologue(&mut self) -> usize {
+        let mut pos = 0;
+        if self.leniency.byte_order_mark && self.input.starts_with("\u{FEFF}".as_bytes()) {
+            self.deviate(pos, Deviation::ByteOrderMark);
+            pos += "\u{FEFF}".len();
+        }
+
+        if self.leniency.leading_blank_lines {
+            let start = pos;
+            loop {
+                let after_blanks = pos + self.blanks(pos);
+                self.suppress_fail += 1;
+                let newline = self.newline(after_blanks);
+                self.suppress_fail -= 1;
+
+                match newline {
+                    Some((new_pos, _)) => pos = new_pos,
+                    None => break,
+                }
+            }
+            if pos != start {
+                self.deviate(start, Deviation::LeadingBlankLines);
+            }
+        }
+
+        pos
+    }
+
+
+    fn archive(&mut self, pos: usize) -> RuleResult<RawArchive> {
+        let mut pos = pos;
+
+        let mut entries = vec![];
+        while let Some((new_pos, entry)) = self.entry(pos) {
+            entries.push(entry);
+            pos = new_pos;
+        }
+
+        let (pos, comment) = self.optional(pos, Parser::comment);
+        Some((pos, (comment, entries)))
+    }
+
+    /// `entry* comment?`, going from one boundary line to the next
+    ///
+    /// Each line is parsed once, and the contents after it are taken to end where the substring search in `contents` finds,
+    /// instead of trying `comment`, `file`, and `directory` in turn, like `entry` does.
+    fn index(&mut self) -> Option<RawArchive> {
+        let mut entries = vec![];
+        let mut comment = None;
+        let mut pos = 0;
+        while pos != self.input.len() {
+            let (after_line, line) = self.boundary_line(pos)?;
+            match line {
+                BoundaryLine::Comment => {
+                    // Not `comment? (file | directory)` and not `comment?` at EOF either
+                    if comment.is_some() {
+                        return None;
+                    }
+                    let (after_contents, contents) = self.contents(after_line)?;
+                    comment = Some(contents);
+                    pos = after_contents;
+                }
+                BoundaryLine::File(path) => {
+                    let (after_body, body) = self.optional(after_line, Parser::contents);
+                    entries.push(RawEntry {
+                        comment: comment.take(),
+                        path: path,
+                        body: Some(body),
+                    });
+                    pos = after_body;
+                }
+                BoundaryLine::Directory(path) => {
+                    entries.push(RawEntry {
+                        comment: comment.take(),
+                        path: path,
+                        body: None,
+                    });
+                    pos = after_line;
+                }
+            }
+        }
+        Some((comment, entries))
+    }
+
+    fn boundary_line(&mut self, pos: usize) -> RuleResult<BoundaryLine> {
+        let (pos, _) = self.boundary(pos)?;
+        if let Some((pos, _)) = self.newline(pos) {
+            return Some((pos, BoundaryLine::Comment));
+        }
+
+        let (pos, _) = self.spaces(pos)?;
+        let (pos, path) = self.path(pos)?;
+        if let Some((pos, _)) = self.newline(pos) {
+            return Some((pos, BoundaryLine::File(path)));
+        }
+
+        let (pos, _) = self.literal(pos, "/")?;
+        let pos = self.trailing_whitespace(pos);
+        let (mut pos, _) = self.newline(pos)?;
+        while let Some((new_pos, _)) = self.newline(pos) {
+            pos = new_pos;
+        }
+        Some((pos, BoundaryLine::Directory(path)))
+    }
+
+    fn entry(&mut self, pos: usize) -> RuleResult<RawEntry> {
+        let (after_comment, comment) = self.optional(pos, Parser::comment);
+
+        if let Some((pos, (path, body))) = self.file(after_comment) {
+            return Some((pos,
+                         RawEntry {
+                             comment: comment.clone(),
+                             path: path,
+                             body: Some(body),
+                         }));
+        }
+
+        if let Some((pos, path)) = self.directory(after_comment) {
+            return Some((pos,
+                         RawEntry {
+                             comment: comment,
+                             path: path,
+                             body: None,
+                         }));
+        }
+
+        None
+    }
+
+    fn comment(&mut self, pos: usize) -> RuleResult<Range<usize>> {
+        let (pos, _) = self.boundary(pos)?;
+        let (pos, _) = self.newline(pos)?;
+        self.contents(pos)
+    }
+
+    fn file(&mut self, pos: usize) -> RuleResult<(Range<usize>, Option<Range<usize>>)> {
+        let (pos, _) = self.boundary(pos)?;
+        let (pos, _) = self.spaces(pos)?;
+        let (pos, path) = self.path(pos)?;
+        let (pos, _) = self.newline(pos)?;
+        let (pos, body) = self.optional(pos, Parser::contents);
+        Some((pos, (path, body)))
+    }
+
+    fn directory(&mut self, pos: usize) -> RuleResult<Range<usize>> {
+        let (pos, _) = self.boundary(pos)?;
+        let (pos, _) = self.spaces(pos)?;
+        let (pos, path) = self.path(pos)?;
+        let (pos, _) = self.literal(pos, "/")?;
+        let pos = self.trailing_whitespace(pos);
+        let (mut pos, _) = self.newline(pos)?;
+        while let Some((new_pos, _)) = self.newline(pos) {
+            pos = new_pos;
+        }
+        Some((pos, path))
+    }
+
+    /// `"<" "="+ ">"`
+    ///
+    /// Must exactly match the first boundary in the archive
+    fn boundary(&mut self, pos: usize) -> RuleResult<()> {
+        let (mut pos, _) = self.literal(pos, "<")?;
+        for _ in 0..self.boundary_length.get() {
+            pos = self.literal(pos, "=")?.0;
+        }
+        self.literal(pos, ">")
+    }
+
+    /// U+000A LINE FEED, optionally preceded by U+000D CARRIAGE RETURN if accepting CRLFs
+    fn newline(&mut self, pos: usize) -> RuleResult<()> {
+        if self.crlf {
+            if let Some(res) = self.literal(pos, "\r\n") {
+                return Some(res);
+            }
+        }
+        self.literal(pos, "\n")
+    }
+
+    /// `" "+`, or `[ \t]+` if accepting tabs after boundaries
+    fn spaces(&mut self, pos: usize) -> RuleResult<()> {
+        let start = pos;
+        let (mut pos, _) = self.space(pos)?;
+        while let Some((new_pos, _)) = self.space(pos) {
+            pos = new_pos;
+        }
+
+        if let Some(tab) = self.input[start..pos].iter().position(|&b| b == b'\t') {
+            self.deviate(start + tab, Deviation::TabAfterBoundary);
+        }
+        Some((pos, ()))
+    }
+
+    fn space(&mut self, pos: usize) -> RuleResult<()> {
+        if self.leniency.tabs_after_boundary && self.input[pos..].starts_with(b"\t") {
+            Some((pos + 1, ()))
+        } else {
+            self.literal(pos, " ")
+        }
+    }
+
+    /// `[ \t]*` if accepting whitespace after directories, otherwise nothing
+    fn trailing_whitespace(&mut self, pos: usize) -> usize {
+        if !self.leniency.trailing_whitespace_after_directory {
+            return pos;
+        }
+
+        let len = self.blanks(pos);
+        if len != 0 {
+            self.deviate(pos, Deviation::TrailingWhitespaceAfterDirectory);
+        }
+        pos + len
+    }
+
+    /// Any sequence of characters that neither begins with `boundary` nor includes U+000A LINE FEED followed immediately by `boundary`
+    ///
+    /// Matches the alternatives `!boundary (!(newline boundary) .)+ newline &boundary` and `!boundary .+`, in that order,
+    /// but finds the end of the first one with a substring search for `newline boundary` instead of going character-by-character.
+    ///
+    /// When accepting CRLFs, a CR right before the terminating `newline boundary` is part of the newline, not of the contents.
+    fn contents(&mut self, pos: usize) -> RuleResult<Range<usize>> {
+        if self.lookahead(pos, Parser::boundary) {
+            return None;
+        }
+
+        let len = self.input.len();
+        match SubstringSearcher::new(self.newline_boundary.as_bytes()).find(&self.input[pos..]) {
+            // `newline &boundary` will always succeed here
+            Some(idx) => {
+                let end = if self.crlf && idx != 0 && self.input[pos + idx - 1] == b'\r' {
+                    idx - 1
+                } else {
+                    idx
+                };
+                if end != 0 {
+                    return Some((pos + idx + 1, pos..pos + end));
+                }
+            }
+            None => {
+                // `.` fails at EOF after consuming everything, then `newline` does, if the search loop matched anything
+                self.mark_failure::<()>(len, "<character>");
+                if pos != len {
+                    self.mark_failure::<()>(len, "\n");
+                }
+            }
+        }
+
+        self.mark_failure::<()>(len, "<character>");
+        if pos != len { Some((len, pos..len)) } else { None }
+    }
+
+    /// `path-component ("/" path-component)*`
+    fn path(&mut self, pos: usize) -> RuleResult<Range<usize>> {
+        let start = pos;
+        let (mut pos, _) = self.path_component(pos)?;
+        while let Some((new_pos, _)) = self.literal(pos, "/").and_then(|(pos, _)| self.path_component(pos)) {
+            if self.is_whitespace_after_directory(pos, new_pos) {
+                break;
+            }
+            pos = new_pos;
+        }
+        Some((pos, start..pos))
+    }
+
+    /// Whether the `"/" path-component` between the specified positions is only spaces, with nothing but spaces and tabs after it on its line,
+    /// and only newlines up to the next boundary or EOF after that, i.e. actually trailing whitespace after a directory, if accepting that
+    ///
+    /// Anything else would be the body of the file strictly named by the whitespace, so that's kept a file.
+    fn is_whitespace_after_directory(&mut self, slash: usize, end: usize) -> bool {
+        if !self.leniency.trailing_whitespace_after_directory {
+            return false;
+        }
+
+        let line_end = slash + 1 + self.blanks(slash + 1);
+        line_end >= end && self.lookahead(line_end, Parser::directory_end)
+    }
+
+    /// `newline+ (&boundary | EOF)`
+    fn directory_end(&mut self, pos: usize) -> RuleResult<()> {
+        let (mut pos, _) = self.newline(pos)?;
+        while let Some((new_pos, _)) = self.newline(pos) {
+            pos = new_pos;
+        }
+
+        if pos == self.input.len() || self.lookahead(pos, Parser::boundary) {
+            Some((pos, ()))
+        } else {
+            None
+        }
+    }
+
+    /// `path-character+`
+    ///
+    /// Not equal to `"."` or `".."`
+    fn path_component(&mut self, pos: usize) -> RuleResult<Range<usize>> {
+        let start = pos;
+        let (mut pos, _) = self.path_character(pos)?;
+        while let Some((new_pos, _)) = self.path_character(pos) {
+            pos = new_pos;
+        }
+
+        match &self.input[start..pos] {
+            b"." | b".." => self.mark_failure(pos, PATH_COMPONENT_DOTS),
+            _ => Some((pos, start..pos)),
+        }
+    }
+
+    /// Any character other than U+0000 through U+001F, U+007F DELETE, U+002F SOLIDUS, U+003A COLON, or U+005C REVERSE SOLIDUS
+    fn path_character(&mut self, pos: usize) -> RuleResult<char> {
+        match first_char(&self.input[pos..]) {
+            Some(c) if !(c <= '\u{1F}' || c == '\u{7F}' || c == '/' || c == ':' || c == '\\') => Some((pos + c.len_utf8(), c)),
+            _ => self.mark_failure(pos, PATH_CHARACTER_EXPECTED),
+        }
+    }
+
+
+    /// `rule?`
+    fn optional<T, F: FnOnce(&mut Parser<'input>, usize) -> RuleResult<T>>(&mut self, pos: usize, rule: F) -> (usize, Option<T>) {
+        match rule(self, pos) {
+            Some((pos, val)) => (pos, Some(val)),
+            None => (pos, None),
+        }
+    }
+
+    /// `&rule`, without recording failures
+    fn lookahead<T, F: FnOnce(&mut Parser<'input>, usize) -> RuleResult<T>>(&mut self, pos: usize, rule: F) -> bool {
+        self.suppress_fail += 1;
+        let res = rule(self, pos).is_some();
+        self.suppress_fail -= 1;
+        res
+    }
+}
+
+
+/// Decode the character at the start of the specified data, if it starts with a valid one
+fn first_char(data: &[u8]) -> Option<char> {
+    let head = &data[..data.len().min(4)];
+    let valid = match str::from_utf8(head) {
+        Ok(s) => s,
+        Err(err) => str::from_utf8(&head[..err.valid_up_to()]).expect("valid prefix"),
+    };
+    valid.chars().next()
+}
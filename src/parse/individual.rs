@@ -1,7 +1,8 @@
-use std::collections::btree_map::{BTreeMap, Entry as BTreeMapEntry};
-use self::super::super::{HrxEntryData, HrxEntry, HrxError, HrxPath};
+use self::super::super::{HrxEntryDataRef, HrxEntryData, HrxEntryRef, HrxEntry, HrxError, HrxPath};
 use linked_hash_map::LinkedHashMap;
+use std::collections::BTreeMap;
 use std::num::NonZeroUsize;
+use std::borrow::Borrow;
 
 
 /// Search the specified for the length of the first `boundary`.
@@ -18,14 +19,15 @@ use std::num::NonZeroUsize;
 ///
 /// assert_eq!(discover_first_boundary_length("<>"), None);
 /// assert_eq!(discover_first_boundary_length("коммунизм"), None);
+/// assert_eq!(discover_first_boundary_length(b"\xFF<==>"), NonZeroUsize::new(2));
 /// ```
-pub fn discover_first_boundary_length<S: AsRef<str>>(in_data: S) -> Option<NonZeroUsize> {
+pub fn discover_first_boundary_length<S: AsRef<[u8]>>(in_data: S) -> Option<NonZeroUsize> {
     discover_first_boundary_length_impl(in_data.as_ref())
 }
 
-fn discover_first_boundary_length_impl(in_data: &str) -> Option<NonZeroUsize> {
-    let begin = ascii_chars!('<').find(in_data)?;
-    let length = ascii_chars!('>').find(&in_data[begin + 1..])?; // Searching from start of "====="s, so 0-based insdex of ">" will be their length
+fn discover_first_boundary_length_impl(in_data: &[u8]) -> Option<NonZeroUsize> {
+    let begin = bytes!(b'<').find(in_data)?;
+    let length = bytes!(b'>').find(&in_data[begin + 1..])?; // Searching from start of "====="s, so 0-based insdex of ">" will be their length
 
     NonZeroUsize::new(length)
 }
@@ -101,42 +103,77 @@ pub fn reduce_raw_entries_and_validate_directory_tree<Ii: IntoIterator<Item = (H
                                                                                                     -> Result<LinkedHashMap<HrxPath, HrxEntry>, HrxError> {
     let iter = iter.into_iter();
     let mut map = LinkedHashMap::with_capacity(iter.size_hint().0);
-    let mut paths = BTreeMap::new();
+    let mut paths = BTreeMap::<String, _>::new();
 
     for (k, v) in iter {
-        reduce_raw_entry_and_validate_its_directory_tree(k, v, &mut map, &mut paths)?;
+        validate_its_directory_tree(&k.0, v.data == HrxEntryData::Directory, &mut paths)?;
+        map.insert(k, v);
     }
 
     Ok(map)
 }
 
-fn reduce_raw_entry_and_validate_its_directory_tree(k: HrxPath, v: HrxEntry, map: &mut LinkedHashMap<HrxPath, HrxEntry>, paths: &mut BTreeMap<String, bool>)
-                                                    -> Result<(), HrxError> {
-    for (slash_i, _) in k.0.match_indices('/') {
-        match paths.entry(k.0[0..slash_i].to_string()) {
-            BTreeMapEntry::Vacant(ve) => {
-                ve.insert(true);
-            }
-            BTreeMapEntry::Occupied(oe) => {
-                if !oe.get() {
-                    return Err(HrxError::FileAsDirectory(oe.key().to_string(), k.0.clone()));
-                }
-            }
-        }
-    }
+/// Convert a collexion of borrowed `(path, entry)` pairs into a `path -> entry` map, erroring on any duplicates and file-as-dir usages.
+///
+/// This is the [`archive_ref()`](fn.archive_ref.html) counterpart to
+/// [`reduce_raw_entries_and_validate_directory_tree()`](fn.reduce_raw_entries_and_validate_directory_tree.html),
+/// and doesn't copy any of the paths.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxEntryDataRef, HrxEntryRef, HrxError};
+/// # use hrx::parse::reduce_raw_entry_refs_and_validate_directory_tree;
+/// let mut source_material = vec![("file1.txt",
+///                                 HrxEntryRef {
+///                                     comment: None,
+///                                     data: HrxEntryDataRef::File { body: Some("First file's contents") },
+///                                 }),
+///                                ("file1.txt/subfile.txt",
+///                                 HrxEntryRef {
+///                                     comment: None,
+///                                     data: HrxEntryDataRef::File { body: Some("Second file's contents") },
+///                                 })];
+///
+/// assert_eq!(reduce_raw_entry_refs_and_validate_directory_tree(source_material.clone()),
+///            Err(HrxError::FileAsDirectory("file1.txt".to_string(), "file1.txt/subfile.txt".to_string())));
+///
+/// source_material[1].0 = "file2.txt";
+/// assert_eq!(reduce_raw_entry_refs_and_validate_directory_tree(source_material.clone()),
+///            Ok(source_material.iter().cloned().collect()));
+/// ```
+pub fn reduce_raw_entry_refs_and_validate_directory_tree<'a, Ii>(iter: Ii) -> Result<LinkedHashMap<&'a str, HrxEntryRef<'a>>, HrxError>
+    where Ii: IntoIterator<Item = (&'a str, HrxEntryRef<'a>)>
+{
+    let iter = iter.into_iter();
+    let mut map = LinkedHashMap::with_capacity(iter.size_hint().0);
+    let mut paths = BTreeMap::<&str, _>::new();
 
-    match paths.entry(k.0) {
-        BTreeMapEntry::Vacant(ve) => {
-            let is_dir = v.data == HrxEntryData::Directory;
+    for (k, v) in iter {
+        validate_its_directory_tree(k, v.data == HrxEntryDataRef::Directory, &mut paths)?;
+        map.insert(k, v);
+    }
 
-            map.insert(HrxPath(ve.key().clone()), v);
+    Ok(map)
+}
 
-            ve.insert(is_dir);
-        }
-        BTreeMapEntry::Occupied(oe) => {
-            return Err(HrxError::DuplicateEntry(oe.remove_entry().0));
+/// Record `path` and its parents in `paths`, which maps known paths to whether they're directories.
+pub(crate) fn validate_its_directory_tree<'p, K: Ord + Borrow<str> + From<&'p str>>(path: &'p str, is_dir: bool, paths: &mut BTreeMap<K, bool>) -> Result<(), HrxError> {
+    for (slash_i, _) in path.match_indices('/') {
+        let parent = &path[0..slash_i];
+        match paths.get(parent) {
+            None => {
+                paths.insert(K::from(parent), true);
+            }
+            Some(&false) => return Err(HrxError::FileAsDirectory(parent.to_string(), path.to_string())),
+            Some(&true) => {}
         }
     }
 
+    if paths.contains_key(path) {
+        return Err(HrxError::DuplicateEntry(path.to_string()));
+    }
+    paths.insert(K::from(path), is_dir);
+
     Ok(())
 }
@@ -5,8 +5,12 @@
 //! In funxions which take the `boundary_length` argument,
 //! that value specifies the amount of `=` characters in the archive boundary.
 //!
-//! However, due to the parser generator used, the `path()` funxion also takes it, despite it being unused.
-//! It can be safely ignored in that case.
+//! For consistency, the `path()` and `path_component()` funxions also take it, despite it being unused.
+//! It can be safely ignored in those cases.
+//!
+//! The `archive_ref()` and `entry_ref()` funxions are the same as `archive()` and `entry()`,
+//! except they borrow the paths, comments, and bodies from the input instead of copying them,
+//! cf. [`HrxArchiveRef`](../struct.HrxArchiveRef.html).
 //!
 //! # Grammar
 //!
@@ -38,5 +42,10 @@
 mod individual;
 mod grammar;
 
-pub use self::grammar::{ParseResult, ParseError, directory, archive, comment, entry, body, file, path};
-pub use self::individual::{reduce_raw_entries_and_validate_directory_tree, discover_first_boundary_length};
+pub use self::grammar::{ParseResult, ParseError, archive_ref, directory, entry_ref, archive, comment, entry, body, file, path,
+                         path_component};
+pub use self::individual::{reduce_raw_entry_refs_and_validate_directory_tree, reduce_raw_entries_and_validate_directory_tree, discover_first_boundary_length};
+pub(crate) use self::grammar::{UTF8_EXPECTED, RawEntry, archive_bytes, archive_index};
+#[cfg(feature = "serde")]
+pub(crate) use self::grammar::EXPECTED_TOKENS;
+pub(crate) use self::individual::validate_its_directory_tree;
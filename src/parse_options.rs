@@ -0,0 +1,179 @@
+use self::super::{HrxEntryDataRef, HrxEntryData, HrxArchive, HrxWarning, HrxEntry, HrxError, HrxPath, parse};
+use self::super::span::Locator;
+use std::borrow::Cow;
+
+
+/// Configuration for [`HrxArchive::parse_with_options()`](struct.HrxArchive.html#method.parse_with_options).
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{ParseOptions, LineEndings, Leniency};
+/// assert_eq!(ParseOptions::default(),
+///            ParseOptions {
+///                line_endings: LineEndings::Lf,
+///                lenient: Leniency::strict(),
+///            });
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ParseOptions {
+    /// What to consider a newline, and what to do with CRLFs in comments and bodies.
+    pub line_endings: LineEndings,
+    /// Which deviations from the spec to accept, reporting each with a warning instead of failing.
+    pub lenient: Leniency,
+}
+
+/// How line endings are treated when parsing and serialising an archive.
+///
+/// Paths can't contain either CR or LF, so they're unaffected.
+///
+/// An archive parsed with a policy serialises back into the same text with the same policy,
+/// as long as all of the newlines in it are the ones the policy serialises them as.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LineEndings {
+    /// Newlines are U+000A LINE FEED, as per the spec; comments and bodies are left as-is
+    Lf,
+    /// Newlines are CRLF or LF when parsing, and CRLF when serialising; comments and bodies are left as-is
+    CrlfPreserve,
+    /// Newlines are CRLF or LF when parsing, and CRLF when serialising;
+    /// CRLFs in comments and bodies are turned into LFs when parsing, and LFs in them back into CRLFs when serialising
+    CrlfNormalise,
+}
+
+/// Deviations from the spec commonly found in hand-written archives, to accept when parsing.
+///
+/// Each one accepted is reported as a [`HrxWarning`](struct.HrxWarning.html);
+/// none of them survive into the parsed archive, so serialising it yields the strict form.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::Leniency;
+/// assert_eq!(Leniency::default(), Leniency::strict());
+/// assert_eq!(Leniency::all(),
+///            Leniency {
+///                byte_order_mark: true,
+///                leading_blank_lines: true,
+///                tabs_after_boundary: true,
+///                trailing_whitespace_after_directory: true,
+///            });
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Leniency {
+    /// Skip a U+FEFF BYTE ORDER MARK at the very start of the archive
+    pub byte_order_mark: bool,
+    /// Skip empty lines, or ones consisting only of spaces and tabs, before the first boundary
+    pub leading_blank_lines: bool,
+    /// Accept U+0009 CHARACTER TABULATION as well as spaces between a boundary and a path
+    pub tabs_after_boundary: bool,
+    /// Skip spaces and tabs between a directory's `/` and the newline after it
+    ///
+    /// Strictly, `<===> dir/ ` is a file called ` ` in `dir`; this makes it the directory `dir` instead,
+    /// but only if nothing but newlines follow it up to the next boundary, so a file with a body keeps its strict meaning.
+    pub trailing_whitespace_after_directory: bool,
+}
+
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            line_endings: LineEndings::Lf,
+            lenient: Leniency::strict(),
+        }
+    }
+}
+
+impl Leniency {
+    /// Accept nothing the spec doesn't allow
+    pub fn strict() -> Leniency {
+        Leniency {
+            byte_order_mark: false,
+            leading_blank_lines: false,
+            tabs_after_boundary: false,
+            trailing_whitespace_after_directory: false,
+        }
+    }
+
+    /// Accept every deviation there is a relaxation for
+    pub fn all() -> Leniency {
+        Leniency {
+            byte_order_mark: true,
+            leading_blank_lines: true,
+            tabs_after_boundary: true,
+            trailing_whitespace_after_directory: true,
+        }
+    }
+}
+
+impl Default for Leniency {
+    fn default() -> Leniency {
+        Leniency::strict()
+    }
+}
+
+impl LineEndings {
+    /// Whether CRLF is accepted as a newline when parsing
+    pub(crate) fn accepts_crlf(&self) -> bool {
+        *self != LineEndings::Lf
+    }
+
+    /// The newline written when serialising
+    pub(crate) fn newline(&self) -> &'static str {
+        match *self {
+            LineEndings::Lf => "\n",
+            LineEndings::CrlfPreserve | LineEndings::CrlfNormalise => "\r\n",
+        }
+    }
+
+    /// Turn a parsed comment or body into what it'll be in the archive
+    pub(crate) fn normalise<'s>(&self, text: &'s str) -> Cow<'s, str> {
+        match *self {
+            LineEndings::CrlfNormalise if text.contains("\r\n") => Cow::Owned(text.replace("\r\n", "\n")),
+            _ => Cow::Borrowed(text),
+        }
+    }
+
+    /// Turn a comment or body in the archive into what it'll be serialised as
+    pub(crate) fn denormalise<'s>(&self, text: &'s str) -> Cow<'s, str> {
+        match *self {
+            LineEndings::CrlfNormalise if text.contains('\n') => Cow::Owned(text.replace('\n', "\r\n")),
+            _ => Cow::Borrowed(text),
+        }
+    }
+}
+
+
+pub fn parse_archive(s: &str, options: &ParseOptions) -> Result<(HrxArchive, Vec<HrxWarning>), HrxError> {
+    let width = parse::discover_first_boundary_length(s).ok_or(HrxError::NoBoundary)?;
+    let ((comment, entries), deviations) = parse::archive_bytes(s.as_bytes(), width, options)?;
+
+    let text = |t: &str| options.line_endings.normalise(t).into_owned();
+    let entries = entries.iter().map(|entry| {
+        let (path, entry) = entry.borrow_from(s);
+        (HrxPath(path.to_string()),
+         HrxEntry {
+             comment: entry.comment.map(&text),
+             data: match entry.data {
+                 HrxEntryDataRef::File { body } => HrxEntryData::File { body: body.map(&text) },
+                 data => data.into_owned(),
+             },
+         })
+    });
+
+    let arch = HrxArchive {
+        comment: comment.map(|c| text(&s[c])),
+        entries: parse::reduce_raw_entries_and_validate_directory_tree(entries)?,
+        boundary_length: width,
+    };
+
+    let mut locator = Locator::new(s.as_bytes());
+    let warnings = deviations.into_iter()
+        .map(|(offset, deviation)| {
+            HrxWarning {
+                position: locator.locate(offset),
+                deviation: deviation,
+            }
+        })
+        .collect();
+    Ok((arch, warnings))
+}
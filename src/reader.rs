@@ -0,0 +1,349 @@
+use self::super::{HrxEntryDataRef, HrxDiagnostic, HrxEntryRef, HrxPosition, HrxArchive, HrxEntry, HrxError, HrxPath, parse};
+use std::io::{Error as IoError, BufRead};
+use std::collections::{BTreeMap, VecDeque};
+use self::super::error::CompoundError;
+use self::super::util::boundary_str;
+use std::iter::FusedIterator;
+use std::num::NonZeroUsize;
+
+
+/// A streaming reader, yielding the entries of an archive one-by-one.
+///
+/// Only the entry currently being read (and the comment preceding it) is kept in memory,
+/// alongside the paths of the entries read so far, which are used to report duplicates and file-as-directory usages,
+/// like [`reduce_raw_entries_and_validate_directory_tree()`](parse/fn.reduce_raw_entries_and_validate_directory_tree.html) does.
+///
+/// The boundary length is discovered from the start of the stream,
+/// like [`discover_first_boundary_length()`](parse/fn.discover_first_boundary_length.html) does.
+///
+/// Iteration stops after the first error; parse errors have positions relative to the start of the stream.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxEntryData, HrxReader, HrxArchive, HrxEntry, HrxError};
+/// # use std::str::FromStr;
+/// let input_text = "<===> input.scss
+/// ul { li { list-style: none; } }
+///
+/// <===>
+/// Generated files
+/// <===> out/
+/// <===>
+/// Archive comment";
+///
+/// let mut reader = HrxReader::new(input_text.as_bytes());
+///
+/// let (path, entry) = reader.next().unwrap().unwrap();
+/// assert_eq!(path.as_ref(), "input.scss");
+/// assert_eq!(entry.data, HrxEntryData::File { body: Some("ul { li { list-style: none; } }\n".to_string()) });
+/// assert_eq!(reader.boundary_length().map(|b| b.get()), Some(3));
+///
+/// let (path, entry) = reader.next().unwrap().unwrap();
+/// assert_eq!(path.as_ref(), "out");
+/// assert_eq!(entry,
+///            HrxEntry {
+///                comment: Some("Generated files".to_string()),
+///                data: HrxEntryData::Directory,
+///            });
+///
+/// assert!(reader.next().is_none());
+/// assert_eq!(reader.comment(), Some("Archive comment"));
+///
+/// let mut reader = HrxReader::new("<===> file\n<===> file\n".as_bytes());
+/// assert!(reader.next().unwrap().is_ok());
+/// assert_eq!(reader.next().unwrap().unwrap_err().unwrap(), HrxError::DuplicateEntry("file".to_string()));
+/// assert!(reader.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct HrxReader<R: BufRead> {
+    input: R,
+    boundary_length: Option<NonZeroUsize>,
+    /// `"<===>"`, i.e. without the leading newline
+    boundary: String,
+    /// Lines read from the input, but not yet consumed
+    pending: VecDeque<String>,
+    /// 1-based line # of the first unconsumed line
+    line: usize,
+    /// Byte offset of the first unconsumed line
+    offset: usize,
+    comment: Option<String>,
+    /// Paths of entries read so far, and whether they're directories
+    paths: BTreeMap<String, bool>,
+    /// Where to record errors when recovering from them, rather than stopping at the first one
+    diagnostics: Option<Vec<HrxDiagnostic>>,
+    done: bool,
+}
+
+/// What [`parse::archive_ref()`](parse/fn.archive_ref.html) returns
+type RawArchiveRef<'a> = (Option<&'a str>, Vec<(&'a str, HrxEntryRef<'a>)>, NonZeroUsize);
+
+
+impl<R: BufRead> HrxReader<R> {
+    /// Create a reader for the archive in the specified stream.
+    ///
+    /// Nothing is read until the first entry is requested.
+    pub fn new(input: R) -> HrxReader<R> {
+        HrxReader {
+            input: input,
+            boundary_length: None,
+            boundary: String::new(),
+            pending: VecDeque::new(),
+            line: 1,
+            offset: 0,
+            comment: None,
+            paths: BTreeMap::new(),
+            diagnostics: None,
+            done: false,
+        }
+    }
+
+    /// Get the boundary length, i.e. the amount of `=` characters in the boundary, if it's been discovered yet.
+    pub fn boundary_length(&self) -> Option<NonZeroUsize> {
+        self.boundary_length
+    }
+
+    /// Get the archive comment, if any.
+    ///
+    /// As the archive comment comes after all entries, this is always `None` before they've all been read.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Unwrap the underlying stream.
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+
+    fn next_entry(&mut self) -> Result<Option<(HrxPath, HrxEntry)>, CompoundError> {
+        let boundary_length = match self.boundary_length {
+            Some(bl) => bl,
+            None => {
+                let bl = self.discover_boundary_length()?;
+                self.boundary = boundary_str(bl)[1..].to_string();
+                self.boundary_length = Some(bl);
+                bl
+            }
+        };
+
+        // Groups only yield nothing at the end of the archive, unless recovering from errors
+        loop {
+            let (group, at_eof) = self.read_group()?;
+            if group.is_empty() {
+                return Ok(None);
+            }
+
+            if let Some(entry) = self.parse_group(group, at_eof, boundary_length)? {
+                return Ok(Some(entry));
+            }
+        }
+    }
+
+    /// Read everything up to the boundary after the first file or directory, comments preceding it included.
+    ///
+    /// Returns whether the end of the input was reached.
+    fn read_group(&mut self) -> Result<(String, bool), IoError> {
+        let mut group = String::new();
+        let mut entry_seen = false;
+        loop {
+            let line = match self.read_line()? {
+                Some(line) => line,
+                None => return Ok((group, true)),
+            };
+
+            if line.starts_with(&self.boundary) {
+                if entry_seen {
+                    self.pending.push_front(line);
+                    return Ok((group, false));
+                }
+                entry_seen = line.trim_end_matches('\n') != self.boundary;
+            }
+            group.push_str(&line);
+        }
+    }
+
+    fn parse_group(&mut self, mut group: String, mut at_eof: bool, boundary_length: NonZeroUsize) -> Result<Option<(HrxPath, HrxEntry)>, CompoundError> {
+        let group_len = group.len();
+
+        if !at_eof {
+            // Bodies end at a newline followed by the boundary, so add a dummy entry for this one's to end at
+            group.push_str(&self.boundary);
+            group.push_str(" x\n");
+
+            let parsed = parse::archive_ref(&group, boundary_length);
+            match parsed {
+                // A body consisting of a single empty line absorbs the rest of the archive, including the dummy
+                Ok((None, ref entries, _)) if entries.len() == 2 => {}
+                Ok(_) => at_eof = true,
+                Err(_) => {}
+            }
+
+            if !at_eof {
+                return self.finish_group(parsed, &group[..group_len]);
+            }
+
+            group.truncate(group_len);
+            while let Some(line) = self.read_line()? {
+                group.push_str(&line);
+            }
+        }
+
+        let parsed = parse::archive_ref(&group, boundary_length);
+        self.finish_group(parsed, &group)
+    }
+
+    fn finish_group(&mut self, parsed: parse::ParseResult<RawArchiveRef>, group: &str) -> Result<Option<(HrxPath, HrxEntry)>, CompoundError> {
+        let (comment, entries, _) = match parsed {
+            Ok(parsed) => parsed,
+            Err(mut pe) => {
+                let group_offset = pe.offset;
+                pe.line += self.line - 1;
+                pe.offset += self.offset;
+
+                let position = HrxPosition {
+                    line: pe.line,
+                    column: pe.column,
+                    offset: pe.offset,
+                };
+                self.report(position, pe.into())?;
+                self.resynchronise(group, group_offset);
+                return Ok(None);
+            }
+        };
+
+        let (line, offset) = (self.line, self.offset);
+        self.line += group.matches('\n').count();
+        self.offset += group.len();
+
+        if let Some(comment) = comment {
+            self.comment = Some(comment.to_string());
+        }
+
+        match entries.into_iter().next() {
+            Some((path, entry)) => {
+                if let Err(err) = parse::validate_its_directory_tree(path, entry.data == HrxEntryDataRef::Directory, &mut self.paths) {
+                    // Point at the entry's boundary line
+                    let path_offset = path.as_ptr() as usize - group.as_ptr() as usize;
+                    let line_start = group[..path_offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+                    let position = HrxPosition {
+                        line: line + group[..line_start].matches('\n').count(),
+                        column: 1,
+                        offset: offset + line_start,
+                    };
+                    self.report(position, err)?;
+                    return Ok(None);
+                }
+
+                Ok(Some((HrxPath(path.to_string()), entry.into_owned())))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Fail with the specified error, or, if recovering from errors, record it
+    fn report(&mut self, position: HrxPosition, error: HrxError) -> Result<(), HrxError> {
+        match self.diagnostics {
+            Some(ref mut diagnostics) => {
+                diagnostics.push(HrxDiagnostic {
+                    position: position,
+                    error: error,
+                });
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Skip the part of the specified group up to the first boundary line after the specified offset,
+    /// leaving the rest to be read again.
+    fn resynchronise(&mut self, group: &str, after: usize) {
+        let resume = group.match_indices('\n')
+            .map(|(i, _)| i + 1)
+            .find(|&i| i > after && group[i..].starts_with(&self.boundary))
+            .unwrap_or(group.len());
+
+        for line in group[resume..].split_inclusive('\n').rev() {
+            self.pending.push_front(line.to_string());
+        }
+
+        self.line += group[..resume].matches('\n').count();
+        self.offset += resume;
+    }
+
+    fn discover_boundary_length(&mut self) -> Result<NonZeroUsize, CompoundError> {
+        let mut seen = String::new();
+        loop {
+            let line = self.read_input_line()?.ok_or(HrxError::NoBoundary)?;
+            seen.push_str(&line);
+            self.pending.push_back(line);
+
+            if let Some(begin) = seen.find('<') {
+                if seen[begin + 1..].contains('>') {
+                    return Ok(parse::discover_first_boundary_length(&seen).ok_or(HrxError::NoBoundary)?);
+                }
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, IoError> {
+        match self.pending.pop_front() {
+            Some(line) => Ok(Some(line)),
+            None => self.read_input_line(),
+        }
+    }
+
+    fn read_input_line(&mut self) -> Result<Option<String>, IoError> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(line))
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for HrxReader<R> {
+    type Item = Result<(HrxPath, HrxEntry), Result<HrxError, IoError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_entry() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err.into()))
+            }
+        }
+    }
+}
+
+impl<R: BufRead> FusedIterator for HrxReader<R> {}
+
+
+/// Parse the specified archive, recording all errors and resynchronising at the boundary line following each one
+pub fn parse_recovering(s: &str) -> Result<(HrxArchive, Vec<HrxDiagnostic>), HrxError> {
+    let mut reader = HrxReader::new(s.as_bytes());
+    reader.diagnostics = Some(vec![]);
+
+    let mut entries = vec![];
+    loop {
+        match reader.next_entry() {
+            Ok(Some(entry)) => entries.push(entry),
+            Ok(None) => break,
+            Err(CompoundError::Hrx(err)) => return Err(err),
+            Err(CompoundError::Io(err)) => unreachable!("Reading from a string can't fail: {}", err),
+        }
+    }
+
+    let mut archive = HrxArchive::new(reader.boundary_length.unwrap());
+    archive.comment = reader.comment;
+    archive.entries.extend(entries);
+    Ok((archive, reader.diagnostics.unwrap()))
+}
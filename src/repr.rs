@@ -1,12 +1,38 @@
-use self::super::{parse, ErroneousBodyPath, HrxError};
-use jetscii::Substring as SubstringSearcher;
-use self::super::output::write_archive;
+use self::super::{parse, SkippedFiles, InvalidPathReason, HrxArchiveSpans, ErroneousBodyPath, ExtractOptions, HrxArchiveRef, HrxDiagnostic, HrxWalkOrder, GlobMatches,
+                  ParseOptions, PackOptions, LineEndings, HrxWarning, HrxError, HrxDiff, HrxTree, HrxWalk, Glob};
+use self::super::reader::parse_recovering;
+use self::super::diff::diff_archives;
+use jetscii::ByteSubstring as SubstringSearcher;
+use self::super::output::{write_archive_with_boundary_length, write_archive_with_line_endings, write_archive};
+use self::super::parse_options::parse_archive;
 use std::io::{Error as IoError, Write};
-use self::super::util::boundary_str;
+use self::super::extract::extract_archive;
+use self::super::pack::pack_directory;
+#[cfg(feature = "tar")]
+use self::super::tarball::{read_tar, write_tar};
+#[cfg(feature = "tar")]
+use self::super::TarOptions;
+#[cfg(feature = "zip")]
+use self::super::zipfile::{read_zip, write_zip};
+#[cfg(feature = "zip")]
+use self::super::ZipOptions;
+#[cfg(feature = "zip")]
+use std::io::Seek;
+#[cfg(any(feature = "tar", feature = "zip"))]
+use std::io::Read;
+#[cfg(feature = "mmap")]
+use self::super::mapped::map_file;
+#[cfg(feature = "mmap")]
+use self::super::HrxMmap;
+use std::path::{Component, PathBuf, Path};
+use self::super::util::{invalid_windows_path_component, boundary_str};
 use linked_hash_map::LinkedHashMap;
 use std::num::NonZeroUsize;
+use std::convert::TryFrom;
+use std::mem;
 use std::borrow::Borrow;
-use std::str::FromStr;
+use std::ffi::OsStr;
+use std::str::{FromStr, Split};
 use std::fmt;
 
 
@@ -24,7 +50,14 @@ use std::fmt;
 ///   2. serialising to an output stream (usually via [`serialise()`](#method.serialise))
 ///
 /// and return the paths to the erroneous (i.e. boundary-containing) `body`s.
+///
+/// Alternatively, [`serialise_auto()`](#method.serialise_auto) will pick the shortest boundary length valid for the current contents,
+/// so it can be used to write out any archive.
+///
+/// With the `serde` feature, archives can be (de)serialised as a structure of the comment, the entries (as a map, in order),
+/// and the boundary length; deserialisation checks the directory tree and the boundary's validity, like parsing does.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct HrxArchive {
     /// Some optional metadata.
     ///
@@ -38,6 +71,7 @@ pub struct HrxArchive {
 
 /// A single entry in the archive, consisting of an optional comment and some data.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct HrxEntry {
     /// Some optional metadata.
     ///
@@ -49,11 +83,20 @@ pub struct HrxEntry {
 
 /// Some variant of an entry's contained data.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum HrxEntryData {
     /// File with some optional contents.
     ///
     /// Cannot contain a newline followed by a boundary nor start with a boundary.
     File { body: Option<String>, },
+    /// File with contents that aren't valid UTF-8.
+    ///
+    /// Only produced by [`HrxArchive::from_bytes()`](struct.HrxArchive.html#method.from_bytes)
+    /// and by packing with [`BinaryPolicy::Raw`](enum.BinaryPolicy.html#variant.Raw);
+    /// archives containing these serialise into bytes which aren't valid UTF-8 either.
+    ///
+    /// Cannot contain a newline followed by a boundary nor start with a boundary.
+    Binary { body: Vec<u8>, },
     /// Bodyless directory.
     Directory,
 }
@@ -89,6 +132,390 @@ impl HrxArchive {
         }
     }
 
+    /// Pack the directory tree rooted at the specified path into an archive.
+    ///
+    /// Files are added with their contents as bodies, and directories are descended into recursively;
+    /// explicit `Directory` entries are only added for empty directories, as others exist implicitly.
+    ///
+    /// The boundary length is set to the shortest one valid for the resulting archive,
+    /// but no shorter than [`PackOptions::min_boundary_length`](struct.PackOptions.html#structfield.min_boundary_length).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{PackOptions, HrxArchive};
+    /// # use std::env::temp_dir;
+    /// # use std::fs;
+    /// let root = temp_dir().join("hrx.rs-doctest-from_dir");
+    /// # let _ = fs::remove_dir_all(&root);
+    /// fs::create_dir_all(root.join("out")).unwrap();
+    /// fs::create_dir_all(root.join("empty")).unwrap();
+    /// fs::write(root.join("input.scss"), "ul { li { list-style: none; } }\n").unwrap();
+    /// fs::write(root.join("out").join("input.css"), "ul li { list-style: none; }\n").unwrap();
+    ///
+    /// let arch = HrxArchive::from_dir(&root, &PackOptions::default()).unwrap();
+    /// let mut out = vec![];
+    /// arch.serialise(&mut out).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), r#"<===> empty/
+    /// <===> input.scss
+    /// ul { li { list-style: none; } }
+    ///
+    /// <===> out/input.css
+    /// ul li { list-style: none; }
+    /// "#);
+    /// ```
+    pub fn from_dir<P: AsRef<Path>>(dir: P, options: &PackOptions) -> Result<HrxArchive, Result<HrxError, IoError>> {
+        pack_directory(dir.as_ref(), options).map(|(arch, _)| arch)
+    }
+
+    /// Pack the specified directory into an archive, as configured,
+    /// also returning the filesystem paths of all files left out as per [`UnrepresentablePolicy::Skip`](enum.UnrepresentablePolicy.html#variant.Skip),
+    /// and why, in the order they were encountered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{UnrepresentableReason, UnrepresentablePolicy, PackOptions, HrxArchive};
+    /// # use std::env::temp_dir;
+    /// # use std::fs;
+    /// let root = temp_dir().join("hrx.rs-doctest-from_dir_with_skipped");
+    /// # let _ = fs::remove_dir_all(&root);
+    /// fs::create_dir_all(&root).unwrap();
+    /// fs::write(root.join("input.scss"), "ul {}\n").unwrap();
+    /// fs::write(root.join("logo.png"), b"\x89PNG\r\n\x1A\n").unwrap();
+    ///
+    /// let (arch, skipped) = HrxArchive::from_dir_with_skipped(&root,
+    ///                                                         &PackOptions {
+    ///                                                             unrepresentable: UnrepresentablePolicy::Skip,
+    ///                                                             ..PackOptions::default()
+    ///                                                         })
+    ///     .unwrap();
+    /// assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["input.scss"]);
+    /// assert_eq!(skipped, vec![(root.join("logo.png").display().to_string(), UnrepresentableReason::NonUtf8Contents)]);
+    /// ```
+    pub fn from_dir_with_skipped<P: AsRef<Path>>(dir: P, options: &PackOptions)
+                                                 -> Result<(HrxArchive, SkippedFiles), Result<HrxError, IoError>> {
+        pack_directory(dir.as_ref(), options)
+    }
+
+    /// Read the tar archive from the specified stream into an archive.
+    ///
+    /// Regular files are added with their contents as bodies, and hard links to them as copies thereof;
+    /// explicit `Directory` entries are only added for empty directories, as others exist implicitly.
+    /// Leading `./` components are stripped.
+    ///
+    /// The boundary length is set to the shortest one valid for the resulting archive,
+    /// but no shorter than [`TarOptions::min_boundary_length`](struct.TarOptions.html#structfield.min_boundary_length).
+    ///
+    /// Only available with the `tar` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{TarOptions, HrxArchive};
+    /// # use std::str::FromStr;
+    /// let arch = HrxArchive::from_str("<===> input.scss\nul {}\n<===> out/input.css\nul {}\n<===> empty/\n").unwrap();
+    /// let mut tar = vec![];
+    /// arch.to_tar(&mut tar).unwrap();
+    ///
+    /// assert_eq!(HrxArchive::from_tar(&tar[..], &TarOptions::default()).unwrap(), arch);
+    /// ```
+    #[cfg(feature = "tar")]
+    pub fn from_tar<R: Read>(tar: R, options: &TarOptions) -> Result<HrxArchive, Result<HrxError, IoError>> {
+        read_tar(tar, options).map(|(arch, _)| arch)
+    }
+
+    /// Read the tar archive from the specified stream into an archive,
+    /// also returning the tar paths of all entries left out as per [`UnrepresentablePolicy::Skip`](enum.UnrepresentablePolicy.html#variant.Skip),
+    /// and why, in archive order.
+    ///
+    /// Only available with the `tar` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate hrx;
+    /// # extern crate tar;
+    /// # use hrx::{UnrepresentableReason, UnrepresentablePolicy, TarOptions, HrxArchive};
+    /// # use tar::{EntryType, Builder, Header};
+    /// # fn main() {
+    /// let mut tar = Builder::new(vec![]);
+    /// let mut header = Header::new_gnu();
+    /// header.set_entry_type(EntryType::Symlink);
+    /// header.set_size(0);
+    /// tar.append_link(&mut header, "link", "target").unwrap();
+    /// let tar = tar.into_inner().unwrap();
+    ///
+    /// let (arch, skipped) = HrxArchive::from_tar_with_skipped(&tar[..],
+    ///                                                         &TarOptions {
+    ///                                                             unrepresentable: UnrepresentablePolicy::Skip,
+    ///                                                             ..TarOptions::default()
+    ///                                                         })
+    ///     .unwrap();
+    /// assert!(arch.entries.is_empty());
+    /// assert_eq!(skipped, vec![("link".to_string(), UnrepresentableReason::Symlink)]);
+    /// # }
+    /// ```
+    #[cfg(feature = "tar")]
+    pub fn from_tar_with_skipped<R: Read>(tar: R, options: &TarOptions)
+                                          -> Result<(HrxArchive, SkippedFiles), Result<HrxError, IoError>> {
+        read_tar(tar, options)
+    }
+
+    /// Read the zip archive from the specified stream into an archive.
+    ///
+    /// Regular files are added with their contents as bodies, and each zip entry's comment becomes the entry's comment,
+    /// as the zip archive's comment does the archive's;
+    /// explicit `Directory` entries are only added for empty or commented directories, as others exist implicitly.
+    ///
+    /// The boundary length is set to the shortest one valid for the resulting archive,
+    /// but no shorter than [`ZipOptions::min_boundary_length`](struct.ZipOptions.html#structfield.min_boundary_length).
+    ///
+    /// Only available with the `zip` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{ZipOptions, HrxArchive};
+    /// # use std::str::FromStr;
+    /// # use std::io::Cursor;
+    /// let arch = HrxArchive::from_str("<===>
+    /// Compiled from SCSS
+    /// <===> input.css
+    /// ul li {}
+    /// <===>
+    /// Test fixtures
+    /// ").unwrap();
+    /// let mut zip = vec![];
+    /// arch.to_zip(&mut zip).unwrap();
+    ///
+    /// assert_eq!(HrxArchive::from_zip(Cursor::new(zip), &ZipOptions::default()).unwrap(), arch);
+    /// ```
+    #[cfg(feature = "zip")]
+    pub fn from_zip<R: Read + Seek>(zip: R, options: &ZipOptions) -> Result<HrxArchive, Result<HrxError, IoError>> {
+        read_zip(zip, options).map(|(arch, _)| arch)
+    }
+
+    /// Read the zip archive from the specified stream into an archive,
+    /// also returning the zip paths of all entries left out as per [`UnrepresentablePolicy::Skip`](enum.UnrepresentablePolicy.html#variant.Skip),
+    /// and why, in archive order.
+    ///
+    /// Only available with the `zip` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate hrx;
+    /// # extern crate zip;
+    /// # use hrx::{UnrepresentableReason, UnrepresentablePolicy, ZipOptions, HrxArchive};
+    /// # use zip::write::{FileOptions, ZipWriter};
+    /// # use std::io::{Cursor, Write};
+    /// # fn main() {
+    /// let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    /// zip.start_file("input.scss", FileOptions::default()).unwrap();
+    /// zip.write_all(b"ul {}\n").unwrap();
+    /// zip.start_file("fi:le", FileOptions::default()).unwrap();
+    /// let zip = zip.finish().unwrap();
+    ///
+    /// let (arch, skipped) = HrxArchive::from_zip_with_skipped(zip,
+    ///                                                         &ZipOptions {
+    ///                                                             unrepresentable: UnrepresentablePolicy::Skip,
+    ///                                                             ..ZipOptions::default()
+    ///                                                         })
+    ///     .unwrap();
+    /// assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["input.scss"]);
+    /// assert_eq!(skipped, vec![("fi:le".to_string(), UnrepresentableReason::InvalidPath)]);
+    /// # }
+    /// ```
+    #[cfg(feature = "zip")]
+    pub fn from_zip_with_skipped<R: Read + Seek>(zip: R, options: &ZipOptions)
+                                                 -> Result<(HrxArchive, SkippedFiles), Result<HrxError, IoError>> {
+        read_zip(zip, options)
+    }
+
+    /// Parse the specified bytes into an archive, allowing file contents that aren't valid UTF-8.
+    ///
+    /// Bodies which are valid UTF-8 become `File`s, and those which aren't become
+    /// [`Binary`](enum.HrxEntryData.html#variant.Binary) entries.
+    /// Boundaries, paths and comments still need to be valid UTF-8, and a parse error pointing at the first offending byte is returned otherwise.
+    ///
+    /// Input which is valid UTF-8 in its entirety is parsed the same as by
+    /// [`from_str()`](#impl-FromStr).
+    ///
+    /// Equivalent to [`HrxArchiveRef::from_bytes()`](struct.HrxArchiveRef.html#method.from_bytes)
+    /// followed by [`into_owned()`](struct.HrxArchiveRef.html#method.into_owned).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxEntryData, HrxArchive, HrxError};
+    /// let arch = HrxArchive::from_bytes(b"<===> latin-1.txt\nCaf\xE9\n<===> utf-8.txt\nCaf\xC3\xA9\n").unwrap();
+    /// assert_eq!(arch.entries["latin-1.txt"].data, HrxEntryData::Binary { body: b"Caf\xE9".to_vec() });
+    /// assert_eq!(arch.entries["utf-8.txt"].data, HrxEntryData::File { body: Some("Café\n".to_string()) });
+    ///
+    /// let mut out = vec![];
+    /// arch.serialise(&mut out).unwrap();
+    /// assert_eq!(out, b"<===> latin-1.txt\nCaf\xE9\n<===> utf-8.txt\nCaf\xC3\xA9\n");
+    ///
+    /// match HrxArchive::from_bytes(b"<===> caf\xE9.txt\n") {
+    ///     Err(HrxError::Parse(err)) => assert_eq!((err.line, err.column), (1, 10)),
+    ///     err => panic!("{:?}", err),
+    /// }
+    /// ```
+    pub fn from_bytes(data: &[u8]) -> Result<HrxArchive, HrxError> {
+        HrxArchiveRef::from_bytes(data).map(HrxArchiveRef::into_owned)
+    }
+
+    /// Memory-map the archive file at the specified path, to parse it into an archive borrowing from the mapping,
+    /// without reading it into memory first.
+    ///
+    /// Only available with the `mmap` feature.
+    ///
+    /// # Safety
+    ///
+    /// The file mustn't be modified or truncated, by this or any other process, for as long as it's mapped,
+    /// as the mapping and everything borrowed from it would change with it:
+    /// strings borrowed from it could stop being valid UTF-8, and reading past a truncated end raises `SIGBUS`.
+    /// This is the same contract as [`memmap2::Mmap::map()`](https://docs.rs/memmap2/*/memmap2/struct.Mmap.html#method.map).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxEntryDataRef, HrxArchive};
+    /// # use std::env::temp_dir;
+    /// # use std::str::FromStr;
+    /// # use std::fs;
+    /// # let path = temp_dir().join("hrx.rs-doctest-open_mmap.hrx");
+    /// fs::write(&path, "<===> input.scss\nul {}\n<===> out/\n").unwrap();
+    ///
+    /// // Nothing else writes to the file while it's mapped
+    /// let map = unsafe { HrxArchive::open_mmap(&path).unwrap() };
+    /// let arch = map.archive().unwrap();
+    /// assert_eq!(arch.get("input.scss").unwrap().data, HrxEntryDataRef::File { body: Some("ul {}") });
+    /// assert_eq!(arch.into_owned(), HrxArchive::from_str("<===> input.scss\nul {}\n<===> out/\n").unwrap());
+    /// ```
+    #[cfg(feature = "mmap")]
+    pub unsafe fn open_mmap<P: AsRef<Path>>(path: P) -> Result<HrxMmap, IoError> {
+        map_file(path.as_ref())
+    }
+
+    /// Parse the specified string into an archive, as configured.
+    ///
+    /// With the default options, this is the same as [`from_str()`](#impl-FromStr).
+    ///
+    /// Deviations accepted as per [`ParseOptions::lenient`](struct.ParseOptions.html#structfield.lenient) aren't reported;
+    /// use [`parse_with_warnings()`](#method.parse_with_warnings) to find out about them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxEntryData, ParseOptions, LineEndings, HrxArchive};
+    /// let arch_str = "<===> input.scss\r\nul {\r\n}\r\n<===> out/\r\n";
+    ///
+    /// let options = ParseOptions { line_endings: LineEndings::CrlfPreserve, ..ParseOptions::default() };
+    /// let arch = HrxArchive::parse_with_options(arch_str, &options).unwrap();
+    /// assert_eq!(arch.entries["input.scss"].data, HrxEntryData::File { body: Some("ul {\r\n}".to_string()) });
+    ///
+    /// let options = ParseOptions { line_endings: LineEndings::CrlfNormalise, ..ParseOptions::default() };
+    /// let arch = HrxArchive::parse_with_options(arch_str, &options).unwrap();
+    /// assert_eq!(arch.entries["input.scss"].data, HrxEntryData::File { body: Some("ul {\n}".to_string()) });
+    ///
+    /// let mut out = vec![];
+    /// arch.serialise_with_line_endings(&mut out, LineEndings::CrlfNormalise).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), arch_str);
+    ///
+    /// assert!(HrxArchive::parse_with_options(arch_str, &ParseOptions::default()).is_err());
+    /// ```
+    pub fn parse_with_options(s: &str, options: &ParseOptions) -> Result<HrxArchive, HrxError> {
+        parse_archive(s, options).map(|(arch, _)| arch)
+    }
+
+    /// Parse the specified string into an archive, as configured, also returning all deviations from the spec accepted, in order.
+    ///
+    /// None of them make it into the archive, so serialising it yields the strict form of the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{ParseOptions, HrxArchive, Deviation, Leniency};
+    /// # use std::str::FromStr;
+    /// let arch_str = "\u{FEFF}\n<===>\tinput.scss\nul {}\n<===> out/  \n";
+    /// let options = ParseOptions {
+    ///     lenient: Leniency::all(),
+    ///     ..ParseOptions::default()
+    /// };
+    ///
+    /// let (arch, warnings) = HrxArchive::parse_with_warnings(arch_str, &options).unwrap();
+    /// assert_eq!(warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+    ///            vec!["1:1: Byte order mark before archive",
+    ///                 "1:2: Blank lines before first boundary",
+    ///                 "2:6: Tab after boundary",
+    ///                 "4:11: Whitespace after directory"]);
+    /// assert_eq!(warnings[2].deviation, Deviation::TabAfterBoundary);
+    ///
+    /// let mut out = vec![];
+    /// arch.serialise(&mut out).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "<===> input.scss\nul {}\n<===> out/\n");
+    ///
+    /// assert!(HrxArchive::from_str(arch_str).is_err());
+    /// ```
+    pub fn parse_with_warnings(s: &str, options: &ParseOptions) -> Result<(HrxArchive, Vec<HrxWarning>), HrxError> {
+        parse_archive(s, options)
+    }
+
+    /// Parse the specified string into an archive, also returning where each of its parts came from therein.
+    ///
+    /// Equivalent to [`HrxArchiveRef::parse()`](struct.HrxArchiveRef.html#method.parse) followed by
+    /// [`spans()`](struct.HrxArchiveRef.html#method.spans) and [`into_owned()`](struct.HrxArchiveRef.html#method.into_owned).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxArchive, HrxPath};
+    /// # use std::str::FromStr;
+    /// let (arch, spans) = HrxArchive::parse_with_spans("<===> input.scss\nul {}\n<===> output.css\n").unwrap();
+    /// assert_eq!(arch, HrxArchive::from_str("<===> input.scss\nul {}\n<===> output.css\n").unwrap());
+    ///
+    /// let output = &spans.entries[&HrxPath::from_str("output.css").unwrap()];
+    /// assert_eq!(format!("fixture.hrx:{}", output.boundary_line.start), "fixture.hrx:3:1");
+    /// assert_eq!(output.body, None);
+    /// ```
+    pub fn parse_with_spans(s: &str) -> Result<(HrxArchive, HrxArchiveSpans), HrxError> {
+        let arch = HrxArchiveRef::parse(s)?;
+        let spans = arch.spans();
+        Ok((arch.into_owned(), spans))
+    }
+
+    /// Parse the specified string into an archive, collecting all problems instead of stopping at the first one.
+    ///
+    /// After each parse error, parsing resumes at the next boundary line, skipping the malformed parts;
+    /// entries which would be duplicates or use a file as a directory are left out.
+    /// The resulting archive consists of whatever could be parsed.
+    ///
+    /// Only fails if no boundary could be found at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxArchive, HrxError};
+    /// let (arch, diagnostics) = HrxArchive::parse_with_diagnostics("<===> file
+    /// <===> dir/
+    /// A directory can't have contents
+    /// <===> fi:le
+    /// <===> file
+    /// <===> ok.txt
+    /// ").unwrap();
+    ///
+    /// assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["file", "ok.txt"]);
+    /// assert_eq!(diagnostics.iter().map(|d| d.position.to_string()).collect::<Vec<_>>(), vec!["3:1", "4:9", "5:1"]);
+    /// assert_eq!(diagnostics[2].error, HrxError::DuplicateEntry("file".to_string()));
+    ///
+    /// assert_eq!(HrxArchive::parse_with_diagnostics("no boundary"), Err(HrxError::NoBoundary));
+    /// ```
+    pub fn parse_with_diagnostics(s: &str) -> Result<(HrxArchive, Vec<HrxDiagnostic>), HrxError> {
+        parse_recovering(s)
+    }
+
     /// Get the current boundary length, i.e. the amount of `=` characters in the boundary.
     ///
     /// # Examples
@@ -197,21 +624,69 @@ impl HrxArchive {
         self.validate_boundlen(self.boundary_length)
     }
 
+    /// Find the shortest boundary length not contained in any `body` in the archive.
+    ///
+    /// All comments and file contents are scanned once for lines starting with a boundary,
+    /// then the smallest length not seen is returned.
+    ///
+    /// Lines at the very start of a `body` are considered as well,
+    /// since, when serialised, those always directly follow a newline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxEntryData, HrxArchive, HrxEntry};
+    /// # use std::num::NonZeroUsize;
+    /// let mut arch = HrxArchive::new(NonZeroUsize::new(3).unwrap());
+    /// assert_eq!(arch.minimal_boundary_length().get(), 1);
+    ///
+    /// arch.comment = Some("<=>\nThe comment contains 1-length\n<==>\nand 2-length boundaries".to_string());
+    /// arch.entries.insert("file.hrx".parse().unwrap(), HrxEntry {
+    ///     comment: None,
+    ///     data: HrxEntryData::File {
+    ///         body: Some("<===> boundary-3.txt\n<====>\n^ 4, but not <=====> 5".to_string()),
+    ///     },
+    /// });
+    ///
+    /// assert_eq!(arch.minimal_boundary_length().get(), 5);
+    /// assert_eq!(arch.set_boundary_length(arch.minimal_boundary_length()), Ok(()));
+    /// ```
+    pub fn minimal_boundary_length(&self) -> NonZeroUsize {
+        self.minimal_boundary_length_from(NonZeroUsize::new(1).unwrap())
+    }
+
+    /// Like [`minimal_boundary_length()`](#method.minimal_boundary_length), but doesn't consider lengths shorter than `min`.
+    pub(crate) fn minimal_boundary_length_from(&self, min: NonZeroUsize) -> NonZeroUsize {
+        let mut used = vec![true];
+
+        mark_boundary_lengths(text_bytes(&self.comment), &mut used);
+        for dt in self.entries.values() {
+            mark_boundary_lengths(text_bytes(&dt.comment), &mut used);
+            match dt.data {
+                HrxEntryData::File { ref body } => mark_boundary_lengths(text_bytes(body), &mut used),
+                HrxEntryData::Binary { ref body } => mark_boundary_lengths(Some(body), &mut used),
+                HrxEntryData::Directory => {}
+            }
+        }
+
+        NonZeroUsize::new((min.get()..).find(|&len| !used.get(len).cloned().unwrap_or(false)).unwrap()).unwrap()
+    }
+
     fn validate_boundlen(&self, len: NonZeroUsize) -> Result<(), HrxError> {
         let bound = boundary_str(len);
-        let ss = SubstringSearcher::new(&bound);
+        let ss = SubstringSearcher::new(bound.as_bytes());
 
         let mut paths = vec![];
 
-        let _ = verify_opt(&self.comment, &ss).map_err(|_| paths.push(ErroneousBodyPath::RootComment));
+        let _ = verify_opt(text_bytes(&self.comment), &ss).map_err(|_| paths.push(ErroneousBodyPath::RootComment));
         for (pp, dt) in &self.entries {
-            let _ = verify_opt(&dt.comment, &ss).map_err(|_| paths.push(ErroneousBodyPath::EntryComment(pp.to_string())));
-            match dt.data {
-                HrxEntryData::File { ref body } => {
-                    let _ = verify_opt(&body, &ss).map_err(|_| paths.push(ErroneousBodyPath::EntryData(pp.to_string())));
-                }
-                HrxEntryData::Directory => {}
-            }
+            let _ = verify_opt(text_bytes(&dt.comment), &ss).map_err(|_| paths.push(ErroneousBodyPath::EntryComment(pp.to_string())));
+            let body = match dt.data {
+                HrxEntryData::File { ref body } => text_bytes(body),
+                HrxEntryData::Binary { ref body } => Some(&body[..]),
+                HrxEntryData::Directory => None,
+            };
+            let _ = verify_opt(body, &ss).map_err(|_| paths.push(ErroneousBodyPath::EntryData(pp.to_string())));
         }
 
         if !paths.is_empty() {
@@ -308,10 +783,320 @@ impl HrxArchive {
     pub fn serialise<W: Write>(&self, into: &mut W) -> Result<(), Result<HrxError, IoError>> {
         write_archive(&self, into)
     }
+
+    /// Write the archive out to the specified output stream, with the specified line endings.
+    ///
+    /// With [`LineEndings::Lf`](enum.LineEndings.html#variant.Lf), this is the same as [`serialise()`](#method.serialise).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{LineEndings, HrxArchive};
+    /// # use std::str::FromStr;
+    /// let arch = HrxArchive::from_str("<===> input.scss\nul {\n}\n<===>\nThe end").unwrap();
+    ///
+    /// let mut out = vec![];
+    /// arch.serialise_with_line_endings(&mut out, LineEndings::CrlfPreserve).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "<===> input.scss\r\nul {\n}\r\n<===>\r\nThe end");
+    ///
+    /// let mut out = vec![];
+    /// arch.serialise_with_line_endings(&mut out, LineEndings::CrlfNormalise).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "<===> input.scss\r\nul {\r\n}\r\n<===>\r\nThe end");
+    /// ```
+    pub fn serialise_with_line_endings<W: Write>(&self, into: &mut W, line_endings: LineEndings) -> Result<(), Result<HrxError, IoError>> {
+        write_archive_with_line_endings(&self, line_endings, into)
+    }
+
+    /// Write the archive out to the specified output stream,
+    /// using the [shortest valid boundary length](#method.minimal_boundary_length) instead of the current one.
+    ///
+    /// The archive itself is left unchanged, the boundary length used is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxEntryData, HrxArchive, HrxEntry};
+    /// # use std::num::NonZeroUsize;
+    /// # use std::str::FromStr;
+    /// let mut arch = HrxArchive::new(NonZeroUsize::new(1).unwrap());
+    /// arch.entries.insert("nested.hrx".parse().unwrap(), HrxEntry {
+    ///     comment: None,
+    ///     data: HrxEntryData::File {
+    ///         body: Some("Nested archive, using the 1-length boundary:\n<=> file.txt\n".to_string()),
+    ///     },
+    /// });
+    ///
+    /// let mut out = vec![];
+    /// assert!(arch.serialise(&mut out).is_err());
+    ///
+    /// out.clear();
+    /// assert_eq!(arch.serialise_auto(&mut out).unwrap().get(), 2);
+    /// assert_eq!(String::from_utf8(out).unwrap(), r#"<==> nested.hrx
+    /// Nested archive, using the 1-length boundary:
+    /// <=> file.txt
+    /// "#);
+    /// assert_eq!(arch.boundary_length().get(), 1);
+    /// ```
+    pub fn serialise_auto<W: Write>(&self, into: &mut W) -> Result<NonZeroUsize, IoError> {
+        let boundary_length = self.minimal_boundary_length();
+        write_archive_with_boundary_length(&self, boundary_length, into)?;
+        Ok(boundary_length)
+    }
+
+    /// Write the archive out as a tar stream.
+    ///
+    /// Directories are written before their contents, whether they're explicit entries or not.
+    ///
+    /// The metadata is deterministic, so that the same archive always yields the same tar stream:
+    /// everything is owned by root and was modified at the epoch, files have mode `0644` and directories `0755`.
+    ///
+    /// Only available with the `tar` feature.
+    #[cfg(feature = "tar")]
+    pub fn to_tar<W: Write>(&self, into: &mut W) -> Result<(), IoError> {
+        write_tar(self, into)
+    }
+
+    /// Write the archive out as a zip archive, with the archive and entry comments as the zip ones.
+    ///
+    /// Directories are written before their contents, whether they're explicit entries or not.
+    /// Files are stored uncompressed, as that's the point of HRX.
+    ///
+    /// The metadata is deterministic, so that the same archive always yields the same zip archive:
+    /// everything was modified at the DOS epoch of 1980-01-01, files have mode `0644` and directories `0755`.
+    ///
+    /// ZIP64 isn't supported, so archives over 4GiB or with more than 65535 entries fail with `InvalidInput`.
+    ///
+    /// Only available with the `zip` feature.
+    #[cfg(feature = "zip")]
+    pub fn to_zip<W: Write>(&self, into: &mut W) -> Result<(), IoError> {
+        write_zip(self, into)
+    }
+
+    /// Get a directory tree view over the archive's entries.
+    ///
+    /// See [`HrxTree`](struct.HrxTree.html) for details.
+    pub fn tree(&self) -> HrxTree<'_> {
+        HrxTree::new(&self.entries)
+    }
+
+    /// Get the paths of the immediate children of the specified directory, in archive order,
+    /// or `None` if it's not a directory.
+    ///
+    /// The directory can exist either explicitly or implicitly, and the empty path refers to the root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxArchive;
+    /// # use std::str::FromStr;
+    /// let arch = HrxArchive::from_str("<===> input.scss\n<===> out/css/input.css\n<===> out/input.map\n").unwrap();
+    ///
+    /// assert_eq!(arch.children(""), Some(vec!["input.scss", "out"]));
+    /// assert_eq!(arch.children("out"), Some(vec!["out/css", "out/input.map"]));
+    /// assert_eq!(arch.children("out/css/input.css"), None);
+    /// assert_eq!(arch.children("in"), None);
+    /// ```
+    pub fn children(&self, dir: &str) -> Option<Vec<&str>> {
+        let tree = self.tree();
+        let node = tree.get(dir).filter(|n| n.is_dir())?;
+        Some(node.children().map(|c| c.path()).collect())
+    }
+
+    /// Check whether the specified path is a directory, be it one with an explicit entry,
+    /// or one existing implicitly as the parent of another entry.
+    ///
+    /// The empty path refers to the root, which is always a directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxArchive;
+    /// # use std::str::FromStr;
+    /// let arch = HrxArchive::from_str("<===> empty/\n<===> a/b/file\n").unwrap();
+    ///
+    /// assert!(arch.is_dir("empty"));
+    /// assert!(arch.is_dir("a"));
+    /// assert!(arch.is_dir("a/b"));
+    /// assert!(!arch.is_dir("a/b/file"));
+    /// assert!(!arch.is_dir("a/b/c"));
+    /// assert!(!arch.is_dir("a/"));
+    /// ```
+    pub fn is_dir(&self, path: &str) -> bool {
+        if path.is_empty() {
+            return true;
+        }
+
+        match self.entries.get(path) {
+            Some(entry) => entry.data == HrxEntryData::Directory,
+            None => self.entries.keys().any(|p| p.0.len() > path.len() && p.0.starts_with(path) && p.0.as_bytes()[path.len()] == b'/'),
+        }
+    }
+
+    /// Iterate over all entries in the archive, implicit directories included, in the specified order.
+    ///
+    /// Equivalent to [`tree()`](#method.tree) followed by [`walk()`](struct.HrxTree.html#method.walk).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxWalkOrder, HrxArchive};
+    /// # use std::str::FromStr;
+    /// let arch = HrxArchive::from_str("<===> a/b/file\n<===> c\n<===> a/d/\n").unwrap();
+    ///
+    /// assert_eq!(arch.walk(HrxWalkOrder::DepthFirst).map(|(p, e)| (p, e.is_some())).collect::<Vec<_>>(),
+    ///            vec![("a", false), ("a/b", false), ("a/b/file", true), ("a/d", true), ("c", true)]);
+    /// assert_eq!(arch.walk(HrxWalkOrder::BreadthFirst).map(|(p, _)| p).collect::<Vec<_>>(),
+    ///            vec!["a", "c", "a/b", "a/d", "a/b/file"]);
+    /// ```
+    pub fn walk(&self, order: HrxWalkOrder) -> HrxWalk<'_, '_> {
+        self.tree().into_walk(order)
+    }
+
+    /// Iterate over the entries whose paths match the specified [glob](struct.Glob.html), in archive order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxArchive;
+    /// # use std::str::FromStr;
+    /// let arch = HrxArchive::from_str("<===> input.scss
+    /// <===> out/input.css
+    /// <===> out/nested/more.css
+    /// <===> out/input.css.map
+    /// ").unwrap();
+    ///
+    /// assert_eq!(arch.glob("out/**/*.css").map(|(p, _)| p.as_ref()).collect::<Vec<_>>(),
+    ///            vec!["out/input.css", "out/nested/more.css"]);
+    /// assert_eq!(arch.glob("*.s[ac]ss").count(), 1);
+    /// ```
+    pub fn glob(&self, pattern: &str) -> GlobMatches<'_> {
+        GlobMatches::new(Glob::new(pattern), self.entries.iter())
+    }
+
+    /// Remove all entries whose paths don't match the specified [glob](struct.Glob.html).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxArchive;
+    /// # use std::str::FromStr;
+    /// let mut arch = HrxArchive::from_str("<===> input.scss\n<===> out/input.css\n<===> out/input.css.map\n").unwrap();
+    /// arch.retain_glob("**/*.css");
+    /// assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["out/input.css"]);
+    /// ```
+    pub fn retain_glob(&mut self, pattern: &str) {
+        self.partition_glob(&Glob::new(pattern), true);
+    }
+
+    /// Remove all entries whose paths match the specified [glob](struct.Glob.html), returning them in archive order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxArchive;
+    /// # use std::str::FromStr;
+    /// let mut arch = HrxArchive::from_str("<===> input.scss\n<===> out/input.css\n<===> out/input.css.map\n").unwrap();
+    /// let removed = arch.remove_glob("out/**");
+    /// assert_eq!(removed.iter().map(|(p, _)| p.as_ref()).collect::<Vec<_>>(), vec!["out/input.css", "out/input.css.map"]);
+    /// assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["input.scss"]);
+    /// ```
+    pub fn remove_glob(&mut self, pattern: &str) -> Vec<(HrxPath, HrxEntry)> {
+        self.partition_glob(&Glob::new(pattern), false)
+    }
+
+    /// Keep the entries that match `glob` iff `keep_matching`, returning the rest
+    fn partition_glob(&mut self, glob: &Glob, keep_matching: bool) -> Vec<(HrxPath, HrxEntry)> {
+        let mut removed = vec![];
+        for (path, entry) in mem::replace(&mut self.entries, LinkedHashMap::new()) {
+            if glob.matches(&path.0) == keep_matching {
+                self.entries.insert(path, entry);
+            } else {
+                removed.push((path, entry));
+            }
+        }
+        removed
+    }
+
+    /// Compare this (old) archive to the specified (new) one, entry-by-entry.
+    ///
+    /// See [`HrxDiff`](struct.HrxDiff.html) for details.
+    pub fn diff(&self, other: &HrxArchive) -> HrxDiff {
+        diff_archives(self, other)
+    }
+
+    /// Write the archive's entries out as a directory tree rooted at the specified path.
+    ///
+    /// Directories are created for `Directory` entries as well as for all parents of each entry,
+    /// files are created with their bodies as contents.
+    /// The target directory itself is created if it doesn't exist.
+    ///
+    /// Nothing can be written outside the target directory:
+    /// `HrxPath`s can't contain `.` nor `..` components nor root or drive prefixes,
+    /// and any symlinks in the way (be it as the entry itself or one of its parents) are reported as
+    /// [`HrxError::PathEscapesTarget`](enum.HrxError.html#variant.PathEscapesTarget).
+    ///
+    /// Returns the paths to all created directories and written files, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{ExtractOptions, OverwritePolicy, HrxArchive};
+    /// # use std::str::FromStr;
+    /// # use std::fs;
+    /// # use std::env::temp_dir;
+    /// let arch = HrxArchive::from_str(r#"<===> input.scss
+    /// ul { li { list-style: none; } }
+    ///
+    /// <===> out/input.css
+    /// ul li { list-style: none; }
+    /// "#).unwrap();
+    ///
+    /// let root = temp_dir().join("hrx.rs-doctest-extract_to");
+    /// # let _ = fs::remove_dir_all(&root);
+    /// assert_eq!(arch.extract_to(&root, &ExtractOptions::default()).unwrap(),
+    ///            vec![root.join("input.scss"), root.join("out"), root.join("out").join("input.css")]);
+    /// assert_eq!(fs::read_to_string(root.join("out").join("input.css")).unwrap(),
+    ///            "ul li { list-style: none; }\n");
+    ///
+    /// // The files are already there now
+    /// assert!(arch.extract_to(&root, &ExtractOptions::default()).is_err());
+    /// assert!(arch.extract_to(&root, &ExtractOptions {
+    ///                overwrite: OverwritePolicy::Skip,
+    ///                ..ExtractOptions::default()
+    ///            }).unwrap().is_empty());
+    /// ```
+    pub fn extract_to<P: AsRef<Path>>(&self, dir: P, options: &ExtractOptions) -> Result<Vec<PathBuf>, Result<HrxError, IoError>> {
+        extract_archive(&self, dir.as_ref(), options)
+    }
+}
+
+/// Mark the lengths of all boundaries starting a line within the specified `body` as used.
+///
+/// `used[0]` is always `true`, as there are no zero-length boundaries.
+fn mark_boundary_lengths(which: Option<&[u8]>, used: &mut Vec<bool>) {
+    if let Some(dt) = which {
+        for line in dt.split(|&c| c == b'\n') {
+            if line.first() != Some(&b'<') {
+                continue;
+            }
+
+            let len = line[1..].iter().take_while(|&&c| c == b'=').count();
+            if len != 0 && line.get(1 + len) == Some(&b'>') {
+                if used.len() <= len {
+                    used.resize(len + 1, false);
+                }
+                used[len] = true;
+            }
+        }
+    }
 }
 
-fn verify_opt(which: &Option<String>, with: &SubstringSearcher) -> Result<(), ()> {
-    if let Some(dt) = which.as_ref() {
+fn text_bytes(which: &Option<String>) -> Option<&[u8]> {
+    which.as_ref().map(|w| w.as_bytes())
+}
+
+fn verify_opt(which: Option<&[u8]>, with: &SubstringSearcher) -> Result<(), ()> {
+    if let Some(dt) = which {
         if with.find(dt).is_some() {
             return Err(());
         }
@@ -336,6 +1121,191 @@ impl FromStr for HrxArchive {
 }
 
 impl HrxPath {
+    /// Construct a path out of the specified components, validating each one.
+    ///
+    /// Fails if there are no components, or if any of them is invalid or contains a `/`,
+    /// with the error position being relative to the would-be path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{HrxError, HrxPath};
+    /// assert_eq!(HrxPath::from_components(&["dir", "sub", "file.txt"]).unwrap().as_ref(), "dir/sub/file.txt");
+    ///
+    /// match HrxPath::from_components(&["dir", "sub/file.txt"]).unwrap_err() {
+    ///     HrxError::Parse(err) => assert_eq!((err.line, err.column, err.offset), (1, 8, 7)),
+    ///     err => panic!("{}", err),
+    /// }
+    /// assert!(HrxPath::from_components(&["dir", ".."]).is_err());
+    /// assert!(HrxPath::from_components(Vec::<String>::new()).is_err());
+    /// ```
+    pub fn from_components<I, S>(components: I) -> Result<HrxPath, HrxError>
+        where I: IntoIterator<Item = S>,
+              S: AsRef<str>
+    {
+        let mut path = String::new();
+        for component in components {
+            let component = component.as_ref();
+            if !path.is_empty() {
+                path.push('/');
+            }
+
+            if let Err(mut err) = parse::path_component(component, NonZeroUsize::new(1).unwrap()) {
+                err.column += path.chars().count();
+                err.offset += path.len();
+                return Err(err.into());
+            }
+            path.push_str(component);
+        }
+
+        if path.is_empty() {
+            // Fails, reporting the missing component
+            return HrxPath::from_str(&path);
+        }
+        Ok(HrxPath(path))
+    }
+
+    /// Iterate over the `/`-separated components of this path.
+    ///
+    /// There's always at least one.
+    pub fn components(&self) -> Split<'_, char> {
+        self.0.split('/')
+    }
+
+    /// Get the amount of components in this path, i.e. 1 for top-level entries.
+    pub fn depth(&self) -> usize {
+        self.0.matches('/').count() + 1
+    }
+
+    /// Get the path to the directory containing this one, or `None` for top-level entries.
+    pub fn parent(&self) -> Option<HrxPath> {
+        self.0.rfind('/').map(|slash_i| HrxPath(self.0[..slash_i].to_string()))
+    }
+
+    /// Get the last component of this path.
+    pub fn file_name(&self) -> &str {
+        match self.0.rfind('/') {
+            Some(slash_i) => &self.0[slash_i + 1..],
+            None => &self.0,
+        }
+    }
+
+    /// Get the part of the [file name](#method.file_name) after its last `.`, if any.
+    ///
+    /// Like with `std::path::Path`, a leading `.` doesn't start an extension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxPath;
+    /// # use std::str::FromStr;
+    /// let path = HrxPath::from_str("out/input.css.map").unwrap();
+    /// assert_eq!(path.file_name(), "input.css.map");
+    /// assert_eq!(path.extension(), Some("map"));
+    /// assert_eq!(path.parent().unwrap().extension(), None);
+    ///
+    /// assert_eq!(HrxPath::from_str("in.d/.gitignore").unwrap().extension(), None);
+    /// ```
+    pub fn extension(&self) -> Option<&str> {
+        let name = self.file_name();
+        match name.rfind('.') {
+            Some(0) | None => None,
+            Some(dot_i) => Some(&name[dot_i + 1..]),
+        }
+    }
+
+    /// Append the specified relative path, consisting of one or more components, validating it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxPath;
+    /// # use std::str::FromStr;
+    /// let path = HrxPath::from_str("out").unwrap();
+    /// assert_eq!(path.join("css/input.css").unwrap().as_ref(), "out/css/input.css");
+    ///
+    /// assert!(path.join("../input.scss").is_err());
+    /// assert!(path.join("").is_err());
+    /// ```
+    pub fn join(&self, path: &str) -> Result<HrxPath, HrxError> {
+        HrxPath::from_str(path)?;
+        Ok(HrxPath(format!("{}/{}", self.0, path)))
+    }
+
+    /// Check whether this path is `base` or is contained therein, comparing whole components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxPath;
+    /// # use std::str::FromStr;
+    /// let path = HrxPath::from_str("out/css/input.css").unwrap();
+    /// assert!(path.starts_with(&HrxPath::from_str("out").unwrap()));
+    /// assert!(path.starts_with(&HrxPath::from_str("out/css/input.css").unwrap()));
+    /// assert!(!path.starts_with(&HrxPath::from_str("ou").unwrap()));
+    /// ```
+    pub fn starts_with(&self, base: &HrxPath) -> bool {
+        self.0.starts_with(&base.0) && (self.0.len() == base.0.len() || self.0.as_bytes()[base.0.len()] == b'/')
+    }
+
+    /// Get this path relative to `base`, or `None` if it's not contained within `base`.
+    ///
+    /// As paths can't be empty, that's also the case for `base` itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::HrxPath;
+    /// # use std::str::FromStr;
+    /// let path = HrxPath::from_str("out/css/input.css").unwrap();
+    /// assert_eq!(path.strip_prefix(&HrxPath::from_str("out").unwrap()).unwrap().as_ref(), "css/input.css");
+    /// assert_eq!(path.strip_prefix(&path), None);
+    /// assert_eq!(path.strip_prefix(&HrxPath::from_str("ou").unwrap()), None);
+    /// ```
+    pub fn strip_prefix(&self, base: &HrxPath) -> Option<HrxPath> {
+        if self.0.len() > base.0.len() && self.starts_with(base) {
+            Some(HrxPath(self.0[base.0.len() + 1..].to_string()))
+        } else {
+            None
+        }
+    }
+
+    /// Convert this path into a relative filesystem one, with the platform's separators.
+    ///
+    /// Components of an `HrxPath` can contain neither separators nor prefixes, and can't be `.` nor `..`,
+    /// so this can only fail on Windows, with [`HrxError::InvalidPathComponent`](enum.HrxError.html#variant.InvalidPathComponent),
+    /// for components which can't name a file there, as per [`util::invalid_windows_path_component()`](util/fn.invalid_windows_path_component.html).
+    ///
+    /// The inverse is the [`TryFrom<&Path>`](#impl-TryFrom%3C%26%27a%20Path%3E) implementation, which validates each component.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hrx::{InvalidPathReason, HrxError, HrxPath};
+    /// # use std::path::{Component, PathBuf, Path};
+    /// # use std::convert::TryFrom;
+    /// # use std::str::FromStr;
+    /// let path = HrxPath::from_str("out/css/input.css").unwrap();
+    /// assert_eq!(path.to_path_buf(), Ok(PathBuf::from("out").join("css").join("input.css")));
+    /// assert_eq!(HrxPath::try_from(path.to_path_buf().unwrap().as_path()), Ok(path));
+    ///
+    /// assert_eq!(HrxPath::try_from(Path::new("out/../input.scss")),
+    ///            Err(HrxError::InvalidPathComponent("out/../input.scss".to_string(), "..".to_string(), InvalidPathReason::ParentDir)));
+    /// assert_eq!(HrxPath::try_from(Path::new("out/a:b")),
+    ///            Err(HrxError::InvalidPathComponent("out/a:b".to_string(), "a:b".to_string(), InvalidPathReason::InvalidCharacter(':'))));
+    /// ```
+    pub fn to_path_buf(&self) -> Result<PathBuf, HrxError> {
+        if cfg!(windows) {
+            for component in self.components() {
+                if let Some(reason) = invalid_windows_path_component(component) {
+                    return Err(HrxError::InvalidPathComponent(self.0.clone(), component.to_string(), reason));
+                }
+            }
+        }
+
+        Ok(self.components().collect())
+    }
+
     /// Unwraps the contained path.
     pub fn into_inner(self) -> String {
         self.0
@@ -358,6 +1328,49 @@ impl FromStr for HrxPath {
     }
 }
 
+impl<'a> TryFrom<&'a Path> for HrxPath {
+    type Error = HrxError;
+
+    /// Convert a relative filesystem path into an archive one.
+    ///
+    /// Fails on empty and absolute paths, paths starting with `.`, as well as ones with prefixes, `..`, or non-UTF-8 components,
+    /// and on components containing characters not allowed in an `HrxPath`,
+    /// pointing out the offending component.
+    ///
+    /// Other `.` components and repeated separators are normalised away, cf. `std::path::Path::components()`.
+    fn try_from(path: &'a Path) -> Result<HrxPath, HrxError> {
+        let invalid = |component: &OsStr, reason| HrxError::InvalidPathComponent(path.display().to_string(), component.to_string_lossy().into_owned(), reason);
+
+        let mut ret = String::new();
+        for component in path.components() {
+            let component = match component {
+                Component::Prefix(prefix) => return Err(invalid(prefix.as_os_str(), InvalidPathReason::Prefix)),
+                Component::RootDir => return Err(invalid(component.as_os_str(), InvalidPathReason::RootDir)),
+                Component::CurDir => return Err(invalid(component.as_os_str(), InvalidPathReason::CurDir)),
+                Component::ParentDir => return Err(invalid(component.as_os_str(), InvalidPathReason::ParentDir)),
+                Component::Normal(component) => component,
+            };
+
+            let component_str = component.to_str().ok_or_else(|| invalid(component, InvalidPathReason::NonUtf8))?;
+            if let Err(err) = parse::path_component(component_str, NonZeroUsize::new(1).unwrap()) {
+                // Normal components are never "." nor "..", so the failure is always at an offending character
+                let c = component_str[err.offset..].chars().next().unwrap();
+                return Err(invalid(component, InvalidPathReason::InvalidCharacter(c)));
+            }
+
+            if !ret.is_empty() {
+                ret.push('/');
+            }
+            ret.push_str(component_str);
+        }
+
+        if ret.is_empty() {
+            return Err(invalid(OsStr::new(""), InvalidPathReason::Empty));
+        }
+        Ok(HrxPath(ret))
+    }
+}
+
 impl Borrow<str> for HrxPath {
     fn borrow(&self) -> &str {
         &self.0
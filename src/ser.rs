@@ -0,0 +1,491 @@
+//! HRX as a serde data format, writing side.
+
+
+use serde::ser::{self, SerializeStructVariant, SerializeTupleVariant, SerializeTupleStruct, SerializeStruct, SerializeTuple, SerializeSeq, SerializeMap, Serialize};
+use self::super::{HrxSerdeError, HrxEntryData, HrxArchive, HrxEntry, HrxPath, parse};
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::fmt::Display;
+
+
+/// Serialise the specified value into an HRX archive string.
+///
+/// See [`to_archive()`](fn.to_archive.html) for how values map onto entries.
+///
+/// An empty string isn't an archive, so a value without any entries is written as an archive with just an empty line as its comment,
+/// which [`from_str()`](fn.from_str.html) reads back as empty.
+///
+/// Only available with the `serde` feature.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate serde;
+/// # extern crate hrx;
+/// # use std::collections::BTreeMap;
+/// #[derive(Serialize)]
+/// struct Fixture {
+///     input: String,
+///     output: Option<String>,
+///     options: BTreeMap<String, u32>,
+/// }
+///
+/// # fn main() {
+/// let fixture = Fixture {
+///     input: "ul { li { list-style: none; } }\n".to_string(),
+///     output: None,
+///     options: vec![("precision".to_string(), 5)].into_iter().collect(),
+/// };
+/// assert_eq!(hrx::to_string(&fixture).unwrap(), "<===> input
+/// ul { li { list-style: none; } }
+///
+/// <===> options/precision
+/// 5");
+/// # }
+/// ```
+pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String, HrxSerdeError> {
+    let mut archive = to_archive(value)?;
+    if archive.entries.is_empty() {
+        archive.comment = Some("\n".to_string());
+    }
+
+    let mut out = vec![];
+    archive.serialise(&mut out).map_err(|e| e.expect("writing to a Vec can't fail"))?;
+    Ok(String::from_utf8(out).expect("archives are UTF-8"))
+}
+
+/// Serialise the specified value into archive entries.
+///
+/// The value must be a struct, a map, or a sequence, each field or element of which becomes an entry named after its key or index:
+///
+///   * strings, characters, numbers, and booleans become files containing their textual representation,
+///   * `None` fields are left out, and `Some`s have their contents serialised in their place;
+///     as that would leave a gap in the indices, `None` can't be an element of a sequence or a tuple,
+///   * structs and maps become directories, with their fields, recursively, as entries therein,
+///   * sequences and tuples become directories with each element named by its index,
+///   * unit values become empty files,
+///   * enum variants are externally tagged: unit ones become files containing their names,
+///     and others become directories containing a single entry named after the variant.
+///
+/// Map keys must be strings, characters, numbers, booleans, or unit variants,
+/// forming valid path components, i.e. non-empty, without `/`, `\\`, `:`, nor control characters, and not `.` nor `..`.
+///
+/// Empty structures are written as explicit directories.
+///
+/// The boundary length is the shortest valid one, but no shorter than 3.
+///
+/// Only available with the `serde` feature.
+pub fn to_archive<T: ?Sized + Serialize>(value: &T) -> Result<HrxArchive, HrxSerdeError> {
+    let mut entries = vec![];
+    value.serialize(EntrySerializer {
+            path: None,
+            entries: &mut entries,
+            element: false,
+        })?;
+
+    let mut archive = HrxArchive::new(NonZeroUsize::new(3).unwrap());
+    let mut paths = BTreeMap::<String, _>::new();
+    for (path, data) in entries {
+        parse::validate_its_directory_tree(&path, data == HrxEntryData::Directory, &mut paths)?;
+        archive.entries.insert(HrxPath(path),
+                               HrxEntry {
+                                   comment: None,
+                                   data: data,
+                               });
+    }
+
+    archive.boundary_length = archive.minimal_boundary_length_from(archive.boundary_length);
+    Ok(archive)
+}
+
+
+/// Serialises a value at `path`, or as the whole archive if `None`
+struct EntrySerializer<'e> {
+    path: Option<String>,
+    entries: &'e mut Vec<(String, HrxEntryData)>,
+    /// Whether this is a sequence or tuple element, named after its index
+    element: bool,
+}
+
+/// Serialises the fields or elements of a directory, ending with an explicit directory entry if none were added
+struct DirectorySerializer<'e> {
+    path: Option<String>,
+    entries: &'e mut Vec<(String, HrxEntryData)>,
+    entries_before: usize,
+    next_index: usize,
+    next_key: Option<String>,
+}
+
+/// Serialises a map key into a path component
+struct KeySerializer;
+
+
+impl<'e> EntrySerializer<'e> {
+    fn file(self, body: &str) -> Result<(), HrxSerdeError> {
+        let path = self.path.ok_or_else(|| HrxSerdeError::Unsupported("a scalar at the top level".to_string()))?;
+        self.entries.push((path, HrxEntryData::File { body: if body.is_empty() { None } else { Some(body.to_string()) } }));
+        Ok(())
+    }
+
+    fn display<T: Display>(self, value: T) -> Result<(), HrxSerdeError> {
+        self.file(&value.to_string())
+    }
+
+    fn directory(self) -> DirectorySerializer<'e> {
+        DirectorySerializer {
+            entries_before: self.entries.len(),
+            path: self.path,
+            entries: self.entries,
+            next_index: 0,
+            next_key: None,
+        }
+    }
+
+    /// A directory containing only the entry named after `variant`
+    fn variant(self, variant: &str) -> EntrySerializer<'e> {
+        EntrySerializer {
+            path: Some(child_path(&self.path, variant)),
+            entries: self.entries,
+            element: false,
+        }
+    }
+}
+
+impl<'e> DirectorySerializer<'e> {
+    fn child(&mut self, name: &str, element: bool) -> EntrySerializer<'_> {
+        EntrySerializer {
+            path: Some(child_path(&self.path, name)),
+            entries: self.entries,
+            element: element,
+        }
+    }
+
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HrxSerdeError> {
+        let name = self.next_index.to_string();
+        self.next_index += 1;
+        value.serialize(self.child(&name, true))
+    }
+
+    fn field<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), HrxSerdeError> {
+        validate_component(key)?;
+        value.serialize(self.child(key, false))
+    }
+
+    fn finish(self) -> Result<(), HrxSerdeError> {
+        if let Some(path) = self.path {
+            if self.entries.len() == self.entries_before {
+                self.entries.push((path, HrxEntryData::Directory));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn child_path(parent: &Option<String>, name: &str) -> String {
+    match parent.as_ref() {
+        Some(parent) => format!("{}/{}", parent, name),
+        None => name.to_string(),
+    }
+}
+
+fn validate_component(name: &str) -> Result<(), HrxSerdeError> {
+    match parse::path_component(name, NonZeroUsize::new(1).unwrap()) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(HrxSerdeError::Unsupported(format!("{:?} as a path component", name))),
+    }
+}
+
+
+macro_rules! serialize_display {
+    ($($fn:ident($tp:ty),)*) => {
+        $(
+            fn $fn(self, v: $tp) -> Result<(), HrxSerdeError> {
+                self.display(v)
+            }
+        )*
+    }
+}
+
+impl<'e> ser::Serializer for EntrySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+    type SerializeSeq = DirectorySerializer<'e>;
+    type SerializeTuple = DirectorySerializer<'e>;
+    type SerializeTupleStruct = DirectorySerializer<'e>;
+    type SerializeTupleVariant = DirectorySerializer<'e>;
+    type SerializeMap = DirectorySerializer<'e>;
+    type SerializeStruct = DirectorySerializer<'e>;
+    type SerializeStructVariant = DirectorySerializer<'e>;
+
+    serialize_display! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), HrxSerdeError> {
+        self.file(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), HrxSerdeError> {
+        match ::std::str::from_utf8(v) {
+            Ok(v) => self.file(v),
+            Err(_) => Err(HrxSerdeError::Unsupported("non-UTF-8 bytes".to_string())),
+        }
+    }
+
+    fn serialize_none(self) -> Result<(), HrxSerdeError> {
+        if self.path.is_none() {
+            return Err(HrxSerdeError::Unsupported("None at the top level".to_string()));
+        }
+        if self.element {
+            // Reading elements back in archive order would shift every later one into its place
+            return Err(HrxSerdeError::Unsupported("None as a sequence or tuple element".to_string()));
+        }
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), HrxSerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), HrxSerdeError> {
+        self.file("")
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), HrxSerdeError> {
+        self.file("")
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<(), HrxSerdeError> {
+        self.file(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<(), HrxSerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, variant: &'static str, value: &T) -> Result<(), HrxSerdeError> {
+        validate_component(variant)?;
+        value.serialize(self.variant(variant))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<DirectorySerializer<'e>, HrxSerdeError> {
+        Ok(self.directory())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<DirectorySerializer<'e>, HrxSerdeError> {
+        Ok(self.directory())
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<DirectorySerializer<'e>, HrxSerdeError> {
+        Ok(self.directory())
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, variant: &'static str, _: usize) -> Result<DirectorySerializer<'e>, HrxSerdeError> {
+        validate_component(variant)?;
+        Ok(self.variant(variant).directory())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<DirectorySerializer<'e>, HrxSerdeError> {
+        Ok(self.directory())
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<DirectorySerializer<'e>, HrxSerdeError> {
+        Ok(self.directory())
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, variant: &'static str, _: usize) -> Result<DirectorySerializer<'e>, HrxSerdeError> {
+        validate_component(variant)?;
+        Ok(self.variant(variant).directory())
+    }
+}
+
+impl<'e> SerializeSeq for DirectorySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HrxSerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), HrxSerdeError> {
+        self.finish()
+    }
+}
+
+impl<'e> SerializeTuple for DirectorySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HrxSerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), HrxSerdeError> {
+        self.finish()
+    }
+}
+
+impl<'e> SerializeTupleStruct for DirectorySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HrxSerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), HrxSerdeError> {
+        self.finish()
+    }
+}
+
+impl<'e> SerializeTupleVariant for DirectorySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HrxSerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), HrxSerdeError> {
+        self.finish()
+    }
+}
+
+impl<'e> SerializeMap for DirectorySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), HrxSerdeError> {
+        self.next_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HrxSerdeError> {
+        let key = self.next_key.take().expect("serialize_value() called before serialize_key()");
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<(), HrxSerdeError> {
+        self.finish()
+    }
+}
+
+impl<'e> SerializeStruct for DirectorySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), HrxSerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), HrxSerdeError> {
+        self.finish()
+    }
+}
+
+impl<'e> SerializeStructVariant for DirectorySerializer<'e> {
+    type Ok = ();
+    type Error = HrxSerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), HrxSerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), HrxSerdeError> {
+        self.finish()
+    }
+}
+
+
+macro_rules! serialize_key_display {
+    ($($fn:ident($tp:ty),)*) => {
+        $(
+            fn $fn(self, v: $tp) -> Result<String, HrxSerdeError> {
+                Ok(v.to_string())
+            }
+        )*
+    }
+}
+
+macro_rules! serialize_key_unsupported {
+    ($($fn:ident($($arg:ty),*) -> $ret:ty: $what:expr,)*) => {
+        $(
+            fn $fn(self, $(_: $arg),*) -> Result<$ret, HrxSerdeError> {
+                Err(HrxSerdeError::Unsupported(format!("{} as a map key", $what)))
+            }
+        )*
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = HrxSerdeError;
+    type SerializeSeq = ser::Impossible<String, HrxSerdeError>;
+    type SerializeTuple = ser::Impossible<String, HrxSerdeError>;
+    type SerializeTupleStruct = ser::Impossible<String, HrxSerdeError>;
+    type SerializeTupleVariant = ser::Impossible<String, HrxSerdeError>;
+    type SerializeMap = ser::Impossible<String, HrxSerdeError>;
+    type SerializeStruct = ser::Impossible<String, HrxSerdeError>;
+    type SerializeStructVariant = ser::Impossible<String, HrxSerdeError>;
+
+    serialize_key_display! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    serialize_key_unsupported! {
+        serialize_bytes(&[u8]) -> String: "bytes",
+        serialize_none() -> String: "None",
+        serialize_unit() -> String: "a unit",
+        serialize_unit_struct(&'static str) -> String: "a unit struct",
+        serialize_seq(Option<usize>) -> Self::SerializeSeq: "a sequence",
+        serialize_tuple(usize) -> Self::SerializeTuple: "a tuple",
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct: "a tuple struct",
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant: "a tuple variant",
+        serialize_map(Option<usize>) -> Self::SerializeMap: "a map",
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct: "a struct",
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant: "a struct variant",
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<String, HrxSerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<String, HrxSerdeError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<String, HrxSerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<String, HrxSerdeError> {
+        Err(HrxSerdeError::Unsupported("a newtype variant as a map key".to_string()))
+    }
+}
@@ -0,0 +1,101 @@
+//! `serde` implementations which can't be derived.
+
+
+use serde::{Deserializer, Deserialize, Serializer, Serialize};
+use self::super::{HrxEntryData, HrxArchive, HrxEntry, HrxPath, parse};
+use linked_hash_map::LinkedHashMap;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use serde::de::Error;
+use std::str::FromStr;
+
+
+impl Serialize for HrxPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Validated like [`HrxPath::from_str()`](struct.HrxPath.html#method.from_str)
+impl<'de> Deserialize<'de> for HrxPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<HrxPath, D::Error> {
+        HrxPath::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+
+#[derive(Deserialize)]
+#[serde(rename = "HrxArchive")]
+struct HrxArchiveFields {
+    comment: Option<String>,
+    entries: LinkedHashMap<HrxPath, HrxEntry>,
+    boundary_length: NonZeroUsize,
+}
+
+/// Validated like [`HrxArchive::from_str()`](struct.HrxArchive.html#method.from_str)
+impl<'de> Deserialize<'de> for HrxArchive {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<HrxArchive, D::Error> {
+        let fields = HrxArchiveFields::deserialize(deserializer)?;
+
+        let mut paths = BTreeMap::<&str, _>::new();
+        for (path, entry) in &fields.entries {
+            parse::validate_its_directory_tree(&path.0, entry.data == HrxEntryData::Directory, &mut paths).map_err(D::Error::custom)?;
+        }
+
+        let archive = HrxArchive {
+            comment: fields.comment,
+            entries: fields.entries,
+            boundary_length: fields.boundary_length,
+        };
+        archive.validate_content().map_err(D::Error::custom)?;
+        Ok(archive)
+    }
+}
+
+
+/// For use with `#[serde(with)]`, since `ParseError::expected` holds `&'static str`s
+pub(crate) mod parse_error {
+    use serde::{Deserializer, Deserialize, Serializer, Serialize};
+    use self::super::super::parse::{EXPECTED_TOKENS, ParseError};
+    use std::collections::{BTreeSet, HashSet};
+    use serde::de::{Unexpected, Error};
+
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "ParseError")]
+    struct ParseErrorFields<S: Ord> {
+        line: usize,
+        column: usize,
+        offset: usize,
+        expected: BTreeSet<S>,
+    }
+
+
+    pub fn serialize<S: Serializer>(err: &ParseError, serializer: S) -> Result<S::Ok, S::Error> {
+        ParseErrorFields {
+                line: err.line,
+                column: err.column,
+                offset: err.offset,
+                expected: err.expected.iter().cloned().collect(),
+            }
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ParseError, D::Error> {
+        let fields = ParseErrorFields::<String>::deserialize(deserializer)?;
+        Ok(ParseError {
+            line: fields.line,
+            column: fields.column,
+            offset: fields.offset,
+            expected: fields.expected.iter().map(|token| expected_token(token)).collect::<Result<HashSet<_>, _>>()?,
+        })
+    }
+
+    /// Only what the parser itself can expect is accepted, so that nothing needs to be leaked to get a `'static` description
+    fn expected_token<E: Error>(token: &str) -> Result<&'static str, E> {
+        EXPECTED_TOKENS.iter()
+            .find(|&&t| t == token)
+            .cloned()
+            .ok_or_else(|| E::invalid_value(Unexpected::Str(token), &"a token expected by the HRX parser"))
+    }
+}
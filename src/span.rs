@@ -0,0 +1,150 @@
+use linked_hash_map::LinkedHashMap;
+use self::super::HrxPath;
+use std::{fmt, str};
+
+
+/// A location in the archive source.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::HrxPosition;
+/// let pos = HrxPosition::locate("<===> file\nсодержание\n", 15);
+/// assert_eq!(pos,
+///            HrxPosition {
+///                line: 2,
+///                column: 3,
+///                offset: 15,
+///            });
+/// assert_eq!(pos.to_string(), "2:3");
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxPosition {
+    /// 1-based line #
+    pub line: usize,
+    /// 1-based column #, in characters
+    pub column: usize,
+    /// Byte offset
+    pub offset: usize,
+}
+
+/// A range in the archive source, from `start` inclusive to `end` exclusive.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxSpan {
+    /// Where the range begins
+    pub start: HrxPosition,
+    /// Where the range ends, i.e. the position right after its last character
+    pub end: HrxPosition,
+}
+
+/// Locations of the parts of a single entry in the archive source.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxEntrySpans {
+    /// The boundary line introducing the entry, e.g. `<===> dir/file`, sans the newline
+    pub boundary_line: HrxSpan,
+    /// The entry's path, sans the trailing `/` for directories
+    pub path: HrxSpan,
+    /// The entry's comment, sans its boundary line
+    pub comment: Option<HrxSpan>,
+    /// The file's contents
+    pub body: Option<HrxSpan>,
+}
+
+/// Locations of the comment and all entries of an archive in its source.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HrxArchiveSpans {
+    /// The archive comment, sans its boundary line
+    pub comment: Option<HrxSpan>,
+    /// Locations of the entries with their paths, in archive order
+    pub entries: LinkedHashMap<HrxPath, HrxEntrySpans>,
+}
+
+
+impl HrxPosition {
+    /// Get the position of the specified byte offset into the specified source.
+    ///
+    /// # Panics
+    ///
+    /// If `offset` is past the end of or not on a character boundary in `source`.
+    pub fn locate(source: &str, offset: usize) -> HrxPosition {
+        assert!(source.is_char_boundary(offset), "{} not on a character boundary", offset);
+        Locator::new(source.as_bytes()).locate(offset)
+    }
+}
+
+impl fmt::Display for HrxPosition {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}:{}", self.line, self.column)
+    }
+}
+
+
+/// Computes positions for offsets into a source, reusing the previous result if moving forward.
+///
+/// Columns are counted in characters, and bytes which aren't part of one count as one each.
+pub(crate) struct Locator<'s> {
+    source: &'s [u8],
+    offset: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'s> Locator<'s> {
+    pub fn new(source: &'s [u8]) -> Locator<'s> {
+        Locator {
+            source: source,
+            offset: 0,
+            line: 1,
+            line_start: 0,
+        }
+    }
+
+    pub fn locate(&mut self, offset: usize) -> HrxPosition {
+        if offset < self.offset {
+            *self = Locator::new(self.source);
+        }
+
+        for (i, _) in self.source[self.offset..offset].iter().enumerate().filter(|&(_, &b)| b == b'\n') {
+            self.line += 1;
+            self.line_start = self.offset + i + 1;
+        }
+        self.offset = offset;
+
+        HrxPosition {
+            line: self.line,
+            column: character_count(&self.source[self.line_start..offset]) + 1,
+            offset: offset,
+        }
+    }
+
+    /// Get the span of the specified subslice of the source.
+    pub fn span_of<S: AsRef<[u8]> + ?Sized>(&mut self, what: &S) -> HrxSpan {
+        let what = what.as_ref();
+        let start = what.as_ptr() as usize - self.source.as_ptr() as usize;
+        self.span(start, start + what.len())
+    }
+
+    pub fn span(&mut self, start: usize, end: usize) -> HrxSpan {
+        HrxSpan {
+            start: self.locate(start),
+            end: self.locate(end),
+        }
+    }
+}
+
+
+/// Count the characters in the specified data, as if every invalid byte were one
+fn character_count(data: &[u8]) -> usize {
+    let mut count = 0;
+    let mut rest = data;
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) => return count + valid.chars().count(),
+            Err(err) => {
+                let invalid = err.error_len().unwrap_or(rest.len() - err.valid_up_to());
+                count += str::from_utf8(&rest[..err.valid_up_to()]).expect("valid prefix").chars().count() + invalid;
+                rest = &rest[err.valid_up_to() + invalid..];
+            }
+        }
+    }
+}
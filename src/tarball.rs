@@ -0,0 +1,178 @@
+use self::super::{UnrepresentableReason, UnrepresentablePolicy, InvalidPathReason, SkippedFiles, HrxEntryData, HrxArchive, HrxEntry, HrxError, HrxPath, parse};
+use std::io::{Error as IoError, Write, Read};
+use self::super::error::CompoundError;
+use tar::{EntryType, Archive, Builder, Header};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path};
+use std::num::NonZeroUsize;
+use std::convert::TryFrom;
+
+
+/// Configuration for [`HrxArchive::from_tar()`](struct.HrxArchive.html#method.from_tar).
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{UnrepresentablePolicy, TarOptions};
+/// # use std::num::NonZeroUsize;
+/// assert_eq!(TarOptions::default(),
+///            TarOptions {
+///                unrepresentable: UnrepresentablePolicy::Error,
+///                min_boundary_length: NonZeroUsize::new(3).unwrap(),
+///            });
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TarOptions {
+    /// What to do with symlinks, devices, FIFOs, and files whose paths or contents can't be represented in an archive.
+    pub unrepresentable: UnrepresentablePolicy,
+    /// The shortest boundary length to consider, the actual one will be the shortest one valid for the archive not below this.
+    pub min_boundary_length: NonZeroUsize,
+}
+
+
+impl Default for TarOptions {
+    fn default() -> TarOptions {
+        TarOptions {
+            unrepresentable: UnrepresentablePolicy::Error,
+            min_boundary_length: NonZeroUsize::new(3).unwrap(),
+        }
+    }
+}
+
+
+pub fn read_tar<R: Read>(tar: R, options: &TarOptions) -> Result<(HrxArchive, SkippedFiles), Result<HrxError, IoError>> {
+    Ok(read_tar_impl(tar, options)?)
+}
+
+fn read_tar_impl<R: Read>(tar: R, options: &TarOptions) -> Result<(HrxArchive, SkippedFiles), CompoundError> {
+    let mut skipped = vec![];
+    let mut unrepresentable = |tar_path: &Path, reason| options.unrepresentable.handle(tar_path.display().to_string(), reason, &mut skipped);
+
+    let mut entries = vec![];
+    let mut tar = Archive::new(tar);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let tar_path = entry.path()?.into_owned();
+
+        let path = match tar_path_to_hrx(&tar_path) {
+            Ok(Some(path)) => path,
+            // The root directory itself, as in "./"
+            Ok(None) => continue,
+            Err(HrxError::InvalidPathComponent(_, _, InvalidPathReason::NonUtf8)) => {
+                unrepresentable(&tar_path, UnrepresentableReason::NonUtf8Path)?;
+                continue;
+            }
+            Err(_) => {
+                unrepresentable(&tar_path, UnrepresentableReason::InvalidPath)?;
+                continue;
+            }
+        };
+
+        let data = match entry.header().entry_type() {
+            EntryType::Regular | EntryType::Continuous => {
+                let mut body = vec![];
+                entry.read_to_end(&mut body)?;
+                match String::from_utf8(body) {
+                    Ok(body) => HrxEntryData::File { body: if body.is_empty() { None } else { Some(body) } },
+                    Err(_) => {
+                        unrepresentable(&tar_path, UnrepresentableReason::NonUtf8Contents)?;
+                        continue;
+                    }
+                }
+            }
+            EntryType::Directory => HrxEntryData::Directory,
+            // A hard link to a file earlier in the archive is just another copy of it
+            EntryType::Link => {
+                let target = entry.link_name()?.and_then(|l| tar_path_to_hrx(&l).ok()).and_then(|l| l);
+                match target.and_then(|t| entries.iter().rev().find(|&&(ref p, _)| *p == t)) {
+                    Some(&(_, ref data @ HrxEntryData::File { .. })) => data.clone(),
+                    _ => {
+                        unrepresentable(&tar_path, UnrepresentableReason::Symlink)?;
+                        continue;
+                    }
+                }
+            }
+            EntryType::Symlink => {
+                unrepresentable(&tar_path, UnrepresentableReason::Symlink)?;
+                continue;
+            }
+            EntryType::XGlobalHeader => continue,
+            _ => {
+                unrepresentable(&tar_path, UnrepresentableReason::SpecialFile)?;
+                continue;
+            }
+        };
+
+        entries.push((path, data));
+    }
+
+    // Explicit directories are only kept if empty, as others exist implicitly
+    let parents: BTreeSet<_> = entries.iter().flat_map(|&(ref p, _)| p.0.match_indices('/').map(move |(slash_i, _)| &p.0[..slash_i])).collect();
+    let mut archive = HrxArchive::new(options.min_boundary_length);
+    let mut paths = BTreeMap::<String, _>::new();
+    for (path, data) in entries.iter().filter(|&&(ref p, ref d)| *d != HrxEntryData::Directory || !parents.contains(&p.0[..])) {
+        parse::validate_its_directory_tree(&path.0, *data == HrxEntryData::Directory, &mut paths)?;
+        archive.entries.insert(path.clone(),
+                               HrxEntry {
+                                   comment: None,
+                                   data: data.clone(),
+                               });
+    }
+
+    archive.boundary_length = archive.minimal_boundary_length_from(options.min_boundary_length);
+    Ok((archive, skipped))
+}
+
+/// Tar paths are usually relative to `.`, which isn't part of archive paths, and `None` for `.` itself
+fn tar_path_to_hrx(tar_path: &Path) -> Result<Option<HrxPath>, HrxError> {
+    let relative: &Path = tar_path.strip_prefix(Component::CurDir).unwrap_or(tar_path);
+    if relative.as_os_str().is_empty() {
+        Ok(None)
+    } else {
+        HrxPath::try_from(relative).map(Some)
+    }
+}
+
+
+pub fn write_tar<W: Write>(archive: &HrxArchive, into: &mut W) -> Result<(), IoError> {
+    let mut tar = Builder::new(into);
+
+    // Directories, explicit or not, precede their contents, so they're created with the right permissions
+    let mut written_dirs = BTreeSet::new();
+    for (path, entry) in &archive.entries {
+        for (slash_i, _) in path.0.match_indices('/') {
+            let parent = &path.0[..slash_i];
+            if written_dirs.insert(parent) {
+                tar.append_data(&mut header(EntryType::Directory, 0), parent, &[][..])?;
+            }
+        }
+
+        match entry.data {
+            HrxEntryData::File { ref body } => {
+                let body = body.as_ref().map(|b| b.as_bytes()).unwrap_or(&[]);
+                tar.append_data(&mut header(EntryType::Regular, body.len() as u64), &path.0, body)?;
+            }
+            HrxEntryData::Binary { ref body } => tar.append_data(&mut header(EntryType::Regular, body.len() as u64), &path.0, &body[..])?,
+            HrxEntryData::Directory => {
+                if written_dirs.insert(&path.0) {
+                    tar.append_data(&mut header(EntryType::Directory, 0), &path.0, &[][..])?;
+                }
+            }
+        }
+    }
+
+    tar.into_inner()?;
+    Ok(())
+}
+
+/// Deterministic metadata: owned by root, modified at the epoch, `0644` for files and `0755` for directories
+fn header(tp: EntryType, size: u64) -> Header {
+    let mut header = Header::new_gnu();
+    header.set_entry_type(tp);
+    header.set_size(size);
+    header.set_mode(if tp == EntryType::Directory { 0o755 } else { 0o644 });
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_mtime(0);
+    header
+}
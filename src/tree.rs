@@ -0,0 +1,192 @@
+use self::super::{HrxEntryData, HrxEntry, HrxPath};
+use linked_hash_map::{self, LinkedHashMap};
+use std::collections::VecDeque;
+use std::iter::FusedIterator;
+use std::borrow::Cow;
+
+
+/// A directory tree view over the entries of an archive.
+///
+/// Each node corresponds either to an entry, or to a directory which exists only implicitly,
+/// as a parent of some entry, the same way [`reduce_raw_entries_and_validate_directory_tree()`](parse/fn.reduce_raw_entries_and_validate_directory_tree.html)
+/// understands them; the root node has an empty path and no entry.
+///
+/// Children are kept in the order they (or, for implicit directories, their first descendants) appear in the archive.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{HrxWalkOrder, HrxArchive};
+/// # use std::str::FromStr;
+/// let arch = HrxArchive::from_str("<===> input.scss
+/// <===> out/
+/// <===> out/css/input.css
+/// <===> out/input.map
+/// ").unwrap();
+/// let tree = arch.tree();
+///
+/// assert_eq!(tree.children().map(|c| c.path()).collect::<Vec<_>>(), vec!["input.scss", "out"]);
+///
+/// let css = tree.get("out/css").unwrap();
+/// assert_eq!(css.name(), "css");
+/// assert!(css.is_dir());
+/// assert!(css.entry().is_none());
+///
+/// assert_eq!(tree.walk(HrxWalkOrder::BreadthFirst).map(|(p, _)| p).collect::<Vec<_>>(),
+///            vec!["input.scss", "out", "out/css", "out/input.map", "out/css/input.css"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HrxTree<'a> {
+    path: &'a str,
+    entry: Option<&'a HrxEntry>,
+    /// By name
+    children: LinkedHashMap<&'a str, HrxTree<'a>>,
+}
+
+/// The order in which to [`walk()`](struct.HrxTree.html#method.walk) a tree.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HrxWalkOrder {
+    /// Each directory is followed by all of its descendants, recursively, before its next sibling
+    DepthFirst,
+    /// All entries of the same depth come before any deeper ones
+    BreadthFirst,
+}
+
+/// An iterator over the nodes of a tree, yielding their paths and entries (`None` for implicit directories).
+///
+/// Returned by [`HrxTree::walk()`](struct.HrxTree.html#method.walk) and [`HrxArchive::walk()`](struct.HrxArchive.html#method.walk).
+#[derive(Debug, Clone)]
+pub struct HrxWalk<'t, 'a: 't> {
+    order: HrxWalkOrder,
+    /// Next node at the front, borrowed from the tree being walked, or owned if it was given up for the walk
+    queue: VecDeque<Cow<'t, HrxTree<'a>>>,
+}
+
+
+impl<'a> HrxTree<'a> {
+    /// Build a tree out of the specified entries.
+    pub fn new(entries: &'a LinkedHashMap<HrxPath, HrxEntry>) -> HrxTree<'a> {
+        let mut root = HrxTree::node("");
+
+        for (path, entry) in entries {
+            let path = &path.0[..];
+
+            let mut node = &mut root;
+            let mut name_start = 0;
+            for (slash_i, _) in path.match_indices('/') {
+                node = node.children.entry(&path[name_start..slash_i]).or_insert_with(|| HrxTree::node(&path[..slash_i]));
+                name_start = slash_i + 1;
+            }
+
+            node.children.entry(&path[name_start..]).or_insert_with(|| HrxTree::node(path)).entry = Some(entry);
+        }
+
+        root
+    }
+
+    fn node(path: &'a str) -> HrxTree<'a> {
+        HrxTree {
+            path: path,
+            entry: None,
+            children: LinkedHashMap::new(),
+        }
+    }
+
+    /// Get the path to this node, empty for the root.
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Get the last component of this node's path, empty for the root.
+    pub fn name(&self) -> &'a str {
+        match self.path.rfind('/') {
+            Some(slash_i) => &self.path[slash_i + 1..],
+            None => self.path,
+        }
+    }
+
+    /// Get the entry at this node, or `None` if it's the root or an implicit directory.
+    pub fn entry(&self) -> Option<&'a HrxEntry> {
+        self.entry
+    }
+
+    /// Check whether this node is a directory, be it explicit, implicit or the root.
+    pub fn is_dir(&self) -> bool {
+        match self.entry {
+            Some(entry) => entry.data == HrxEntryData::Directory,
+            None => true,
+        }
+    }
+
+    /// Get the immediate children of this node, in archive order.
+    pub fn children(&self) -> linked_hash_map::Values<'_, &'a str, HrxTree<'a>> {
+        self.children.values()
+    }
+
+    /// Get the node at the specified path relative to this one, if it exists.
+    ///
+    /// The empty path refers to this node.
+    pub fn get(&self, path: &str) -> Option<&HrxTree<'a>> {
+        if path.is_empty() {
+            return Some(self);
+        }
+
+        let mut node = self;
+        for name in path.split('/') {
+            node = node.children.get(name)?;
+        }
+        Some(node)
+    }
+
+    /// Iterate over all descendants of this node in the specified order, this node excluded.
+    ///
+    /// Siblings are always visited in archive order.
+    pub fn walk(&self, order: HrxWalkOrder) -> HrxWalk<'_, 'a> {
+        HrxWalk {
+            order: order,
+            queue: self.children.values().map(Cow::Borrowed).collect(),
+        }
+    }
+
+    /// Iterate over all descendants of this node in the specified order, this node excluded, consuming it.
+    ///
+    /// Same as [`walk()`](#method.walk), but for when the tree isn't needed afterwards,
+    /// as in [`HrxArchive::walk()`](struct.HrxArchive.html#method.walk).
+    pub fn into_walk(self, order: HrxWalkOrder) -> HrxWalk<'a, 'a> {
+        HrxWalk {
+            order: order,
+            queue: self.children.into_iter().map(|(_, child)| Cow::Owned(child)).collect(),
+        }
+    }
+}
+
+impl<'t, 'a> HrxWalk<'t, 'a> {
+    fn enqueue<I: DoubleEndedIterator<Item = Cow<'t, HrxTree<'a>>>>(&mut self, children: I) {
+        match self.order {
+            HrxWalkOrder::DepthFirst => {
+                for child in children.rev() {
+                    self.queue.push_front(child);
+                }
+            }
+            HrxWalkOrder::BreadthFirst => self.queue.extend(children),
+        }
+    }
+}
+
+impl<'t, 'a> Iterator for HrxWalk<'t, 'a> {
+    type Item = (&'a str, Option<&'a HrxEntry>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        let item = (node.path, node.entry);
+
+        match node {
+            Cow::Borrowed(node) => self.enqueue(node.children.values().map(Cow::Borrowed)),
+            Cow::Owned(node) => self.enqueue(node.children.into_iter().map(|(_, child)| Cow::Owned(child))),
+        }
+
+        Some(item)
+    }
+}
+
+impl<'t, 'a> FusedIterator for HrxWalk<'t, 'a> {}
@@ -1,6 +1,7 @@
 //! Module containing various utility functions.
 
 
+use self::super::InvalidPathReason;
 use std::num::NonZeroUsize;
 use std::iter;
 
@@ -26,3 +27,40 @@ pub fn boundary_str(of_length: NonZeroUsize) -> String {
 
     res
 }
+
+/// Check whether the specified archive path component can't be a Windows filesystem path component, and why.
+///
+/// That's the case if it contains any of `<>"|?*`, ends in `.` or a space, or is a reserved device name,
+/// like `CON` or `LPT1`, optionally followed by an extension.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::util::invalid_windows_path_component;
+/// # use hrx::InvalidPathReason;
+/// assert_eq!(invalid_windows_path_component("input.scss"), None);
+/// assert_eq!(invalid_windows_path_component("what?"), Some(InvalidPathReason::InvalidCharacter('?')));
+/// assert_eq!(invalid_windows_path_component("file."), Some(InvalidPathReason::TrailingCharacter('.')));
+/// assert_eq!(invalid_windows_path_component("nul.txt"), Some(InvalidPathReason::ReservedName));
+/// assert_eq!(invalid_windows_path_component("console"), None);
+/// ```
+pub fn invalid_windows_path_component(component: &str) -> Option<InvalidPathReason> {
+    static RESERVED_NAMES: &[&str] = &["CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+                                       "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9"];
+
+    if let Some(c) = component.chars().find(|c| "<>\"|?*".contains(*c)) {
+        return Some(InvalidPathReason::InvalidCharacter(c));
+    }
+
+    match component.chars().last() {
+        Some(c @ '.') | Some(c @ ' ') => return Some(InvalidPathReason::TrailingCharacter(c)),
+        _ => {}
+    }
+
+    let stem = component.split('.').next().unwrap_or(component).trim_end_matches(' ');
+    if RESERVED_NAMES.iter().any(|name| name.eq_ignore_ascii_case(stem)) {
+        return Some(InvalidPathReason::ReservedName);
+    }
+
+    None
+}
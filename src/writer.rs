@@ -0,0 +1,168 @@
+use self::super::{ErroneousBodyPath, HrxEntryData, HrxEntry, HrxError, HrxPath, parse};
+use jetscii::ByteSubstring as SubstringSearcher;
+use std::io::{Error as IoError, Write};
+use self::super::error::CompoundError;
+use self::super::output::Emitter;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+
+
+/// A streaming writer, serialising entries one-by-one as they're added.
+///
+/// Each entry is validated before being written, with the same rules the parser uses, i.e.:
+///
+///   * comments and bodies can neither contain a newline followed by the boundary, nor start with the boundary,
+///   * no two entries can have the same path, and
+///   * no entry can use a file as a directory,
+///
+/// so the output will always parse back into the same archive.
+/// An entry which fails validation isn't written, and the writer can continue to be used.
+///
+/// Empty comments and bodies can't be represented, and are written as absent ones.
+///
+/// Call [`finish()`](#method.finish) when done, to add the archive comment and the final newline.
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{ErroneousBodyPath, HrxWriter, HrxArchive, HrxError};
+/// # use std::num::NonZeroUsize;
+/// # use std::str::FromStr;
+/// let mut writer = HrxWriter::new(vec![], NonZeroUsize::new(3).unwrap());
+///
+/// writer.add_file(&"input.scss".parse()?, None, Some("ul { li { list-style: none; } }\n")).unwrap();
+/// writer.add_dir(&"out".parse()?, Some("Generated files")).unwrap();
+///
+/// assert_eq!(writer.add_file(&"out".parse()?, None, None).unwrap_err().unwrap(),
+///            HrxError::DuplicateEntry("out".to_string()));
+/// assert_eq!(writer.add_file(&"bad.txt".parse()?, None, Some("A line\n<===> looking like a boundary")).unwrap_err().unwrap(),
+///            ErroneousBodyPath::EntryData("bad.txt".to_string()).into());
+///
+/// let out = String::from_utf8(writer.finish(Some("Archive comment")).unwrap()).unwrap();
+/// assert_eq!(out, "<===> input.scss
+/// ul { li { list-style: none; } }
+///
+/// <===>
+/// Generated files
+/// <===> out/
+/// <===>
+/// Archive comment");
+/// assert_eq!(HrxArchive::from_str(&out)?.entries.len(), 2);
+/// # Ok::<(), HrxError>(())
+/// ```
+pub struct HrxWriter<W: Write> {
+    into: W,
+    boundary_length: NonZeroUsize,
+    emitter: Emitter,
+    /// Paths of entries written so far, and whether they're directories
+    paths: BTreeMap<String, bool>,
+}
+
+
+impl<W: Write> HrxWriter<W> {
+    /// Create a writer outputting to the specified stream with the specified boundary length.
+    ///
+    /// Nothing is written until the first entry is added.
+    pub fn new(into: W, boundary_length: NonZeroUsize) -> HrxWriter<W> {
+        HrxWriter {
+            into: into,
+            boundary_length: boundary_length,
+            emitter: Emitter::new(boundary_length),
+            paths: BTreeMap::new(),
+        }
+    }
+
+    /// Get the boundary length, i.e. the amount of `=` characters in the boundary.
+    pub fn boundary_length(&self) -> NonZeroUsize {
+        self.boundary_length
+    }
+
+    /// Write a file with an optional comment and contents.
+    pub fn add_file(&mut self, path: &HrxPath, comment: Option<&str>, body: Option<&str>) -> Result<(), Result<HrxError, IoError>> {
+        self.add_file_impl(path, non_empty(comment), non_empty(body).map(str::as_bytes))?;
+        Ok(())
+    }
+
+    /// Write a file with an optional comment and contents that needn't be valid UTF-8.
+    pub fn add_binary_file(&mut self, path: &HrxPath, comment: Option<&str>, body: &[u8]) -> Result<(), Result<HrxError, IoError>> {
+        self.add_file_impl(path, non_empty(comment), Some(body).filter(|b| !b.is_empty()))?;
+        Ok(())
+    }
+
+    /// Write a directory with an optional comment.
+    pub fn add_dir(&mut self, path: &HrxPath, comment: Option<&str>) -> Result<(), Result<HrxError, IoError>> {
+        self.add_dir_impl(path, non_empty(comment))?;
+        Ok(())
+    }
+
+    /// Write the specified entry, whichever kind it is.
+    pub fn add_entry(&mut self, path: &HrxPath, entry: &HrxEntry) -> Result<(), Result<HrxError, IoError>> {
+        match entry.data {
+            HrxEntryData::File { ref body } => self.add_file(path, entry.comment.as_deref(), body.as_deref()),
+            HrxEntryData::Binary { ref body } => self.add_binary_file(path, entry.comment.as_deref(), body),
+            HrxEntryData::Directory => self.add_dir(path, entry.comment.as_deref()),
+        }
+    }
+
+    /// Write the optional archive comment and finish the archive, returning the underlying stream.
+    pub fn finish(mut self, comment: Option<&str>) -> Result<W, Result<HrxError, IoError>> {
+        self.finish_impl(non_empty(comment))?;
+        Ok(self.into)
+    }
+
+    fn add_file_impl(&mut self, path: &HrxPath, comment: Option<&str>, body: Option<&[u8]>) -> Result<(), CompoundError> {
+        let mut erroneous = vec![];
+        if self.contains_boundary(comment.map(str::as_bytes)) {
+            erroneous.push(ErroneousBodyPath::EntryComment(path.to_string()));
+        }
+        if self.contains_boundary(body) {
+            erroneous.push(ErroneousBodyPath::EntryData(path.to_string()));
+        }
+        if !erroneous.is_empty() {
+            return Err(HrxError::from(erroneous).into());
+        }
+
+        parse::validate_its_directory_tree(&path.0, false, &mut self.paths)?;
+        self.emitter.file(&path.0, comment, body, &mut self.into)?;
+
+        Ok(())
+    }
+
+    fn add_dir_impl(&mut self, path: &HrxPath, comment: Option<&str>) -> Result<(), CompoundError> {
+        if self.contains_boundary(comment.map(str::as_bytes)) {
+            return Err(HrxError::from(ErroneousBodyPath::EntryComment(path.to_string())).into());
+        }
+
+        parse::validate_its_directory_tree(&path.0, true, &mut self.paths)?;
+        self.emitter.directory(&path.0, comment, &mut self.into)?;
+
+        Ok(())
+    }
+
+    fn finish_impl(&mut self, comment: Option<&str>) -> Result<(), CompoundError> {
+        if self.contains_boundary(comment.map(str::as_bytes)) {
+            return Err(HrxError::from(ErroneousBodyPath::RootComment).into());
+        }
+
+        self.emitter.finish(comment, &mut self.into)?;
+        self.into.flush()?;
+
+        Ok(())
+    }
+
+    /// Check whether the specified body would contain a newline followed by the boundary when written out,
+    /// which is also the case if it starts with the boundary
+    fn contains_boundary(&self, which: Option<&[u8]>) -> bool {
+        match which {
+            Some(which) => {
+                let bound = self.emitter.bound().as_bytes();
+                which.starts_with(&bound[1..]) || SubstringSearcher::new(bound).find(which).is_some()
+            }
+            None => false,
+        }
+    }
+}
+
+fn non_empty(which: Option<&str>) -> Option<&str> {
+    which.filter(|w| !w.is_empty())
+}
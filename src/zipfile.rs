@@ -0,0 +1,280 @@
+use self::super::{UnrepresentableReason, UnrepresentablePolicy, SkippedFiles, HrxEntryData, HrxArchive, HrxEntry, HrxError, HrxPath, parse};
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Write, Read, Seek};
+use self::super::error::CompoundError;
+use std::collections::{BTreeMap, BTreeSet};
+use std::num::NonZeroUsize;
+use zip::ZipArchive;
+use std::str::FromStr;
+
+
+/// Configuration for [`HrxArchive::from_zip()`](struct.HrxArchive.html#method.from_zip).
+///
+/// # Examples
+///
+/// ```
+/// # use hrx::{UnrepresentablePolicy, ZipOptions};
+/// # use std::num::NonZeroUsize;
+/// assert_eq!(ZipOptions::default(),
+///            ZipOptions {
+///                unrepresentable: UnrepresentablePolicy::Error,
+///                min_boundary_length: NonZeroUsize::new(3).unwrap(),
+///            });
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ZipOptions {
+    /// What to do with symlinks, devices, FIFOs, and files whose paths or contents can't be represented in an archive.
+    pub unrepresentable: UnrepresentablePolicy,
+    /// The shortest boundary length to consider, the actual one will be the shortest one valid for the archive not below this.
+    pub min_boundary_length: NonZeroUsize,
+}
+
+
+impl Default for ZipOptions {
+    fn default() -> ZipOptions {
+        ZipOptions {
+            unrepresentable: UnrepresentablePolicy::Error,
+            min_boundary_length: NonZeroUsize::new(3).unwrap(),
+        }
+    }
+}
+
+
+const S_IFMT: u32 = 0o170000;
+const S_IFREG: u32 = 0o100000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
+
+
+pub fn read_zip<R: Read + Seek>(zip: R, options: &ZipOptions) -> Result<(HrxArchive, SkippedFiles), Result<HrxError, IoError>> {
+    Ok(read_zip_impl(zip, options)?)
+}
+
+fn read_zip_impl<R: Read + Seek>(zip: R, options: &ZipOptions) -> Result<(HrxArchive, SkippedFiles), CompoundError> {
+    let mut skipped = vec![];
+    let mut unrepresentable = |zip_path: &str, reason| options.unrepresentable.handle(zip_path.to_string(), reason, &mut skipped);
+
+    let mut zip = ZipArchive::new(zip).map_err(IoError::from)?;
+
+    let mut entries = vec![];
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(IoError::from)?;
+        let zip_path = file.name().to_string();
+
+        let path = match HrxPath::from_str(zip_path.strip_suffix('/').unwrap_or(&zip_path)) {
+            Ok(path) => path,
+            Err(_) => {
+                unrepresentable(&zip_path, UnrepresentableReason::InvalidPath)?;
+                continue;
+            }
+        };
+
+        let data = match file.unix_mode().map(|m| m & S_IFMT) {
+            _ if file.is_dir() => HrxEntryData::Directory,
+            None | Some(0) | Some(S_IFREG) => {
+                let mut body = vec![];
+                file.read_to_end(&mut body)?;
+                match String::from_utf8(body) {
+                    Ok(body) => HrxEntryData::File { body: if body.is_empty() { None } else { Some(body) } },
+                    Err(_) => {
+                        unrepresentable(&zip_path, UnrepresentableReason::NonUtf8Contents)?;
+                        continue;
+                    }
+                }
+            }
+            Some(S_IFLNK) => {
+                unrepresentable(&zip_path, UnrepresentableReason::Symlink)?;
+                continue;
+            }
+            Some(_) => {
+                unrepresentable(&zip_path, UnrepresentableReason::SpecialFile)?;
+                continue;
+            }
+        };
+
+        entries.push((path,
+                      HrxEntry {
+                          comment: if file.comment().is_empty() { None } else { Some(file.comment().to_string()) },
+                          data: data,
+                      }));
+    }
+
+    // Explicit directories are only kept if empty or commented, as others exist implicitly
+    let parents: BTreeSet<_> = entries.iter().flat_map(|&(ref p, _)| p.0.match_indices('/').map(move |(slash_i, _)| &p.0[..slash_i])).collect();
+    let kept: Vec<_> = entries.iter().filter(|&&(ref p, ref e)| e.data != HrxEntryData::Directory || e.comment.is_some() || !parents.contains(&p.0[..])).collect();
+
+    // Zip tools routinely list directories after their contents, so those are moved ahead of the first entry inside them
+    let mut first_inside = BTreeMap::<&str, usize>::new();
+    for (i, &&(ref path, _)) in kept.iter().enumerate() {
+        for (slash_i, _) in path.0.match_indices('/') {
+            first_inside.entry(&path.0[..slash_i]).or_insert(i);
+        }
+    }
+    let mut order: Vec<_> = (0..kept.len()).collect();
+    order.sort_by_key(|&i| {
+        let path = &kept[i].0 .0;
+        (first_inside.get(&path[..]).map_or(i, |&first| first.min(i)), path.matches('/').count())
+    });
+
+    let mut archive = HrxArchive::new(options.min_boundary_length);
+    let mut paths = BTreeMap::<String, _>::new();
+    for &(ref path, ref entry) in order.into_iter().map(|i| kept[i]) {
+        parse::validate_its_directory_tree(&path.0, entry.data == HrxEntryData::Directory, &mut paths)?;
+        archive.entries.insert(path.clone(), entry.clone());
+    }
+
+    if !zip.comment().is_empty() {
+        archive.comment = Some(String::from_utf8_lossy(zip.comment()).into_owned());
+    }
+
+    archive.boundary_length = archive.minimal_boundary_length_from(options.min_boundary_length);
+    Ok((archive, skipped))
+}
+
+
+/// A central directory record waiting to be written
+struct CentralRecord<'a> {
+    name: String,
+    comment: &'a str,
+    is_dir: bool,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+// Hand-rolled rather than using zip::ZipWriter, as that (as of zip 0.6) can't write per-entry comments, which entry comments map onto
+pub fn write_zip<W: Write>(archive: &HrxArchive, into: &mut W) -> Result<(), IoError> {
+    let mut records = vec![];
+    let mut offset = 0u64;
+
+    // Directories, explicit or not, precede their contents, so they're created with the right permissions
+    let mut written_dirs = BTreeSet::new();
+    for (path, entry) in &archive.entries {
+        for (slash_i, _) in path.0.match_indices('/') {
+            let parent = &path.0[..slash_i];
+            if written_dirs.insert(parent) {
+                records.push(write_local(into, &mut offset, format!("{}/", parent), "", None)?);
+            }
+        }
+
+        let comment = entry.comment.as_ref().map(|c| &c[..]).unwrap_or("");
+        match entry.data {
+            HrxEntryData::File { ref body } => {
+                let body = body.as_ref().map(|b| b.as_bytes()).unwrap_or(&[]);
+                records.push(write_local(into, &mut offset, path.0.clone(), comment, Some(body))?);
+            }
+            HrxEntryData::Binary { ref body } => records.push(write_local(into, &mut offset, path.0.clone(), comment, Some(body))?),
+            HrxEntryData::Directory => {
+                if written_dirs.insert(&path.0) {
+                    records.push(write_local(into, &mut offset, format!("{}/", path), comment, None)?);
+                } else if let Some(record) = records.iter_mut().find(|r| r.is_dir && r.name[..r.name.len() - 1] == path.0) {
+                    // Already written implicitly, which can only happen with an explicit directory after its contents
+                    record.comment = comment;
+                }
+            }
+        }
+    }
+
+    let cd_offset = offset;
+    for record in &records {
+        let name = record.name.as_bytes();
+        let external_attributes = if record.is_dir { S_IFDIR | 0o755 } else { S_IFREG | 0o644 } << 16;
+
+        into.write_all(&[0x50, 0x4B, 0x01, 0x02])?;
+        write_u16(into, 3 << 8 | 20)?; // made by: Unix, 2.0
+        write_header_fields(into, record.crc, record.size, name.len())?;
+        write_u16(into, u16_len(record.comment.len(), "entry comment")?)?;
+        write_u16(into, 0)?; // disk number
+        write_u16(into, 0)?; // internal attributes
+        write_u32(into, external_attributes)?;
+        write_u32(into, record.offset)?;
+        into.write_all(name)?;
+        into.write_all(record.comment.as_bytes())?;
+        offset += 46 + name.len() as u64 + record.comment.len() as u64;
+    }
+
+    let archive_comment = archive.comment.as_ref().map(|c| &c[..]).unwrap_or("");
+    let records_len = u16_len(records.len(), "entry count")?;
+    into.write_all(&[0x50, 0x4B, 0x05, 0x06])?;
+    write_u16(into, 0)?; // disk number
+    write_u16(into, 0)?; // central directory disk number
+    write_u16(into, records_len)?;
+    write_u16(into, records_len)?;
+    write_u32(into, u32_len(offset - cd_offset)?)?;
+    write_u32(into, u32_len(cd_offset)?)?;
+    write_u16(into, u16_len(archive_comment.len(), "archive comment")?)?;
+    into.write_all(archive_comment.as_bytes())?;
+
+    Ok(())
+}
+
+/// Write a stored local file header followed by `body`, or nothing for directories, whose `name` ends with a `/`
+fn write_local<'a, W: Write>(into: &mut W, offset: &mut u64, name: String, comment: &'a str, body: Option<&[u8]>) -> Result<CentralRecord<'a>, IoError> {
+    let body = body.unwrap_or(&[]);
+    let record = CentralRecord {
+        crc: crc32(body),
+        size: u32_len(body.len() as u64)?,
+        offset: u32_len(*offset)?,
+        is_dir: name.ends_with('/'),
+        comment: comment,
+        name: name,
+    };
+
+    into.write_all(&[0x50, 0x4B, 0x03, 0x04])?;
+    write_header_fields(into, record.crc, record.size, record.name.len())?;
+    into.write_all(record.name.as_bytes())?;
+    into.write_all(body)?;
+
+    *offset += 30 + record.name.len() as u64 + body.len() as u64;
+    Ok(record)
+}
+
+/// The fields shared by local and central headers, from the version needed to extract up to the extra field length
+fn write_header_fields<W: Write>(into: &mut W, crc: u32, size: u32, name_len: usize) -> Result<(), IoError> {
+    write_u16(into, 20)?; // needed to extract: 2.0
+    write_u16(into, 1 << 11)?; // flags: UTF-8 names and comments
+    write_u16(into, 0)?; // stored
+    write_u16(into, 0)?; // modified at 00:00:00,
+    write_u16(into, 1 << 5 | 1)?; // 1980-01-01, the DOS epoch
+    write_u32(into, crc)?;
+    write_u32(into, size)?; // compressed
+    write_u32(into, size)?; // uncompressed
+    write_u16(into, u16_len(name_len, "entry path")?)?;
+    write_u16(into, 0) // extra field length
+}
+
+fn write_u16<W: Write>(into: &mut W, val: u16) -> Result<(), IoError> {
+    into.write_all(&val.to_le_bytes())
+}
+
+fn write_u32<W: Write>(into: &mut W, val: u32) -> Result<(), IoError> {
+    into.write_all(&val.to_le_bytes())
+}
+
+fn u16_len(len: usize, what: &str) -> Result<u16, IoError> {
+    if len > u16::MAX as usize {
+        Err(IoError::new(IoErrorKind::InvalidInput, format!("{} too long for a zip archive: {}", what, len)))
+    } else {
+        Ok(len as u16)
+    }
+}
+
+/// ZIP64 isn't supported
+fn u32_len(len: u64) -> Result<u32, IoError> {
+    if len > u32::MAX as u64 {
+        Err(IoError::new(IoErrorKind::InvalidInput, format!("archive too large for a non-ZIP64 zip archive: {} bytes", len)))
+    } else {
+        Ok(len as u32)
+    }
+}
+
+/// The CRC-32 used by zip, with the reflected 0xEDB88320 polynomial
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,198 @@
+use hrx::{UnrepresentableReason, ErroneousBodyPath, HrxEntryDataRef, ExtractOptions, BASE64_COMMENT, HrxArchiveRef, HrxEntryData, BinaryPolicy, PackOptions,
+          HrxArchive, HrxWriter, HrxError, HrxEntry, HrxPath};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::env::temp_dir;
+use std::fs;
+
+
+/// A PNG signature, a Latin-1 "café", and the bytes which would be a boundary, if they were preceded by a newline
+const BINARY: &[u8] = b"\x89PNG\r\n\x1A\n\x00caf\xE9\n<===>";
+
+
+fn root(name: &str) -> PathBuf {
+    let root = temp_dir().join("hrx.rs-test").join("binary").join(name);
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+
+#[test]
+fn parse() {
+    let mut input = b"<====>\nA comment\n<====> image.png\n".to_vec();
+    input.extend_from_slice(BINARY);
+    input.extend_from_slice(b"\n<====> text.txt\ncaf\xC3\xA9\n<====> dir/\n");
+
+    let arch = HrxArchive::from_bytes(&input).unwrap();
+    assert_eq!(arch.boundary_length().get(), 4);
+    assert_eq!(arch.entries["image.png"].comment, Some("A comment".to_string()));
+    assert_eq!(arch.entries["image.png"].data, HrxEntryData::Binary { body: BINARY.to_vec() });
+    assert_eq!(arch.entries["text.txt"].data, HrxEntryData::File { body: Some("café".to_string()) });
+    assert_eq!(arch.entries["dir"].data, HrxEntryData::Directory);
+
+    let mut out = vec![];
+    arch.serialise(&mut out).unwrap();
+    assert_eq!(out, input);
+}
+
+#[test]
+fn parse_borrowed() {
+    let mut input = b"<====> image.png\n".to_vec();
+    let body_start = input.len();
+    input.extend_from_slice(BINARY);
+    input.extend_from_slice(b"\n<====> text.txt\ncaf\xC3\xA9");
+
+    let arch = HrxArchiveRef::from_bytes(&input).unwrap();
+    assert_eq!(arch.get("image.png").unwrap().data, HrxEntryDataRef::Binary { body: BINARY });
+    assert_eq!(arch.get("text.txt").unwrap().data, HrxEntryDataRef::File { body: Some("café") });
+
+    let body = arch.entry_spans("image.png").unwrap().body.unwrap();
+    assert_eq!((body.start.offset, body.end.offset), (body_start, body_start + BINARY.len()));
+    assert_eq!((body.end.line, body.end.column), (5, 6));
+
+    assert_eq!(Ok(arch.into_owned()), HrxArchive::from_bytes(&input));
+}
+
+#[test]
+fn parse_text() {
+    let input = "<===> input.scss\nul {}\n<===> out/\n<===>\nthe end";
+    assert_eq!(HrxArchive::from_bytes(input.as_bytes()), HrxArchive::from_str(input));
+    assert_eq!(HrxArchive::from_bytes(b"no boundary \xFF"), Err(HrxError::NoBoundary));
+}
+
+#[test]
+fn parse_invalid() {
+    for &(input, line, column) in &[(&b"<===> caf\xE9\n"[..], 1, 10),
+                                    (&b"<===>\ncaf\xE9\n<===> file\n"[..], 2, 4),
+                                    (&b"<===> file\n<===>\nthe \xFF end"[..], 3, 5),
+                                    (&b"<===> file\nok\n<===> dir/\n\xFF"[..], 4, 1)] {
+        match HrxArchive::from_bytes(input) {
+            Err(HrxError::Parse(err)) => {
+                assert_eq!((err.line, err.column), (line, column), "{:?}", input);
+                assert_eq!(err.expected.into_iter().collect::<Vec<_>>(), vec!["valid UTF-8"]);
+            }
+            err => panic!("{:?}: {:?}", input, err),
+        }
+    }
+}
+
+#[test]
+fn boundaries() {
+    let mut arch = HrxArchive::new(NonZeroUsize::new(3).unwrap());
+    arch.entries.insert(HrxPath::from_str("image.png").unwrap(), HrxEntry { comment: None, data: HrxEntryData::Binary { body: BINARY.to_vec() } });
+
+    assert_eq!(arch.validate_content(), Err(ErroneousBodyPath::EntryData("image.png".to_string()).into()));
+    assert_eq!(arch.minimal_boundary_length().get(), 1);
+    assert_eq!(arch.set_boundary_length(NonZeroUsize::new(4).unwrap()), Ok(()));
+
+    arch.entries.insert(HrxPath::from_str("start.bin").unwrap(), HrxEntry { comment: None, data: HrxEntryData::Binary { body: b"<====>\xFF".to_vec() } });
+    assert_eq!(arch.minimal_boundary_length().get(), 1);
+    assert_eq!(arch.set_boundary_length(NonZeroUsize::new(1).unwrap()), Ok(()));
+
+    let mut writer = HrxWriter::new(vec![], NonZeroUsize::new(3).unwrap());
+    assert_eq!(writer.add_binary_file(&HrxPath::from_str("image.png").unwrap(), None, BINARY).unwrap_err().unwrap(),
+               ErroneousBodyPath::EntryData("image.png".to_string()).into());
+    writer.add_entry(&HrxPath::from_str("start.bin").unwrap(), &arch.entries["start.bin"]).unwrap();
+    assert_eq!(writer.finish(None).unwrap(), b"<===> start.bin\n<====>\xFF");
+}
+
+#[test]
+fn pack_unrepresentable() {
+    let root = root("pack_unrepresentable");
+    fs::write(root.join("image.png"), BINARY).unwrap();
+
+    assert_eq!(HrxArchive::from_dir(&root, &PackOptions::default()).unwrap_err().unwrap(),
+               HrxError::Unrepresentable(root.join("image.png").display().to_string(), UnrepresentableReason::NonUtf8Contents));
+}
+
+#[test]
+fn pack_raw() {
+    let root = root("pack_raw");
+    fs::write(root.join("image.png"), BINARY).unwrap();
+    fs::write(root.join("text.txt"), "café").unwrap();
+
+    let arch = HrxArchive::from_dir(&root,
+                                    &PackOptions {
+                                        binary: BinaryPolicy::Raw,
+                                        ..PackOptions::default()
+                                    })
+        .unwrap();
+    assert_eq!(arch.boundary_length().get(), 4);
+    assert_eq!(arch.entries["image.png"].data, HrxEntryData::Binary { body: BINARY.to_vec() });
+    assert_eq!(arch.entries["text.txt"].data, HrxEntryData::File { body: Some("café".to_string()) });
+
+    let mut out = vec![];
+    arch.serialise(&mut out).unwrap();
+    assert_eq!(HrxArchive::from_bytes(&out).unwrap(), arch);
+
+    let target = root.join("out");
+    arch.extract_to(&target, &ExtractOptions::default()).unwrap();
+    assert_eq!(fs::read(target.join("image.png")).unwrap(), BINARY);
+    assert_eq!(fs::read_to_string(target.join("text.txt")).unwrap(), "café");
+}
+
+#[test]
+fn pack_base64() {
+    let root = root("pack_base64");
+    fs::write(root.join("image.png"), BINARY).unwrap();
+    fs::write(root.join("large.bin"), (0..100).map(|b| b ^ 0x80).collect::<Vec<u8>>()).unwrap();
+    fs::write(root.join("text.txt"), "café").unwrap();
+
+    let arch = HrxArchive::from_dir(&root,
+                                    &PackOptions {
+                                        binary: BinaryPolicy::Base64,
+                                        ..PackOptions::default()
+                                    })
+        .unwrap();
+
+    let mut out = vec![];
+    arch.serialise(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(),
+               format!("<===>\n{comment}\n<===> image.png\niVBORw0KGgoAY2Fm6Qo8PT09Pg==\n\n<===>\n{comment}\n<===> large.bin\n\
+                        gIGCg4SFhoeIiYqLjI2Oj5CRkpOUlZaXmJmam5ydnp+goaKjpKWmp6ipqqusra6vsLGys7S1tre4\n\
+                        ubq7vL2+v8DBwsPExcbHyMnKy8zNzs/Q0dLT1NXW19jZ2tvc3d7f4OHi4w==\n\n<===> text.txt\ncafé",
+                       comment = BASE64_COMMENT));
+
+    let verbatim = root.join("verbatim");
+    arch.extract_to(&verbatim, &ExtractOptions::default()).unwrap();
+    assert_eq!(fs::read_to_string(verbatim.join("image.png")).unwrap(), "iVBORw0KGgoAY2Fm6Qo8PT09Pg==\n");
+
+    let decoded = root.join("decoded");
+    arch.extract_to(&decoded,
+                    &ExtractOptions {
+                        decode_base64: true,
+                        ..ExtractOptions::default()
+                    })
+        .unwrap();
+    assert_eq!(fs::read(decoded.join("image.png")).unwrap(), BINARY);
+    assert_eq!(fs::read(decoded.join("large.bin")).unwrap(), (0..100).map(|b| b ^ 0x80).collect::<Vec<u8>>());
+    assert_eq!(fs::read_to_string(decoded.join("text.txt")).unwrap(), "café");
+}
+
+#[test]
+fn extract_invalid_base64() {
+    let root = root("extract_invalid_base64");
+    for body in &["not base64!", "QQ=", "Q", "QQ==QQ==", "QUJD===="] {
+        let arch = HrxArchive::from_str(&format!("<===>\n{}\n<===> file.bin\n{}", BASE64_COMMENT, body)).unwrap();
+        assert!(arch.extract_to(&root,
+                                &ExtractOptions {
+                                    decode_base64: true,
+                                    ..ExtractOptions::default()
+                                })
+                    .is_err(),
+                "{}",
+                body);
+    }
+}
+
+#[test]
+fn diff() {
+    let old = HrxArchive::from_bytes(b"<===> image.png\n\x89PNG\n<===> text.txt\nold").unwrap();
+    let new = HrxArchive::from_bytes(b"<===> image.png\n\x89PNG!\n<===> text.txt\n\xFF").unwrap();
+
+    let diff = old.diff(&new);
+    assert!(!diff.entries.values().any(|d| d.is_kind_change()));
+    assert_eq!(diff.unified(3), "Binary files a/image.png and b/image.png differ\nBinary files a/text.txt and b/text.txt differ\n");
+}
@@ -0,0 +1,81 @@
+use std::process::{Command, Stdio};
+use std::path::{PathBuf, Path};
+use std::env::temp_dir;
+use std::fs;
+
+
+fn dir(name: &str) -> PathBuf {
+    let root = temp_dir().join("hrx.rs-test").join("cli").join(name);
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+fn hrx(args: &[&str]) -> i32 {
+    Command::new(env!("CARGO_BIN_EXE_hrx")).args(args).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).status().unwrap().code().unwrap()
+}
+
+fn write(root: &Path, name: &str, body: &str) -> String {
+    let path = root.join(name);
+    fs::write(&path, body).unwrap();
+    path.display().to_string()
+}
+
+
+#[test]
+fn validate() {
+    let root = dir("validate");
+    let duplicates = write(&root, "duplicates.hrx", "<===> a\n<===> a\n");
+    let garbage = write(&root, "garbage.hrx", "not an archive\n");
+
+    assert_eq!(hrx(&["validate", "ext/hrx/example/simple.hrx", "ext/hrx/example/directory.hrx"]), 0);
+    assert_eq!(hrx(&["validate", "ext/hrx/example/simple.hrx", &duplicates]), 2);
+    assert_eq!(hrx(&["validate", &garbage]), 2);
+    assert_eq!(hrx(&["validate", "ext/hrx/example/nonexistent.hrx"]), 3);
+}
+
+#[test]
+fn merge_clean() {
+    let root = dir("merge_clean");
+    let base = write(&root, "base.hrx", "<===> a\nx\n");
+    let ours = write(&root, "ours.hrx", "<===> a\ny\n");
+    let theirs = write(&root, "theirs.hrx", "<===> b\nz\n<===> a\nx\n");
+
+    assert_eq!(hrx(&["merge", &base, &ours, &theirs]), 0);
+    let merged = fs::read_to_string(&ours).unwrap();
+    assert!(merged.contains("<===> a\ny\n"));
+    assert!(merged.contains("<===> b\nz"));
+}
+
+#[test]
+fn merge_conflicts() {
+    let root = dir("merge_conflicts");
+    let base = write(&root, "base.hrx", "<===> a\nx\n");
+    let ours = write(&root, "ours.hrx", "<===> a\ny\n");
+    let theirs = write(&root, "theirs.hrx", "<===> a\nz\n");
+    let output = root.join("out.hrx").display().to_string();
+
+    assert_eq!(hrx(&["merge", &base, &ours, &theirs, "-o", &output]), 7);
+    assert!(fs::read_to_string(&output).unwrap().contains("\n<<<<<<<"));
+    assert_eq!(fs::read_to_string(&ours).unwrap(), "<===> a\ny\n");
+}
+
+#[test]
+fn merge_invalid() {
+    let root = dir("merge_invalid");
+    let base = write(&root, "base.hrx", "");
+    let ours = write(&root, "ours.hrx", "not an archive\n");
+
+    assert_eq!(hrx(&["merge", &base, &ours, &base]), 2);
+    assert_eq!(hrx(&["merge", &base, &base, &root.join("nonexistent.hrx").display().to_string()]), 3);
+}
+
+#[test]
+fn merge_arguments() {
+    let root = dir("merge_arguments");
+    let base = write(&root, "base.hrx", "");
+
+    assert_eq!(hrx(&["merge", &base, &base, &base, "-o", "-", "-L", "0"]), 1);
+    assert_eq!(hrx(&["merge", "-", &base, "-", "-o", "-"]), 1);
+    assert_eq!(hrx(&["merge", &base, &base, "-", "-o", "-"]), 0);
+}
@@ -0,0 +1,202 @@
+use hrx::{HrxEntryData, HrxEntryDiff, HrxArchive, HrxEntry, HrxPath};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::io::Read;
+
+
+fn examples() -> Vec<HrxArchive> {
+    let mut ret = vec![];
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        ret.push(HrxArchive::from_str(&body).unwrap());
+    }
+    ret
+}
+
+fn file(body: &str) -> HrxArchive {
+    let mut arch = HrxArchive::new(NonZeroUsize::new(3).unwrap());
+    arch.entries.insert("file".parse().unwrap(),
+                        HrxEntry {
+                            comment: None,
+                            data: HrxEntryData::File { body: Some(body.to_string()) },
+                        });
+    arch
+}
+
+/// Apply the unified diff of a single file to `old`
+fn patch(old: &str, diff: &str) -> String {
+    let old_lines: Vec<_> = old.split_inclusive('\n').collect();
+    let mut new = String::new();
+    let mut old_i = 0;
+
+    let mut lines = diff.lines().skip(2).peekable();
+    while let Some(line) = lines.next() {
+        let no_newline = lines.peek() == Some(&"\\ No newline at end of file");
+        if no_newline {
+            lines.next();
+        }
+        let content = &line[1..];
+
+        match &line[..1] {
+            "@" => {
+                // "@@ -start,len +..." with ",len" omitted for 1, and start before the hunk for 0
+                let mut old_range = line[4..].split(' ').next().unwrap().split(',');
+                let start: usize = old_range.next().unwrap().parse().unwrap();
+                let old_start = match old_range.next() {
+                    Some("0") => start,
+                    _ => start - 1,
+                };
+                while old_i < old_start {
+                    new.push_str(old_lines[old_i]);
+                    old_i += 1;
+                }
+            }
+            " " | "-" => {
+                assert_eq!(old_lines[old_i].trim_end_matches('\n'), content);
+                assert_eq!(old_lines[old_i].ends_with('\n'), !no_newline);
+                if &line[..1] == " " {
+                    new.push_str(old_lines[old_i]);
+                }
+                old_i += 1;
+            }
+            "+" => {
+                new.push_str(content);
+                if !no_newline {
+                    new.push('\n');
+                }
+            }
+            _ => panic!("{:?}", line),
+        }
+    }
+
+    for line in &old_lines[old_i..] {
+        new.push_str(line);
+    }
+    new
+}
+
+
+#[test]
+fn boundary_length_ignored() {
+    for arch in examples() {
+        let mut longer = arch.clone();
+        let boundary_length = NonZeroUsize::new(arch.minimal_boundary_length().get() + 10).unwrap();
+        longer.set_boundary_length(boundary_length).unwrap();
+
+        assert!(arch.diff(&longer).is_empty());
+        assert_eq!(arch.diff(&longer).unified(3), "");
+    }
+}
+
+#[test]
+fn reconstruct() {
+    let examples = examples();
+    for old in &examples {
+        for new in &examples {
+            let diff = old.diff(new);
+
+            let mut entries: BTreeMap<_, _> = old.entries.iter().map(|(p, e)| (p.clone(), e.clone())).collect();
+            for (path, entry_diff) in &diff.entries {
+                match *entry_diff {
+                    HrxEntryDiff::Added(ref entry) => assert!(entries.insert(path.clone(), entry.clone()).is_none()),
+                    HrxEntryDiff::Removed(ref entry) => assert_eq!(entries.remove(path).as_ref(), Some(entry)),
+                    HrxEntryDiff::Modified { ref comment, ref data } => {
+                        assert!(comment.is_some() || data.is_some());
+
+                        let entry = entries.get_mut(path).unwrap();
+                        if let Some(ref comment) = *comment {
+                            assert_eq!(entry.comment, comment.old);
+                            entry.comment = comment.new.clone();
+                        }
+                        if let Some(ref data) = *data {
+                            assert_eq!(entry.data, data.old);
+                            entry.data = data.new.clone();
+                        }
+                    }
+                }
+            }
+            assert_eq!(entries, new.entries.iter().map(|(p, e)| (p.clone(), e.clone())).collect());
+
+            match diff.comment {
+                Some(ref comment) => {
+                    assert_eq!(old.comment, comment.old);
+                    assert_eq!(new.comment, comment.new);
+                }
+                None => assert_eq!(old.comment, new.comment),
+            }
+        }
+    }
+}
+
+#[test]
+fn unified_applies() {
+    let bodies = ["",
+                  "one line",
+                  "one line\n",
+                  "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n",
+                  "a\nb\nC\nd\ne\nf\ng\nh\ni\nj\n",
+                  "a\nb\nc\nd\ne\nf\ng\nh\ni\nj",
+                  "x\na\nb\nc\nd\ne\nf\ng\nh\ni\nj\ny\n",
+                  "b\nc\nd\nX\nY\nf\ng\nh\ni\n",
+                  "\n\n\n",
+                  "j\ni\nh\ng\nf\ne\nd\nc\nb\na\n"];
+
+    for old in &bodies {
+        for new in &bodies {
+            let diff = file(old).diff(&file(new));
+            let path = HrxPath::from_str("file").unwrap();
+
+            for context in 0..4 {
+                match diff.entries.get(&path) {
+                    Some(entry_diff) => {
+                        let unified = entry_diff.unified(&path, context).unwrap();
+                        assert_eq!(&patch(old, &unified), new, "{:?} -> {:?}, {} context:\n{}", old, new, context, unified);
+                    }
+                    None => assert_eq!(old, new),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn nothing_in_common() {
+    let old: String = (0..4000).map(|i| format!("old {}\n", i)).collect();
+    let new: String = (0..4000).map(|i| format!("new {}\n", i)).collect();
+
+    let path = HrxPath::from_str("file").unwrap();
+    let unified = file(&old).diff(&file(&new)).entries[&path].unified(&path, 3).unwrap();
+    assert!(unified.starts_with("--- a/file\n+++ b/file\n@@ -1,4000 +1,4000 @@\n-old 0\n"));
+    assert_eq!(unified.lines().filter(|l| l.starts_with('-')).count(), 4000 + 1);
+    assert_eq!(unified.lines().filter(|l| l.starts_with('+')).count(), 4000 + 1);
+    assert_eq!(patch(&old, &unified), new);
+}
+
+#[test]
+fn kind_change() {
+    let mut dir = HrxArchive::new(NonZeroUsize::new(3).unwrap());
+    dir.entries.insert("file".parse().unwrap(),
+                       HrxEntry {
+                           comment: None,
+                           data: HrxEntryData::Directory,
+                       });
+
+    let path = HrxPath::from_str("file").unwrap();
+    let to_dir = file("contents\n").diff(&dir);
+    assert!(to_dir.entries[&path].is_kind_change());
+    assert_eq!(to_dir.unified(3), "--- a/file\n+++ /dev/null\n@@ -1 +0,0 @@\n-contents\n");
+
+    let to_file = dir.diff(&file(""));
+    assert!(to_file.entries[&path].is_kind_change());
+    assert_eq!(to_file.unified(3), "--- /dev/null\n+++ b/file\n");
+
+    assert!(!file("a").diff(&file("b")).entries[&path].is_kind_change());
+}
@@ -1,4 +1,4 @@
-use hrx::{HrxEntryData, HrxArchive};
+use hrx::{HrxEntryData, HrxArchiveRef, HrxArchive};
 use std::fs::{self, File};
 use std::str::FromStr;
 use std::io::Read;
@@ -44,3 +44,54 @@ fn invalid() {
         }
     }
 }
+
+#[test]
+fn borrowed() {
+    for dir in &["ext/hrx/example", "ext/hrx/example/invalid"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                continue;
+            }
+
+            let mut body = String::new();
+            File::open(path).unwrap().read_to_string(&mut body).unwrap();
+
+            assert_eq!(HrxArchiveRef::parse(&body).map(HrxArchiveRef::into_owned), HrxArchive::from_str(&body));
+        }
+    }
+}
+
+#[test]
+fn bytes() {
+    for dir in &["ext/hrx/example", "ext/hrx/example/invalid"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                continue;
+            }
+
+            let mut body = String::new();
+            File::open(path).unwrap().read_to_string(&mut body).unwrap();
+
+            assert_eq!(HrxArchiveRef::from_bytes(body.as_bytes()), HrxArchiveRef::parse(&body));
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mapped() {
+    for dir in &["ext/hrx/example", "ext/hrx/example/invalid"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                continue;
+            }
+
+            // The corpus isn't written to while testing
+            let map = unsafe { HrxArchive::open_mmap(&path).unwrap() };
+            assert_eq!(map.archive().map(HrxArchiveRef::into_owned), HrxArchive::from_str(&fs::read_to_string(&path).unwrap()));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use hrx::{ExtractOptions, OverwritePolicy, HrxArchive, HrxError};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::env::temp_dir;
+use std::fs;
+
+
+fn target(name: &str) -> PathBuf {
+    let root = temp_dir().join("hrx.rs-test").join("extract").join(name);
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+
+#[test]
+fn directories() {
+    let root = target("directories");
+    let arch = HrxArchive::from_str("<===> dir/\n<===> dir/subdir/\n<===> other/subdir/\n").unwrap();
+
+    assert_eq!(arch.extract_to(&root, &ExtractOptions::default()).unwrap(),
+               vec![root.join("dir"), root.join("dir").join("subdir"), root.join("other"), root.join("other").join("subdir")]);
+    assert!(root.join("dir").join("subdir").is_dir());
+    assert!(root.join("other").join("subdir").is_dir());
+}
+
+#[test]
+fn empty_file() {
+    let root = target("empty_file");
+    let arch = HrxArchive::from_str("<===> file1\n<===> file2\n").unwrap();
+
+    assert_eq!(arch.extract_to(&root, &ExtractOptions::default()).unwrap(),
+               vec![root.join("file1"), root.join("file2")]);
+    assert_eq!(fs::read_to_string(root.join("file1")).unwrap(), "");
+    assert_eq!(fs::read_to_string(root.join("file2")).unwrap(), "");
+}
+
+#[test]
+fn dry_run() {
+    let root = target("dry_run");
+    let arch = HrxArchive::from_str("<===> dir/file\ncontents\n").unwrap();
+
+    assert_eq!(arch.extract_to(&root,
+                               &ExtractOptions {
+                                   overwrite: OverwritePolicy::Error,
+                                   dry_run: true,
+                                   decode_base64: false,
+                               })
+                   .unwrap(),
+               vec![root.join("dir"), root.join("dir").join("file")]);
+    assert!(!root.exists());
+}
+
+#[test]
+fn overwrite() {
+    let root = target("overwrite");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("file"), "old contents").unwrap();
+
+    let arch = HrxArchive::from_str("<===> file\nnew contents").unwrap();
+    assert!(arch.extract_to(&root, &ExtractOptions::default()).is_err());
+    assert_eq!(fs::read_to_string(root.join("file")).unwrap(), "old contents");
+
+    assert_eq!(arch.extract_to(&root,
+                               &ExtractOptions {
+                                   overwrite: OverwritePolicy::Overwrite,
+                                   dry_run: false,
+                                   decode_base64: false,
+                               })
+                   .unwrap(),
+               vec![root.join("file")]);
+    assert_eq!(fs::read_to_string(root.join("file")).unwrap(), "new contents");
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_invalid_names() {
+    use hrx::InvalidPathReason;
+
+    let root = target("windows_invalid_names");
+    let arch = HrxArchive::from_str("<===> dir/file\n<===> dir/aux.txt\ncontents\n").unwrap();
+
+    assert_eq!(arch.extract_to(&root, &ExtractOptions::default()).unwrap_err().unwrap(),
+               HrxError::InvalidPathComponent("dir/aux.txt".to_string(), "aux.txt".to_string(), InvalidPathReason::ReservedName));
+    assert!(!root.join("dir").join("aux.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink() {
+    use std::os::unix::fs::symlink;
+
+    let root = target("symlink");
+    let outside = target("symlink-outside");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    symlink(&outside, root.join("dir")).unwrap();
+
+    let arch = HrxArchive::from_str("<===> dir/file\ncontents\n").unwrap();
+    assert_eq!(arch.extract_to(&root, &ExtractOptions::default()).unwrap_err().unwrap(),
+               HrxError::PathEscapesTarget("dir/file".to_string()));
+    assert!(!outside.join("file").exists());
+}
@@ -0,0 +1,198 @@
+use hrx::{HrxSerdeError, HrxEntryData, HrxArchive, HrxError};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Fixture {
+    input: String,
+    output: Option<String>,
+    error: Option<String>,
+    precision: u8,
+    indent: char,
+    warnings: Vec<String>,
+    syntax: Syntax,
+    importer: Importer,
+    options: BTreeMap<u16, bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Syntax {
+    Scss,
+    Sass,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Importer {
+    None,
+    Path(String),
+    Load { url: String, contents: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Empty {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Nothing {
+    a: Option<String>,
+    b: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct Borrowed<'a> {
+    a: &'a str,
+    #[serde(borrow)]
+    b: BTreeMap<&'a str, &'a str>,
+}
+
+
+fn fixture() -> Fixture {
+    Fixture {
+        input: "a {\n  b: c;\n}\n".to_string(),
+        output: Some("a {\n  b: c;\n}\n\n".to_string()),
+        error: None,
+        precision: 10,
+        indent: ' ',
+        warnings: vec!["deprecated".to_string(), "".to_string()],
+        syntax: Syntax::Sass,
+        importer: Importer::Load {
+            url: "http://example.com/".to_string(),
+            contents: "<===>\n".to_string(),
+        },
+        options: vec![(1, true), (20, false)].into_iter().collect(),
+    }
+}
+
+
+#[test]
+fn round_trip() {
+    let fixture = fixture();
+    let archive = hrx::to_archive(&fixture).unwrap();
+    assert_eq!(hrx::from_archive::<Fixture>(&archive).unwrap(), fixture);
+
+    let serialised = hrx::to_string(&fixture).unwrap();
+    assert_eq!(HrxArchive::from_str(&serialised).unwrap(), archive);
+    assert_eq!(hrx::from_str::<Fixture>(&serialised).unwrap(), fixture);
+}
+
+#[test]
+fn format() {
+    assert_eq!(hrx::to_string(&fixture()).unwrap(),
+               "<====> input\na {\n  b: c;\n}\n\n<====> output\na {\n  b: c;\n}\n\n\n<====> precision\n10\n<====> indent\n \n\
+                <====> warnings/0\ndeprecated\n<====> warnings/1\n<====> syntax\nSass\n<====> importer/Load/url\nhttp://example.com/\n\
+                <====> importer/Load/contents\n<===>\n\n<====> options/1\ntrue\n<====> options/20\nfalse");
+}
+
+#[test]
+fn empty() {
+    assert_eq!(hrx::to_string(&Vec::<String>::new()).unwrap(), "<===>\n\n");
+    assert_eq!(hrx::from_str::<Vec<String>>(&hrx::to_string(&Vec::<String>::new()).unwrap()).unwrap(), Vec::<String>::new());
+    assert_eq!(hrx::from_archive::<Vec<String>>(&hrx::to_archive(&Vec::<String>::new()).unwrap()).unwrap(), Vec::<String>::new());
+    assert_eq!(hrx::from_str::<BTreeMap<String, String>>(&hrx::to_string(&BTreeMap::<String, String>::new()).unwrap()).unwrap(),
+               BTreeMap::new());
+    assert_eq!(hrx::from_str::<Empty>(&hrx::to_string(&Empty {}).unwrap()).unwrap(), Empty {});
+
+    let nothing = Nothing { a: None, b: None };
+    assert_eq!(hrx::from_str::<Nothing>(&hrx::to_string(&nothing).unwrap()).unwrap(), nothing);
+
+    let mut nested = BTreeMap::new();
+    nested.insert("empty", BTreeMap::<String, String>::new());
+    assert_eq!(hrx::to_string(&nested).unwrap(), "<===> empty/\n");
+    assert_eq!(hrx::from_str::<BTreeMap<String, BTreeMap<String, String>>>("<===> empty/\n").unwrap().len(), 1);
+}
+
+#[test]
+fn borrowed() {
+    let archive = HrxArchive::from_str("<===> a\nalpha\n<===> b/c\n<===> b/d\ndelta").unwrap();
+    let read: Borrowed = hrx::from_archive(&archive).unwrap();
+    assert_eq!(read.a, "alpha");
+    assert_eq!(read.b, vec![("c", ""), ("d", "delta")].into_iter().collect());
+}
+
+#[test]
+fn unsupported() {
+    assert_eq!(hrx::to_string(&"top-level string"), Err(HrxSerdeError::Unsupported("a scalar at the top level".to_string())));
+
+    for key in &["", ".", "..", "a/b", "back\\slash", "c:", "new\nline"] {
+        let mut map = BTreeMap::new();
+        map.insert(*key, "value");
+        assert!(hrx::to_string(&map).is_err(), "{:?}", key);
+    }
+
+    let mut map = BTreeMap::new();
+    map.insert(vec![1], "value");
+    assert!(hrx::to_string(&map).is_err());
+}
+
+#[test]
+fn optional_elements() {
+    let all = vec![Some(0), Some(1), Some(2), Some(3)];
+    assert_eq!(hrx::from_archive::<Vec<Option<u8>>>(&hrx::to_archive(&all).unwrap()).unwrap(), all);
+
+    let err = Err(HrxSerdeError::Unsupported("None as a sequence or tuple element".to_string()));
+    assert_eq!(hrx::to_archive(&vec![None, Some(1), None, Some(3)]), err);
+    assert_eq!(hrx::to_archive(&(Some(0), None::<u8>)), err);
+    assert_eq!(hrx::to_archive(&vec![Some(None::<u8>)]), err);
+
+    let mut nested = BTreeMap::new();
+    nested.insert("a", vec![BTreeMap::<String, Option<u8>>::new()]);
+    nested.get_mut("a").unwrap()[0].insert("b".to_string(), None);
+    assert_eq!(hrx::to_string(&nested).unwrap(), "<===> a/0/\n");
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(hrx::from_str::<Fixture>("not an archive"), Err(HrxSerdeError::Hrx(HrxError::NoBoundary)));
+    assert!(hrx::from_str::<Fixture>("<===> input\n").is_err());
+    assert!(hrx::from_str::<BTreeMap<String, u8>>("<===> a\n256").is_err());
+    assert!(hrx::from_str::<BTreeMap<String, char>>("<===> a\nab").is_err());
+    assert!(hrx::from_str::<BTreeMap<String, String>>("<===> a/b\n").is_err());
+    assert!(hrx::from_str::<BTreeMap<String, Syntax>>("<===> a\nScssy").is_err());
+    assert!(hrx::from_str::<BTreeMap<String, Importer>>("<===> a/Path\n<===> a/None\n").is_err());
+}
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let archive = HrxArchive::from_str(&body).unwrap();
+
+        let mut files: Vec<_> = archive.entries
+            .iter()
+            .flat_map(|(p, e)| match e.data {
+                HrxEntryData::File { ref body } => Some((p.to_string(), body.as_ref().map(|b| &b[..]).unwrap_or(""))),
+                _ => None,
+            })
+            .collect();
+        files.sort();
+        assert_eq!(hrx::from_archive::<Tree>(&archive).unwrap().files(""), files, "{}", path.display());
+    }
+}
+
+
+/// Any archive, with borrowed bodies
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Tree<'a> {
+    File(&'a str),
+    Directory(#[serde(borrow)] BTreeMap<&'a str, Tree<'a>>),
+}
+
+impl<'a> Tree<'a> {
+    fn files(&self, prefix: &str) -> Vec<(String, &'a str)> {
+        match self {
+            &Tree::File(body) => vec![(prefix.to_string(), body)],
+            &Tree::Directory(ref children) => {
+                children.iter().flat_map(|(name, child)| child.files(&if prefix.is_empty() { name.to_string() } else { format!("{}/{}", prefix, name) })).collect()
+            }
+        }
+    }
+}
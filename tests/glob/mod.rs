@@ -0,0 +1,57 @@
+use hrx::{HrxArchive, Glob};
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let arch = HrxArchive::from_str(&body).unwrap();
+
+        for pattern in &["**", "*", "**/*", "*/**", "**/*.*", "**/[a-m]*", "?*/**"] {
+            let matching = arch.glob(pattern).map(|(p, e)| (p.clone(), e.clone())).collect::<Vec<_>>();
+
+            let mut retained = arch.clone();
+            retained.retain_glob(pattern);
+            assert_eq!(retained.entries.into_iter().collect::<Vec<_>>(), matching, "{}: {}", path.display(), pattern);
+
+            let mut removed = arch.clone();
+            assert_eq!(removed.remove_glob(pattern), matching, "{}: {}", path.display(), pattern);
+            assert!(removed.glob(pattern).next().is_none(), "{}: {}", path.display(), pattern);
+            assert_eq!(removed.entries.len() + matching.len(), arch.entries.len(), "{}: {}", path.display(), pattern);
+        }
+
+        assert_eq!(arch.glob("**").count(), arch.entries.len(), "{}", path.display());
+    }
+}
+
+#[test]
+fn patterns() {
+    for &(pattern, path, matches) in &[("out/*.css", "out/input.css", true),
+                                       ("out/*.css", "out/nested/input.css", false),
+                                       ("out/**/*.css", "out/input.css", true),
+                                       ("out/**/*.css", "out/a/b/input.css", true),
+                                       ("out/**", "out", true),
+                                       ("**/out", "a/b/out", true),
+                                       ("*", "", true),
+                                       ("?", "", false),
+                                       ("?", "ы", true),
+                                       ("[a-c]x", "bx", true),
+                                       ("[!a-c]x", "bx", false),
+                                       ("[^a-c]x", "dx", true),
+                                       ("[]]", "]", true),
+                                       ("[a-]", "-", true),
+                                       ("[unclosed", "[unclosed", true),
+                                       ("*.*", "noext", false),
+                                       ("a*b*c", "aXbYbZc", true)] {
+        assert_eq!(Glob::new(pattern).matches(path), matches, "{} ~ {}", pattern, path);
+    }
+}
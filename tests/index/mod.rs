@@ -0,0 +1,111 @@
+use hrx::{HrxEntryDataRef, HrxArchiveRef, HrxEntryRef, HrxIndex, HrxError, HrxPath};
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[test]
+fn build() {
+    let arch_str = "<====> file1\nThe first file.\n<====>\nA comment\n<====> dir/\n\n<====> dir/file2\n<====>\nThe archive comment";
+    let index = HrxIndex::build(arch_str).unwrap();
+
+    assert_eq!(index.source(), arch_str);
+    assert_eq!(index.boundary_length().get(), 4);
+    assert_eq!(index.comment(), Some("The archive comment"));
+    assert_eq!(index.paths(), &["file1", "dir", "dir/file2"]);
+    assert_eq!(index.len(), 3);
+    assert!(!index.is_empty());
+
+    assert!(index.contains("dir"));
+    assert!(index.contains(HrxPath::from_str("dir/file2").unwrap()));
+    assert!(!index.contains("dir/file1"));
+
+    assert_eq!(index.get("file1"),
+               Some(HrxEntryRef {
+                   comment: None,
+                   data: HrxEntryDataRef::File { body: Some("The first file.") },
+               }));
+    assert_eq!(index.get("dir"),
+               Some(HrxEntryRef {
+                   comment: Some("A comment"),
+                   data: HrxEntryDataRef::Directory,
+               }));
+    assert_eq!(index.get("dir/file2"),
+               Some(HrxEntryRef {
+                   comment: None,
+                   data: HrxEntryDataRef::File { body: None },
+               }));
+    assert_eq!(index.get("file2"), None);
+}
+
+#[test]
+fn comment_only() {
+    let index = HrxIndex::build("<===>\nJust a comment\n").unwrap();
+    assert!(index.is_empty());
+    assert_eq!(index.paths(), &[] as &[&str]);
+    assert_eq!(index.comment(), Some("Just a comment\n"));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(HrxIndex::build("no boundary"), Err(HrxError::NoBoundary));
+    assert_eq!(HrxIndex::build("<===> file\n<===> file\n"), Err(HrxError::DuplicateEntry("file".to_string())));
+    assert_eq!(HrxIndex::build("<===> file\n<===> file/sub\n"),
+               Err(HrxError::FileAsDirectory("file".to_string(), "file/sub".to_string())));
+
+    let invalid = "<===> file\n<===> ../escape\n";
+    assert_eq!(HrxIndex::build(invalid).unwrap_err(), HrxArchiveRef::parse(invalid).unwrap_err());
+}
+
+#[test]
+fn same_as_parsed() {
+    for arch_str in &["<===> dir/\n\n\n<===>\ncomment\n<===> dir/file\nbody\n\n<===>\ntrailing\n",
+                      "<===> a\n\n<===> b\n",
+                      "<===>\n\n<===> b\n",
+                      "<===> a\n<====> b\n<===> b\n",
+                      "<===> a\nbody",
+                      "<===>\ncomment\n<===>\ncomment\n",
+                      "<===>\n<===> a\n",
+                      "<===> dir/\ntext\n",
+                      "<===> a/../b\n",
+                      "<===> a",
+                      "<===>\n"] {
+        match (HrxIndex::build(arch_str), HrxArchiveRef::parse(arch_str)) {
+            (Ok(index), Ok(arch)) => {
+                assert_eq!(index.comment(), arch.comment(), "{:?}", arch_str);
+                assert_eq!(index.paths(), &arch.entries().keys().cloned().collect::<Vec<_>>()[..], "{:?}", arch_str);
+                for (&path, entry) in arch.entries() {
+                    assert_eq!(index.get(path).as_ref(), Some(entry), "{:?}", arch_str);
+                }
+            }
+            (index, arch) => assert_eq!(index.unwrap_err(), arch.unwrap_err(), "{:?}", arch_str),
+        }
+    }
+}
+
+#[test]
+fn examples() {
+    for dir in &["ext/hrx/example", "ext/hrx/example/invalid"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                continue;
+            }
+
+            let mut body = String::new();
+            File::open(path).unwrap().read_to_string(&mut body).unwrap();
+
+            match (HrxIndex::build(&body), HrxArchiveRef::parse(&body)) {
+                (Ok(index), Ok(arch)) => {
+                    assert_eq!(index.comment(), arch.comment());
+                    assert_eq!(index.boundary_length(), arch.boundary_length());
+                    assert_eq!(index.paths(), &arch.entries().keys().cloned().collect::<Vec<_>>()[..]);
+                    for (&path, entry) in arch.entries() {
+                        assert_eq!(index.get(path).as_ref(), Some(entry));
+                    }
+                }
+                (index, arch) => assert_eq!(index.unwrap_err(), arch.unwrap_err()),
+            };
+        }
+    }
+}
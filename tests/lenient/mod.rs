@@ -0,0 +1,143 @@
+use hrx::{ParseOptions, LineEndings, HrxArchive, HrxWarning, Deviation, HrxError, Leniency};
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+fn options(lenient: Leniency) -> ParseOptions {
+    ParseOptions {
+        line_endings: LineEndings::Lf,
+        lenient: lenient,
+    }
+}
+
+fn deviations(warnings: &[HrxWarning]) -> Vec<(usize, usize, Deviation)> {
+    warnings.iter().map(|w| (w.position.line, w.position.column, w.deviation)).collect()
+}
+
+
+#[test]
+fn strict() {
+    for arch_str in &["\u{FEFF}<===> file\n", "\n \t\n<===> file\n", "<===>\tfile\n", "<===> dir/ \t\n"] {
+        assert!(HrxArchive::parse_with_options(arch_str, &options(Leniency::strict())).is_err(), "{:?}", arch_str);
+        assert_eq!(HrxArchive::parse_with_options(arch_str, &options(Leniency::strict())), HrxArchive::from_str(arch_str));
+    }
+}
+
+#[test]
+fn byte_order_mark() {
+    let lenient = Leniency { byte_order_mark: true, ..Leniency::strict() };
+    let (arch, warnings) = HrxArchive::parse_with_warnings("\u{FEFF}<===> file\n", &options(lenient)).unwrap();
+    assert_eq!(arch, HrxArchive::from_str("<===> file\n").unwrap());
+    assert_eq!(deviations(&warnings), vec![(1, 1, Deviation::ByteOrderMark)]);
+    assert_eq!(warnings[0].position.offset, 0);
+
+    assert!(HrxArchive::parse_with_options("\u{FEFF}\n<===> file\n", &options(lenient)).is_err());
+    assert_eq!(HrxArchive::parse_with_options("<===> file\n\u{FEFF}", &options(lenient)), HrxArchive::from_str("<===> file\n\u{FEFF}"));
+}
+
+#[test]
+fn leading_blank_lines() {
+    let lenient = Leniency { leading_blank_lines: true, ..Leniency::strict() };
+    let (arch, warnings) = HrxArchive::parse_with_warnings("\n  \n\t \n<===> file\n", &options(lenient)).unwrap();
+    assert_eq!(arch, HrxArchive::from_str("<===> file\n").unwrap());
+    assert_eq!(deviations(&warnings), vec![(1, 1, Deviation::LeadingBlankLines)]);
+
+    match HrxArchive::parse_with_options("\n  <===> file\n", &options(lenient)) {
+        Err(HrxError::Parse(err)) => assert_eq!((err.line, err.column), (2, 1)),
+        err => panic!("{:?}", err),
+    }
+}
+
+#[test]
+fn tabs_after_boundary() {
+    let lenient = Leniency { tabs_after_boundary: true, ..Leniency::strict() };
+    let (arch, warnings) = HrxArchive::parse_with_warnings("<===>\tfile\nbody\n<===> \t\tdir/\n<===>  other\n", &options(lenient)).unwrap();
+    assert_eq!(arch, HrxArchive::from_str("<===> file\nbody\n<===> dir/\n<===> other\n").unwrap());
+    assert_eq!(deviations(&warnings),
+               vec![(1, 6, Deviation::TabAfterBoundary), (3, 7, Deviation::TabAfterBoundary)]);
+
+    assert!(HrxArchive::parse_with_options("<===>\t\n", &options(lenient)).is_err());
+}
+
+#[test]
+fn trailing_whitespace_after_directory() {
+    let lenient = Leniency { trailing_whitespace_after_directory: true, ..Leniency::strict() };
+    let (arch, warnings) = HrxArchive::parse_with_warnings("<===> dir/ \t\n\n<===> dir/sub/\n<===> file \n", &options(lenient)).unwrap();
+    assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["dir", "dir/sub", "file "]);
+    assert_eq!(deviations(&warnings), vec![(1, 11, Deviation::TrailingWhitespaceAfterDirectory)]);
+
+    assert!(HrxArchive::parse_with_options("<===> dir/ \tx\n", &options(lenient)).is_err());
+}
+
+#[test]
+fn strict_meaning_kept() {
+    for arch_str in &["<===> dir/ \nbody\n", "<===> dir/ \n\nbody", "<===> dir/  \n body\n<===> other\n", "<===> dir/ \n\n\n\ttext\n<===> dir/a\n"] {
+        let (arch, warnings) = HrxArchive::parse_with_warnings(arch_str, &options(Leniency::all())).unwrap();
+        assert_eq!(Ok(arch), HrxArchive::from_str(arch_str), "{:?}", arch_str);
+        assert_eq!(warnings, vec![], "{:?}", arch_str);
+    }
+}
+
+#[test]
+fn crlf() {
+    let arch_str = "\u{FEFF}\r\n \r\n<===>\tfile\r\nbody\r\n<===> dir/ \r\n";
+    let (arch, warnings) = HrxArchive::parse_with_warnings(arch_str,
+                                                           &ParseOptions {
+                                                               line_endings: LineEndings::CrlfNormalise,
+                                                               lenient: Leniency::all(),
+                                                           })
+        .unwrap();
+    assert_eq!(arch, HrxArchive::from_str("<===> file\nbody\n<===> dir/\n").unwrap());
+    assert_eq!(deviations(&warnings),
+               vec![(1, 1, Deviation::ByteOrderMark),
+                    (1, 2, Deviation::LeadingBlankLines),
+                    (3, 6, Deviation::TabAfterBoundary),
+                    (5, 11, Deviation::TrailingWhitespaceAfterDirectory)]);
+}
+
+#[test]
+fn valid_examples_unchanged() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+
+        let (lenient, warnings) = HrxArchive::parse_with_warnings(&body, &options(Leniency::all())).unwrap();
+        assert_eq!(Ok(lenient), HrxArchive::from_str(&body), "{}", path.display());
+        assert_eq!(warnings, vec![], "{}", path.display());
+    }
+}
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(path).unwrap().read_to_string(&mut body).unwrap();
+        let arch = HrxArchive::from_str(&body).unwrap();
+
+        let entry_line = format!("<{}> ", "=".repeat(arch.boundary_length().get()));
+        let legacy = body.split('\n')
+            .map(|line| match (line.starts_with(&entry_line), line.ends_with('/')) {
+                (true, true) => line.replacen(' ', "\t", 1) + " ",
+                (true, false) => line.replacen(' ', "\t", 1),
+                (false, _) => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let legacy = format!("\u{FEFF}\n\n{}", legacy);
+        let (lenient, warnings) = HrxArchive::parse_with_warnings(&legacy, &options(Leniency::all())).unwrap();
+        assert_eq!(lenient, arch);
+        assert_eq!(warnings[..2].iter().map(|w| w.deviation).collect::<Vec<_>>(),
+                   vec![Deviation::ByteOrderMark, Deviation::LeadingBlankLines]);
+    }
+}
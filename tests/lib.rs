@@ -1,4 +1,36 @@
 extern crate hrx;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "tar")]
+extern crate tar;
+#[cfg(feature = "zip")]
+extern crate zip;
 
+mod binary;
+#[cfg(feature = "cli")]
+mod cli;
+mod diff;
 mod example;
+mod extract;
+#[cfg(feature = "serde")]
+mod format;
+mod glob;
+mod index;
+mod lenient;
+mod line_endings;
+mod merge;
+mod pack;
 mod parse;
+mod path;
+mod reader;
+#[cfg(feature = "serde")]
+mod serialisation;
+#[cfg(feature = "tar")]
+mod tarball;
+mod tree;
+mod writer;
+#[cfg(feature = "zip")]
+mod zipfile;
@@ -0,0 +1,102 @@
+use hrx::{HrxEntryData, ParseOptions, LineEndings, HrxArchive, HrxError, Leniency};
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+fn options(line_endings: LineEndings) -> ParseOptions {
+    ParseOptions {
+        line_endings: line_endings,
+        lenient: Leniency::strict(),
+    }
+}
+
+fn serialise(arch: &HrxArchive, line_endings: LineEndings) -> String {
+    let mut out = vec![];
+    arch.serialise_with_line_endings(&mut out, line_endings).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+
+#[test]
+fn lf() {
+    let arch_str = "<===> file\r\nbody\r\n<===> dir/\r\n";
+
+    assert_eq!(HrxArchive::parse_with_options(arch_str, &options(LineEndings::Lf)), HrxArchive::from_str(arch_str));
+    match HrxArchive::parse_with_options(arch_str, &options(LineEndings::Lf)) {
+        Err(HrxError::Parse(err)) => assert_eq!((err.line, err.column), (1, 11)),
+        err => panic!("{:?}", err),
+    }
+}
+
+#[test]
+fn crlf() {
+    let arch_str = "<===>\r\nA comment\r\n<===> file\r\nline 1\r\nline 2\nline 3\r\n\r\n<===> dir/\r\n\r\n<===> empty\r\n<===>\r\nThe end\r\n";
+
+    let preserved = HrxArchive::parse_with_options(arch_str, &options(LineEndings::CrlfPreserve)).unwrap();
+    assert_eq!(preserved.entries["file"].comment, Some("A comment".to_string()));
+    assert_eq!(preserved.entries["file"].data, HrxEntryData::File { body: Some("line 1\r\nline 2\nline 3\r\n".to_string()) });
+    assert_eq!(preserved.entries["dir"].data, HrxEntryData::Directory);
+    assert_eq!(preserved.entries["empty"].data, HrxEntryData::File { body: None });
+    assert_eq!(preserved.comment, Some("The end\r\n".to_string()));
+
+    let normalised = HrxArchive::parse_with_options(arch_str, &options(LineEndings::CrlfNormalise)).unwrap();
+    assert_eq!(normalised.entries["file"].data, HrxEntryData::File { body: Some("line 1\nline 2\nline 3\n".to_string()) });
+    assert_eq!(normalised.comment, Some("The end\n".to_string()));
+
+    assert_eq!(serialise(&preserved, LineEndings::CrlfPreserve),
+               "<===>\r\nA comment\r\n<===> file\r\nline 1\r\nline 2\nline 3\r\n\r\n<===> dir/\r\n<===> empty\r\n<===>\r\nThe end\r\n");
+    assert_eq!(serialise(&normalised, LineEndings::CrlfNormalise),
+               "<===>\r\nA comment\r\n<===> file\r\nline 1\r\nline 2\r\nline 3\r\n\r\n<===> dir/\r\n<===> empty\r\n<===>\r\nThe end\r\n");
+    assert_eq!(serialise(&normalised, LineEndings::Lf),
+               "<===>\nA comment\n<===> file\nline 1\nline 2\nline 3\n\n<===> dir/\n<===> empty\n<===>\nThe end\n");
+}
+
+#[test]
+fn crlf_errors() {
+    match HrxArchive::parse_with_options("<===> file\r\n<===> fi\rle\r\n", &options(LineEndings::CrlfPreserve)) {
+        Err(HrxError::Parse(err)) => {
+            assert_eq!((err.line, err.column), (2, 9));
+            assert!(err.expected.contains("\r\n"));
+            assert!(err.expected.contains("\n"));
+        }
+        err => panic!("{:?}", err),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut arch = HrxArchive::from_str("<===> file\nline 1\nline 2\n<===>\nA comment\n<===> cr\nends in a CR\r").unwrap();
+    arch.entries.insert("crlf".parse().unwrap(), HrxArchive::from_str("<===> crlf\nDOS\r\n\r\n").unwrap().entries["crlf"].clone());
+
+    for &line_endings in &[LineEndings::Lf, LineEndings::CrlfPreserve, LineEndings::CrlfNormalise] {
+        let out = serialise(&arch, line_endings);
+        let parsed = HrxArchive::parse_with_options(&out, &options(line_endings)).unwrap();
+        assert_eq!(parsed, arch, "{:?}", line_endings);
+        assert_eq!(serialise(&parsed, line_endings), out, "{:?}", line_endings);
+    }
+}
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(path).unwrap().read_to_string(&mut body).unwrap();
+        let crlf_body = body.replace('\n', "\r\n");
+
+        let arch = HrxArchive::from_str(&body).unwrap();
+        assert_eq!(HrxArchive::parse_with_options(&body, &options(LineEndings::CrlfPreserve)).as_ref(), Ok(&arch));
+
+        let normalised = HrxArchive::parse_with_options(&crlf_body, &options(LineEndings::CrlfNormalise)).unwrap();
+        assert_eq!(normalised, arch);
+        assert_eq!(serialise(&normalised, LineEndings::CrlfNormalise), serialise(&arch, LineEndings::Lf).replace('\n', "\r\n"));
+
+        let preserved = HrxArchive::parse_with_options(&crlf_body, &options(LineEndings::CrlfPreserve)).unwrap();
+        assert_eq!(serialise(&preserved, LineEndings::CrlfPreserve), serialise(&arch, LineEndings::Lf).replace('\n', "\r\n"));
+    }
+}
@@ -0,0 +1,134 @@
+use hrx::{MergeOptions, HrxArchive, merge_with_options, merge};
+use std::num::NonZeroUsize;
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+fn examples() -> Vec<HrxArchive> {
+    let mut ret = vec![];
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        ret.push(HrxArchive::from_str(&body).unwrap());
+    }
+    ret
+}
+
+fn serialise(arch: &HrxArchive) -> String {
+    let mut out = vec![];
+    arch.serialise(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+
+#[test]
+fn one_sided() {
+    let empty = HrxArchive::new(NonZeroUsize::new(5).unwrap());
+    for arch in examples() {
+        for &(base, ours, theirs) in &[(&arch, &arch, &arch), (&empty, &empty, &arch), (&empty, &arch, &empty), (&empty, &arch, &arch)] {
+            let merged = merge(base, ours, theirs);
+            assert!(merged.is_clean());
+            assert_eq!(merged.archive.comment, arch.comment);
+            assert_eq!(merged.archive.entries, arch.entries);
+        }
+
+        let merged = merge(&arch, &arch, &empty);
+        assert!(merged.is_clean());
+        assert!(merged.archive.comment.is_none());
+        assert!(merged.archive.entries.is_empty());
+    }
+}
+
+#[test]
+fn disjoint_lines() {
+    let base = HrxArchive::from_str("<===>\nhead\n<===> file\n1\n2\n3\n4\n5\n").unwrap();
+    let ours = HrxArchive::from_str("<===>\nhead\n<===> file\n0\n1\n2\n3\n4\n5\n").unwrap();
+    let theirs = HrxArchive::from_str("<===>\nheader\n<===> file\n1\n2\n3\n4\nfive\n").unwrap();
+
+    let merged = merge(&base, &ours, &theirs);
+    assert!(merged.is_clean());
+    assert_eq!(serialise(&merged.archive), "<===>\nheader\n<===> file\n0\n1\n2\n3\n4\nfive\n");
+}
+
+#[test]
+fn conflicting_comment() {
+    let base = HrxArchive::from_str("<===> file\n<===>\nbase\n").unwrap();
+    let ours = HrxArchive::from_str("<===> file\n<===>\nours\n").unwrap();
+    let theirs = HrxArchive::from_str("<===> file\n<===>\ntheirs\n").unwrap();
+
+    let merged = merge_with_options(&base,
+                                    &ours,
+                                    &theirs,
+                                    &MergeOptions {
+                                        marker_size: 3,
+                                        ours_label: "HEAD".to_string(),
+                                        theirs_label: "feature".to_string(),
+                                    });
+    assert!(merged.comment_conflict);
+    assert!(merged.conflicts.is_empty());
+    assert_eq!(merged.archive.comment.as_deref(), Some("<<< HEAD\nours\n===\ntheirs\n>>> feature\n"));
+}
+
+#[test]
+fn removed_and_modified() {
+    let base = HrxArchive::from_str("<===> file\nbase\n<===> other\n").unwrap();
+    let ours = HrxArchive::from_str("<===> other\n").unwrap();
+    let theirs = HrxArchive::from_str("<===> file\ntheirs\n<===> other\n").unwrap();
+
+    let merged = merge(&base, &ours, &theirs);
+    assert_eq!(merged.conflicts.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["file"]);
+    let conflict = &merged.conflicts.values().next().unwrap();
+    assert_eq!(conflict.base, base.entries.values().next().cloned());
+    assert_eq!(conflict.ours, None);
+    assert_eq!(conflict.theirs, theirs.entries.values().next().cloned());
+
+    assert_eq!(serialise(&merged.archive), "<===> other\n<===> file\ntheirs");
+}
+
+#[test]
+fn file_and_directory() {
+    let base = HrxArchive::from_str("<===> other\n").unwrap();
+    let ours = HrxArchive::from_str("<===> other\n<===> dir\n").unwrap();
+    let theirs = HrxArchive::from_str("<===> other\n<===> dir/file\n").unwrap();
+
+    let merged = merge(&base, &ours, &theirs);
+    assert_eq!(merged.conflicts.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["dir/file"]);
+    assert_eq!(serialise(&merged.archive), "<===> other\n<===> dir\n");
+    assert!(merged.archive.validate_content().is_ok());
+}
+
+#[test]
+fn boundary_lengthened() {
+    let base = HrxArchive::from_str("<===> file\n1\n").unwrap();
+    let ours = HrxArchive::from_str("<===> other\n<===> file\n1\n").unwrap();
+    let theirs = HrxArchive::from_str("<====> file\n<===> 2\n").unwrap();
+
+    let merged = merge(&base, &ours, &theirs);
+    assert!(merged.is_clean());
+    assert_eq!(merged.archive.boundary_length(), NonZeroUsize::new(4).unwrap());
+    assert_eq!(serialise(&merged.archive), "<====> other\n<====> file\n<===> 2\n");
+    assert!(merged.archive.validate_content().is_ok());
+}
+
+#[test]
+fn nothing_in_common() {
+    let body = |prefix: &str| (0..4000).map(|i| format!("{}{}\n", prefix, i)).collect::<String>();
+    let base = HrxArchive::from_str(&format!("<===> file\n{}", body("base "))).unwrap();
+    let ours = HrxArchive::from_str(&format!("<===> file\n{}", body("ours "))).unwrap();
+    let theirs = HrxArchive::from_str(&format!("<===> file\n{}", body("theirs "))).unwrap();
+
+    let merged = merge(&base, &ours, &base);
+    assert!(merged.is_clean());
+    assert_eq!(merged.archive.entries, ours.entries);
+
+    let merged = merge(&base, &ours, &theirs);
+    assert_eq!(merged.conflicts.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["file"]);
+    assert_eq!(serialise(&merged.archive),
+               format!("<===> file\n<<<<<<< ours\n{}=======\n{}>>>>>>> theirs\n", body("ours "), body("theirs ")));
+}
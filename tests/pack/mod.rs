@@ -0,0 +1,188 @@
+use hrx::{UnrepresentableReason, UnrepresentablePolicy, SymlinkPolicy, ExtractOptions, PackOptions, HrxArchive, PackOrder, HrxError};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::env::temp_dir;
+use std::fs;
+
+
+fn source(name: &str) -> PathBuf {
+    let root = temp_dir().join("hrx.rs-test").join("pack").join(name);
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+fn paths(arch: &HrxArchive) -> Vec<&str> {
+    arch.entries.keys().map(|k| k.as_ref()).collect()
+}
+
+
+#[test]
+fn round_trip() {
+    let arch = HrxArchive::from_str(r#"<====> dir/
+<====> input.scss
+ul {
+  li {
+    list-style: none;
+  }
+}
+
+<====> out/input.css
+ul li {
+  list-style: none;
+}
+<====> out/nested.hrx
+<===> inner.txt
+The boundary of this nested archive forces the outer one to be longer.
+"#)
+        .unwrap();
+
+    let root = source("round_trip");
+    arch.extract_to(&root, &ExtractOptions::default()).unwrap();
+
+    let packed = HrxArchive::from_dir(&root, &PackOptions::default()).unwrap();
+    assert_eq!(packed.boundary_length().get(), 4);
+    assert_eq!(packed.entries, arch.entries);
+}
+
+#[test]
+fn order() {
+    let root = source("order");
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("a").join("file"), "").unwrap();
+    fs::write(root.join("b"), "").unwrap();
+
+    assert_eq!(paths(&HrxArchive::from_dir(&root, &PackOptions::default()).unwrap()), vec!["a/file", "b"]);
+    assert_eq!(paths(&HrxArchive::from_dir(&root,
+                                           &PackOptions {
+                                               order: PackOrder::FilesFirst,
+                                               ..PackOptions::default()
+                                           })
+                         .unwrap()),
+               vec!["b", "a/file"]);
+}
+
+#[test]
+fn globs() {
+    let root = source("globs");
+    fs::create_dir_all(root.join("out").join("deep")).unwrap();
+    fs::create_dir_all(root.join("target")).unwrap();
+    fs::write(root.join("input.scss"), "").unwrap();
+    fs::write(root.join("out").join("input.css"), "").unwrap();
+    fs::write(root.join("out").join("deep").join("input.css"), "").unwrap();
+    fs::write(root.join("out").join("input.css.map"), "").unwrap();
+    fs::write(root.join("target").join("output.css"), "").unwrap();
+
+    assert_eq!(paths(&HrxArchive::from_dir(&root,
+                                           &PackOptions {
+                                               include: vec!["**/*.css".to_string()],
+                                               exclude: vec!["target".to_string()],
+                                               ..PackOptions::default()
+                                           })
+                         .unwrap()),
+               vec!["out/deep/input.css", "out/input.css"]);
+    assert_eq!(paths(&HrxArchive::from_dir(&root,
+                                           &PackOptions {
+                                               exclude: vec!["out/**/*.css".to_string(), "*.[ms]*".to_string()],
+                                               ..PackOptions::default()
+                                           })
+                         .unwrap()),
+               vec!["out/deep", "out/input.css.map", "target/output.css"]);
+}
+
+#[test]
+fn non_utf8_contents() {
+    let root = source("non_utf8_contents");
+    fs::write(root.join("binary"), b"\xFF\xFE").unwrap();
+    fs::write(root.join("text"), "text").unwrap();
+
+    assert_eq!(HrxArchive::from_dir(&root, &PackOptions::default()).unwrap_err().unwrap(),
+               HrxError::Unrepresentable(root.join("binary").display().to_string(), UnrepresentableReason::NonUtf8Contents));
+    let (arch, skipped) = HrxArchive::from_dir_with_skipped(&root,
+                                                            &PackOptions {
+                                                                unrepresentable: UnrepresentablePolicy::Skip,
+                                                                ..PackOptions::default()
+                                                            })
+        .unwrap();
+    assert_eq!(paths(&arch), vec!["text"]);
+    assert_eq!(skipped, vec![(root.join("binary").display().to_string(), UnrepresentableReason::NonUtf8Contents)]);
+}
+
+#[cfg(unix)]
+#[test]
+fn invalid_name() {
+    let root = source("invalid_name");
+    fs::write(root.join("C:file"), "").unwrap();
+    fs::write(root.join("back\\slash"), "").unwrap();
+
+    assert_eq!(HrxArchive::from_dir(&root, &PackOptions::default()).unwrap_err().unwrap(),
+               HrxError::Unrepresentable(root.join("C:file").display().to_string(), UnrepresentableReason::InvalidPath));
+    let (arch, skipped) = HrxArchive::from_dir_with_skipped(&root,
+                                                            &PackOptions {
+                                                                unrepresentable: UnrepresentablePolicy::Skip,
+                                                                ..PackOptions::default()
+                                                            })
+        .unwrap();
+    assert!(arch.entries.is_empty());
+    assert_eq!(skipped,
+               vec![(root.join("C:file").display().to_string(), UnrepresentableReason::InvalidPath),
+                    (root.join("back\\slash").display().to_string(), UnrepresentableReason::InvalidPath)]);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let root = source("symlinks");
+    fs::create_dir_all(root.join("dir")).unwrap();
+    fs::write(root.join("dir").join("file"), "contents").unwrap();
+    symlink(root.join("dir"), root.join("link")).unwrap();
+    symlink(&root, root.join("dir").join("loop")).unwrap();
+
+    assert_eq!(HrxArchive::from_dir(&root, &PackOptions::default()).unwrap_err().unwrap(),
+               HrxError::Unrepresentable(root.join("dir").join("loop").display().to_string(), UnrepresentableReason::Symlink));
+    assert_eq!(paths(&HrxArchive::from_dir(&root,
+                                           &PackOptions {
+                                               symlinks: SymlinkPolicy::Skip,
+                                               ..PackOptions::default()
+                                           })
+                         .unwrap()),
+               vec!["dir/file"]);
+    assert!(HrxArchive::from_dir(&root,
+                                 &PackOptions {
+                                     symlinks: SymlinkPolicy::Follow,
+                                     ..PackOptions::default()
+                                 })
+        .unwrap_err()
+        .is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn dangling_symlink() {
+    use std::os::unix::fs::symlink;
+
+    let root = source("dangling_symlink");
+    fs::write(root.join("file"), "contents").unwrap();
+    symlink(root.join("nonexistent"), root.join("dangling")).unwrap();
+
+    assert_eq!(HrxArchive::from_dir(&root,
+                                    &PackOptions {
+                                        symlinks: SymlinkPolicy::Follow,
+                                        ..PackOptions::default()
+                                    })
+                   .unwrap_err()
+                   .unwrap(),
+               HrxError::Unrepresentable(root.join("dangling").display().to_string(), UnrepresentableReason::Symlink));
+
+    let (arch, skipped) = HrxArchive::from_dir_with_skipped(&root,
+                                                            &PackOptions {
+                                                                symlinks: SymlinkPolicy::Follow,
+                                                                unrepresentable: UnrepresentablePolicy::Skip,
+                                                                ..PackOptions::default()
+                                                            })
+        .unwrap();
+    assert_eq!(paths(&arch), vec!["file"]);
+    assert_eq!(skipped, vec![(root.join("dangling").display().to_string(), UnrepresentableReason::Symlink)]);
+}
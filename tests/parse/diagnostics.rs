@@ -0,0 +1,95 @@
+use hrx::{HrxDiagnostic, HrxEntryData, HrxPosition, HrxArchive, HrxError};
+use hrx::parse::ParseError;
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+
+        assert_eq!(HrxArchive::parse_with_diagnostics(&body), Ok((HrxArchive::from_str(&body).unwrap(), vec![])));
+    }
+}
+
+#[test]
+fn invalid_examples() {
+    for entry in fs::read_dir("ext/hrx/example/invalid").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+
+        match HrxArchive::from_str(&body) {
+            Ok(_) => assert_eq!(HrxArchive::parse_with_diagnostics(&body).unwrap().1, vec![]),
+            Err(HrxError::NoBoundary) => assert_eq!(HrxArchive::parse_with_diagnostics(&body), Err(HrxError::NoBoundary)),
+            Err(err) => assert_eq!(HrxArchive::parse_with_diagnostics(&body).unwrap().1[0].error, err, "{}", path.display()),
+        }
+    }
+}
+
+#[test]
+fn consecutive_comments() {
+    let (arch, diagnostics) = HrxArchive::parse_with_diagnostics("<===>\ncomment 1\n<===>\ncomment 2\n<===> file\nbody\n").unwrap();
+
+    assert_eq!(arch.entries.len(), 1);
+    assert_eq!(arch.entries["file"].comment, None);
+    assert_eq!(arch.entries["file"].data, HrxEntryData::File { body: Some("body\n".to_string()) });
+    assert_eq!(diagnostics,
+               vec![HrxDiagnostic {
+                        position: HrxPosition {
+                            line: 3,
+                            column: 6,
+                            offset: 21,
+                        },
+                        error: ParseError {
+                                line: 3,
+                                column: 6,
+                                offset: 21,
+                                expected: vec![" "].into_iter().collect(),
+                            }
+                            .into(),
+                    }]);
+}
+
+#[test]
+fn leading_junk() {
+    let (arch, diagnostics) = HrxArchive::parse_with_diagnostics("junk\nmore junk\n<==> file\n").unwrap();
+
+    assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["file"]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].position.to_string(), "1:1");
+}
+
+#[test]
+fn everything() {
+    let arch_str = "<===> dir/sub
+<===> dir/sub/file
+<===> bad\\path
+<===> dir/
+contents
+<===> dir/sub/
+<===> good
+Good contents
+<===>
+Archive comment";
+    let (arch, diagnostics) = HrxArchive::parse_with_diagnostics(arch_str).unwrap();
+
+    assert_eq!(arch.entries.keys().map(|p| p.as_ref()).collect::<Vec<_>>(), vec!["dir/sub", "good"]);
+    assert_eq!(arch.comment, Some("Archive comment".to_string()));
+    assert_eq!(diagnostics.iter().map(|d| d.position.to_string()).collect::<Vec<_>>(),
+               vec!["2:1", "3:10", "5:1", "6:1"]);
+    assert_eq!(diagnostics[0].error, HrxError::FileAsDirectory("dir/sub".to_string(), "dir/sub/file".to_string()));
+    assert_eq!(diagnostics[3].error, HrxError::DuplicateEntry("dir/sub".to_string()));
+}
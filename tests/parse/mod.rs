@@ -1,4 +1,6 @@
+mod diagnostics;
 mod invalid;
+mod spans;
 
 use hrx::{HrxEntryData, HrxArchive, HrxEntry};
 use std::num::NonZeroUsize;
@@ -313,3 +315,27 @@ A comment may appear at the end of a file.
 
     assert_eq!(HrxArchive::from_str(arch_str), Ok(arch));
 }
+
+#[test]
+fn other_boundaries_in_body() {
+    let arch_str = r#"<===> file
+<====> not a boundary
+<==> neither
+ <===> nor this
+<===> other
+"#;
+
+    let mut arch = HrxArchive::new(NonZeroUsize::new(3).unwrap());
+    arch.entries.insert("file".parse().unwrap(),
+                        HrxEntry {
+                            comment: None,
+                            data: HrxEntryData::File { body: Some("<====> not a boundary\n<==> neither\n <===> nor this".to_string()) },
+                        });
+    arch.entries.insert("other".parse().unwrap(),
+                        HrxEntry {
+                            comment: None,
+                            data: HrxEntryData::File { body: None },
+                        });
+
+    assert_eq!(HrxArchive::from_str(arch_str), Ok(arch));
+}
@@ -0,0 +1,73 @@
+use hrx::{HrxArchiveRef, HrxEntryDataRef, HrxArchive, HrxPosition, HrxSpan};
+use std::fs::{self, File};
+use std::io::Read;
+
+
+fn span(start: (usize, usize, usize), end: (usize, usize, usize)) -> HrxSpan {
+    HrxSpan {
+        start: HrxPosition {
+            line: start.0,
+            column: start.1,
+            offset: start.2,
+        },
+        end: HrxPosition {
+            line: end.0,
+            column: end.1,
+            offset: end.2,
+        },
+    }
+}
+
+
+#[test]
+fn all_parts() {
+    let arch_str = "<==>\nComment\nover two lines\n<==>   хэнло/file\nbody\n\n<==> dir/\n\n<==>\nArchive comment";
+    let (_, spans) = HrxArchive::parse_with_spans(arch_str).unwrap();
+
+    let file = &spans.entries["хэнло/file"];
+    assert_eq!(file.comment, Some(span((2, 1, 5), (3, 15, 27))));
+    assert_eq!(file.boundary_line, span((4, 1, 28), (4, 18, 50)));
+    assert_eq!(file.path, span((4, 8, 35), (4, 18, 50)));
+    assert_eq!(file.body, Some(span((5, 1, 51), (6, 1, 56))));
+
+    let dir = &spans.entries["dir"];
+    assert_eq!(dir.comment, None);
+    assert_eq!(dir.boundary_line, span((7, 1, 57), (7, 10, 66)));
+    assert_eq!(dir.path, span((7, 6, 62), (7, 9, 65)));
+    assert_eq!(dir.body, None);
+
+    assert_eq!(spans.comment, Some(span((10, 1, 73), (10, 16, 88))));
+}
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(path).unwrap().read_to_string(&mut body).unwrap();
+
+        let arch = HrxArchiveRef::parse(&body).unwrap();
+        let spans = arch.spans();
+        assert_eq!(arch.comment_span(), spans.comment);
+        assert_eq!(arch.comment(), spans.comment.map(|c| &body[c.start.offset..c.end.offset]));
+
+        for ((&path, entry), (_, entry_spans)) in arch.entries().iter().zip(spans.entries.iter()) {
+            assert_eq!(arch.entry_spans(path).as_ref(), Some(entry_spans));
+
+            assert_eq!(&body[entry_spans.path.start.offset..entry_spans.path.end.offset], path);
+            assert!(body[entry_spans.boundary_line.start.offset..entry_spans.boundary_line.end.offset].starts_with("<="));
+            assert_eq!(entry.comment, entry_spans.comment.map(|c| &body[c.start.offset..c.end.offset]));
+            match entry.data {
+                HrxEntryDataRef::File { body: file_body } => assert_eq!(file_body, entry_spans.body.map(|b| &body[b.start.offset..b.end.offset])),
+                HrxEntryDataRef::Binary { .. } => unreachable!(),
+                HrxEntryDataRef::Directory => assert_eq!(entry_spans.body, None),
+            }
+
+            assert_eq!(HrxPosition::locate(&body, entry_spans.path.start.offset), entry_spans.path.start);
+        }
+    }
+}
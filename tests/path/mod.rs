@@ -0,0 +1,147 @@
+use hrx::util::invalid_windows_path_component;
+use hrx::{InvalidPathReason, HrxArchive, HrxError, HrxPath};
+use std::fs::{self, File};
+use std::convert::TryFrom;
+use std::path::Path;
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let arch = HrxArchive::from_str(&body).unwrap();
+
+        for p in arch.entries.keys() {
+            let components = p.components().collect::<Vec<_>>();
+            assert_eq!(components.len(), p.depth());
+            assert_eq!(&HrxPath::from_components(&components).unwrap(), p);
+            assert_eq!(p.file_name(), *components.last().unwrap());
+
+            match p.parent() {
+                Some(parent) => {
+                    assert_eq!(parent.depth() + 1, p.depth());
+                    assert!(p.starts_with(&parent));
+                    assert_eq!(&parent.join(p.file_name()).unwrap(), p);
+                    assert_eq!(p.strip_prefix(&parent).unwrap().as_ref(), p.file_name());
+                }
+                None => assert_eq!(p.depth(), 1),
+            }
+
+            assert_eq!(&HrxPath::try_from(p.to_path_buf().unwrap().as_path()).unwrap(), p);
+        }
+    }
+}
+
+#[test]
+fn invalid_components() {
+    for components in &[&["dir", ""][..], &["."], &["dir", ".."], &["a:b"], &["a\\b"], &["a\nb"], &["dir", "a/b"]] {
+        assert!(HrxPath::from_components(*components).is_err(), "{:?}", components);
+    }
+
+    let base = HrxPath::from_str("dir").unwrap();
+    for joined in &["", "/abs", "trailing/", "a//b", "a/./b"] {
+        assert!(base.join(joined).is_err(), "{:?}", joined);
+    }
+}
+
+#[test]
+fn prefixes() {
+    let path = HrxPath::from_str("a/bc/d").unwrap();
+    for &(base, starts) in &[("a", true), ("a/bc", true), ("a/bc/d", true), ("a/b", false), ("a/bc/d/e", false), ("b", false)] {
+        let base = HrxPath::from_str(base).unwrap();
+        assert_eq!(path.starts_with(&base), starts, "{}", base);
+        assert_eq!(path.strip_prefix(&base).is_some(), starts && base != path, "{}", base);
+    }
+}
+
+#[test]
+fn from_fs_path() {
+    assert_eq!(HrxPath::try_from(Path::new("a//b/./c")).unwrap().as_ref(), "a/b/c");
+
+    for &(path, component, reason) in &[("", "", InvalidPathReason::Empty),
+                                        ("/abs/file", "/", InvalidPathReason::RootDir),
+                                        ("./file", ".", InvalidPathReason::CurDir),
+                                        ("dir/../file", "..", InvalidPathReason::ParentDir),
+                                        ("dir/a:b/file", "a:b", InvalidPathReason::InvalidCharacter(':')),
+                                        ("dir/tab\tfile", "tab\tfile", InvalidPathReason::InvalidCharacter('\t'))] {
+        assert_eq!(HrxPath::try_from(Path::new(path)),
+                   Err(HrxError::InvalidPathComponent(path.to_string(), component.to_string(), reason)));
+    }
+}
+
+#[test]
+fn windows_components() {
+    for component in &["input.scss", ".hidden", "a.b.c", "console", "com10", "lpt", "CON_", "\u{1F600}"] {
+        assert_eq!(invalid_windows_path_component(component), None, "{:?}", component);
+    }
+
+    for &(component, reason) in &[("a<b", InvalidPathReason::InvalidCharacter('<')),
+                                  ("a>b", InvalidPathReason::InvalidCharacter('>')),
+                                  ("\"quoted\"", InvalidPathReason::InvalidCharacter('"')),
+                                  ("a|b", InvalidPathReason::InvalidCharacter('|')),
+                                  ("what?", InvalidPathReason::InvalidCharacter('?')),
+                                  ("*.txt", InvalidPathReason::InvalidCharacter('*')),
+                                  ("file.", InvalidPathReason::TrailingCharacter('.')),
+                                  ("...", InvalidPathReason::TrailingCharacter('.')),
+                                  ("file ", InvalidPathReason::TrailingCharacter(' ')),
+                                  ("CON", InvalidPathReason::ReservedName),
+                                  ("nul", InvalidPathReason::ReservedName),
+                                  ("Aux.txt", InvalidPathReason::ReservedName),
+                                  ("com1.tar.gz", InvalidPathReason::ReservedName),
+                                  ("LPT9 .log", InvalidPathReason::ReservedName)] {
+        assert_eq!(invalid_windows_path_component(component), Some(reason), "{:?}", component);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn to_fs_path_unix() {
+    for path in &["dir/what?", "dir/file.", "CON", "a<b>/nul.txt"] {
+        let path = HrxPath::from_str(path).unwrap();
+        assert_eq!(path.to_path_buf().unwrap(), Path::new(path.as_ref()));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn from_fs_path_unix() {
+    use std::os::unix::ffi::OsStrExt;
+    use std::ffi::OsStr;
+
+    assert_eq!(HrxPath::try_from(Path::new("dir/back\\slash")),
+               Err(HrxError::InvalidPathComponent("dir/back\\slash".to_string(), "back\\slash".to_string(), InvalidPathReason::InvalidCharacter('\\'))));
+    assert_eq!(HrxPath::try_from(Path::new(OsStr::from_bytes(b"dir/\xFF"))),
+               Err(HrxError::InvalidPathComponent("dir/\u{FFFD}".to_string(), "\u{FFFD}".to_string(), InvalidPathReason::NonUtf8)));
+}
+
+#[cfg(windows)]
+#[test]
+fn from_fs_path_windows() {
+    assert_eq!(HrxPath::try_from(Path::new("dir\\sub\\file")).unwrap().as_ref(), "dir/sub/file");
+    assert_eq!(HrxPath::try_from(Path::new("C:file")),
+               Err(HrxError::InvalidPathComponent("C:file".to_string(), "C:".to_string(), InvalidPathReason::Prefix)));
+}
+
+#[cfg(windows)]
+#[test]
+fn to_fs_path_windows() {
+    assert_eq!(HrxPath::from_str("dir/sub/file").unwrap().to_path_buf(), Ok(Path::new("dir\\sub\\file").to_path_buf()));
+
+    for &(path, component, reason) in &[("dir/what?", "what?", InvalidPathReason::InvalidCharacter('?')),
+                                        ("a<b>/file", "a<b>", InvalidPathReason::InvalidCharacter('<')),
+                                        ("dir/file.", "file.", InvalidPathReason::TrailingCharacter('.')),
+                                        ("dir /file", "dir ", InvalidPathReason::TrailingCharacter(' ')),
+                                        ("CON", "CON", InvalidPathReason::ReservedName),
+                                        ("dir/nul.txt", "nul.txt", InvalidPathReason::ReservedName)] {
+        assert_eq!(HrxPath::from_str(path).unwrap().to_path_buf(),
+                   Err(HrxError::InvalidPathComponent(path.to_string(), component.to_string(), reason)));
+    }
+}
@@ -0,0 +1,77 @@
+use hrx::{HrxReader, HrxArchive, HrxError};
+use std::io::{BufReader, ErrorKind};
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+fn read_archive(arch_str: &str, buffer_size: usize) -> Result<HrxArchive, HrxError> {
+    let mut reader = HrxReader::new(BufReader::with_capacity(buffer_size, arch_str.as_bytes()));
+
+    let mut entries = vec![];
+    for entry in reader.by_ref() {
+        entries.push(entry.map_err(|e| e.unwrap())?);
+    }
+
+    let mut arch = HrxArchive::new(reader.boundary_length().unwrap());
+    arch.comment = reader.comment().map(str::to_string);
+    arch.entries.extend(entries);
+    Ok(arch)
+}
+
+
+#[test]
+fn examples() {
+    for dir in &["ext/hrx/example", "ext/hrx/example/invalid"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                continue;
+            }
+
+            let mut body = String::new();
+            File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+
+            for &buffer_size in &[1, 8192] {
+                assert_eq!(read_archive(&body, buffer_size), HrxArchive::from_str(&body), "{}", path.display());
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_line_body() {
+    for arch_str in &["<===> file\n\n<===> other\n<===> third\n",
+                      "<===>\n\n<===> file\n<===> other\n",
+                      "<===> file\n\n\n<===> other\n",
+                      "<===> dir/\n\n\n<===> file\n"] {
+        assert_eq!(read_archive(arch_str, 8192), HrxArchive::from_str(arch_str));
+    }
+}
+
+#[test]
+fn errors() {
+    for arch_str in &["",
+                      "no boundary",
+                      "leading junk\n<===> file\n",
+                      "<===> file\n<===>\ncomment\n<===>\nanother comment\n<===> other\n",
+                      "<===> file\n<===> dir/\ncontents\n",
+                      "<===> file\n<===> file/sub\n",
+                      "<===> file\n<===> inva:lid\n"] {
+        assert_eq!(read_archive(arch_str, 8192), HrxArchive::from_str(arch_str));
+    }
+}
+
+#[test]
+fn stops_after_error() {
+    let mut reader = HrxReader::new("<===> file\n<===> file\n<===> other\n".as_bytes());
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn non_utf8() {
+    let mut reader = HrxReader::new(&b"<===> file\n\xFF\n"[..]);
+    assert_eq!(reader.next().unwrap().unwrap_err().unwrap_err().kind(), ErrorKind::InvalidData);
+}
@@ -0,0 +1,86 @@
+use hrx::{UnrepresentableReason, InvalidPathReason, ErroneousBodyPath, HrxArchive, HrxError, HrxPath};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[test]
+fn examples_round_trip() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let arch = HrxArchive::from_str(&body).unwrap();
+
+        let json = serde_json::to_string(&arch).unwrap();
+        let read: HrxArchive = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, arch);
+        assert_eq!(read.boundary_length(), arch.boundary_length());
+        assert_eq!(read.entries.keys().collect::<Vec<_>>(), arch.entries.keys().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn format() {
+    let arch = HrxArchive::from_str("<====> z.txt\nlast\n<====>\nnested\n<====> a/\n<====>\nthe end").unwrap();
+
+    assert_eq!(serde_json::to_string(&arch).unwrap(),
+               r#"{"comment":"the end","entries":{"z.txt":{"comment":null,"data":{"File":{"body":"last"}}},"a":{"comment":"nested","data":"Directory"}},"boundary_length":4}"#);
+}
+
+#[test]
+fn invalid_path() {
+    assert_eq!(serde_json::from_str::<HrxPath>(r#""dir/file.txt""#).unwrap(), HrxPath::from_str("dir/file.txt").unwrap());
+    for path in &[r#""""#, r#""dir//file.txt""#, r#""../file.txt""#, r#""back\\slash""#, r#""/absolute""#] {
+        assert!(serde_json::from_str::<HrxPath>(path).is_err(), "{}", path);
+    }
+}
+
+#[test]
+fn invalid_archive() {
+    for json in &[r#"{"comment":null,"entries":{"file":{"comment":null,"data":{"File":{"body":null}}},"file/nested":{"comment":null,"data":"Directory"}},"boundary_length":3}"#,
+                  r#"{"comment":null,"entries":{"a/b":{"comment":null,"data":"Directory"},"a":{"comment":null,"data":"Directory"}},"boundary_length":3}"#,
+                  r#"{"comment":null,"entries":{"file":{"comment":null,"data":{"File":{"body":"\n<===> nested"}}}},"boundary_length":3}"#,
+                  r#"{"comment":"\n<===>","entries":{},"boundary_length":3}"#,
+                  r#"{"comment":null,"entries":{},"boundary_length":0}"#] {
+        assert!(serde_json::from_str::<HrxArchive>(json).is_err(), "{}", json);
+    }
+
+    assert!(serde_json::from_str::<HrxArchive>(r#"{"comment":"\n<===>","entries":{},"boundary_length":4}"#).is_ok());
+}
+
+#[test]
+fn errors() {
+    let parse = HrxArchive::from_str("<===> file\n<===>").unwrap_err();
+    for err in &[HrxError::NoBoundary,
+                 parse,
+                 HrxError::BodyContainsBoundary(vec![ErroneousBodyPath::RootComment,
+                                                     ErroneousBodyPath::EntryComment("file".to_string()),
+                                                     ErroneousBodyPath::EntryData("file".to_string())]),
+                 HrxError::DuplicateEntry("file".to_string()),
+                 HrxError::FileAsDirectory("file".to_string(), "file/nested".to_string()),
+                 HrxError::PathEscapesTarget("link/file".to_string()),
+                 HrxError::Unrepresentable("fifo".to_string(), UnrepresentableReason::SpecialFile),
+                 HrxError::InvalidPathComponent("a/:/b".to_string(), ":".to_string(), InvalidPathReason::InvalidCharacter(':'))] {
+        let json = serde_json::to_string(err).unwrap();
+        assert_eq!(&serde_json::from_str::<HrxError>(&json).unwrap(), err);
+        assert_eq!(serde_json::to_string(&serde_json::from_str::<HrxError>(&json).unwrap()).unwrap(), json);
+    }
+}
+
+#[test]
+fn parse_error_expected() {
+    let json = r#"{"Parse":{"line":1,"column":6,"offset":5,"expected":["<character>","valid UTF-8"]}}"#;
+    match serde_json::from_str::<HrxError>(json).unwrap() {
+        HrxError::Parse(err) => assert_eq!(err.expected, vec!["<character>", "valid UTF-8"].into_iter().collect::<HashSet<_>>()),
+        err => panic!("{:?}", err),
+    }
+
+    let json = r#"{"Parse":{"line":1,"column":6,"offset":5,"expected":["<character>","anything else"]}}"#;
+    assert!(serde_json::from_str::<HrxError>(json).is_err());
+}
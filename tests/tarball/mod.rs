@@ -0,0 +1,144 @@
+use hrx::{UnrepresentableReason, UnrepresentablePolicy, TarOptions, HrxArchive, HrxError};
+use tar::{EntryType, Archive, Builder, Header};
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+fn tarball(entries: &[(&str, EntryType, &[u8])]) -> Vec<u8> {
+    let mut tar = Builder::new(vec![]);
+    for &(path, tp, data) in entries {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(tp);
+        header.set_mode(0o600);
+        if tp == EntryType::Symlink || tp == EntryType::Link {
+            header.set_size(0);
+            tar.append_link(&mut header, path, String::from_utf8_lossy(data).as_ref()).unwrap();
+        } else {
+            header.set_size(data.len() as u64);
+            tar.append_data(&mut header, path, data).unwrap();
+        }
+    }
+    tar.into_inner().unwrap()
+}
+
+fn paths(arch: &HrxArchive) -> Vec<&str> {
+    arch.entries.keys().map(|k| k.as_ref()).collect()
+}
+
+fn skip() -> TarOptions {
+    TarOptions { unrepresentable: UnrepresentablePolicy::Skip, ..TarOptions::default() }
+}
+
+
+#[test]
+fn examples_round_trip() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let mut arch = HrxArchive::from_str(&body).unwrap();
+
+        let mut tar = vec![];
+        arch.to_tar(&mut tar).unwrap();
+
+        let mut again = vec![];
+        arch.to_tar(&mut again).unwrap();
+        assert_eq!(tar, again);
+
+        // Comments and redundant explicit directories don't survive
+        arch.comment = None;
+        for (_, entry) in arch.entries.iter_mut() {
+            entry.comment = None;
+        }
+        let dirs: Vec<_> = arch.entries.keys().filter(|p| arch.entries.keys().any(|c| c.starts_with(p) && c != *p)).cloned().collect();
+        for dir in dirs {
+            arch.entries.remove(&dir);
+        }
+
+        let read = HrxArchive::from_tar(&tar[..], &TarOptions::default()).unwrap();
+        assert_eq!(read.entries, arch.entries);
+    }
+}
+
+#[test]
+fn metadata() {
+    let arch = HrxArchive::from_str("<===> dir/file\ncontent\n<===> empty/\n").unwrap();
+    let mut tar = vec![];
+    arch.to_tar(&mut tar).unwrap();
+
+    let mut tar = Archive::new(&tar[..]);
+    let entries: Vec<_> = tar.entries()
+        .unwrap()
+        .map(|e| {
+            let e = e.unwrap();
+            let hdr = e.header();
+            (e.path().unwrap().to_string_lossy().into_owned(), hdr.entry_type(), hdr.mode().unwrap(), hdr.mtime().unwrap(), hdr.uid().unwrap(), hdr.size().unwrap())
+        })
+        .collect();
+    assert_eq!(entries,
+               vec![("dir".to_string(), EntryType::Directory, 0o755, 0, 0, 0),
+                    ("dir/file".to_string(), EntryType::Regular, 0o644, 0, 0, 7),
+                    ("empty".to_string(), EntryType::Directory, 0o755, 0, 0, 0)]);
+}
+
+#[test]
+fn relative_to_current() {
+    let tar = tarball(&[("./", EntryType::Directory, b""),
+                        ("./dir/", EntryType::Directory, b""),
+                        ("./dir/file", EntryType::Regular, b"content\n"),
+                        ("./empty/", EntryType::Directory, b""),
+                        ("./file", EntryType::Regular, b"")]);
+
+    let arch = HrxArchive::from_tar(&tar[..], &TarOptions::default()).unwrap();
+    assert_eq!(paths(&arch), vec!["dir/file", "empty", "file"]);
+    assert!(arch.is_dir("empty"));
+}
+
+#[test]
+fn hard_links() {
+    let tar = tarball(&[("file", EntryType::Regular, b"content\n"), ("link", EntryType::Link, b"file"), ("dangling", EntryType::Link, b"nonexistent")]);
+
+    assert_eq!(HrxArchive::from_tar(&tar[..], &TarOptions::default()).unwrap_err().unwrap(),
+               HrxError::Unrepresentable("dangling".to_string(), UnrepresentableReason::Symlink));
+
+    let arch = HrxArchive::from_tar(&tar[..], &skip()).unwrap();
+    assert_eq!(paths(&arch), vec!["file", "link"]);
+    assert_eq!(arch.entries.values().next(), arch.entries.values().nth(1));
+}
+
+#[test]
+fn unrepresentable() {
+    for &(entry, reason) in &[(("symlink", EntryType::Symlink, &b"file"[..]), UnrepresentableReason::Symlink),
+                              (("fifo", EntryType::Fifo, &b""[..]), UnrepresentableReason::SpecialFile),
+                              (("device", EntryType::Char, &b""[..]), UnrepresentableReason::SpecialFile),
+                              (("binary", EntryType::Regular, &b"\xFF\xFE"[..]), UnrepresentableReason::NonUtf8Contents),
+                              (("back\\slash", EntryType::Regular, &b""[..]), UnrepresentableReason::InvalidPath)] {
+        let tar = tarball(&[("file", EntryType::Regular, b""), entry]);
+
+        assert_eq!(HrxArchive::from_tar(&tar[..], &TarOptions::default()).unwrap_err().unwrap(),
+                   HrxError::Unrepresentable(entry.0.to_string(), reason));
+        let (arch, skipped) = HrxArchive::from_tar_with_skipped(&tar[..], &skip()).unwrap();
+        assert_eq!(paths(&arch), vec!["file"]);
+        assert_eq!(skipped, vec![(entry.0.to_string(), reason)]);
+    }
+}
+
+#[test]
+fn file_as_directory() {
+    let tar = tarball(&[("file", EntryType::Regular, b""), ("file/nested", EntryType::Regular, b"")]);
+
+    assert_eq!(HrxArchive::from_tar(&tar[..], &skip()).unwrap_err().unwrap(),
+               HrxError::FileAsDirectory("file".to_string(), "file/nested".to_string()));
+}
+
+#[test]
+fn boundary_length() {
+    let tar = tarball(&[("nested.hrx", EntryType::Regular, b"<===> inner\n<====>\n")]);
+
+    assert_eq!(HrxArchive::from_tar(&tar[..], &TarOptions::default()).unwrap().boundary_length().get(), 5);
+}
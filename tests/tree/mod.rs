@@ -0,0 +1,108 @@
+use hrx::{HrxEntryData, HrxWalkOrder, HrxArchive, HrxEntry};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let arch = HrxArchive::from_str(&body).unwrap();
+
+        let depth_first = arch.walk(HrxWalkOrder::DepthFirst).collect::<Vec<_>>();
+        let breadth_first = arch.walk(HrxWalkOrder::BreadthFirst).collect::<Vec<_>>();
+        assert_eq!(depth_first.iter().collect::<BTreeSet<_>>(), breadth_first.iter().collect::<BTreeSet<_>>(), "{}", path.display());
+
+        let mut expected_paths = BTreeSet::new();
+        for p in arch.entries.keys() {
+            expected_paths.insert(p.as_ref());
+            expected_paths.extend(p.as_ref().match_indices('/').map(|(i, _)| &p.as_ref()[..i]));
+        }
+        assert_eq!(depth_first.iter().map(|&(p, _)| p).collect::<BTreeSet<_>>(), expected_paths, "{}", path.display());
+
+        for (p, e) in depth_first {
+            assert_eq!(e, arch.entries.get(p), "{}: {}", path.display(), p);
+            assert_eq!(arch.is_dir(p), e.map(|e| e.data == HrxEntryData::Directory).unwrap_or(true), "{}: {}", path.display(), p);
+        }
+
+        // Depth-first order keeps each node's descendants right after it
+        let tree = arch.tree();
+        let mut expected_depth_first = vec![];
+        let mut stack = tree.children().collect::<Vec<_>>();
+        stack.reverse();
+        while let Some(node) = stack.pop() {
+            expected_depth_first.push(node.path());
+            stack.extend(node.children().rev());
+        }
+        assert_eq!(arch.walk(HrxWalkOrder::DepthFirst).map(|(p, _)| p).collect::<Vec<_>>(), expected_depth_first, "{}", path.display());
+
+        for &order in &[HrxWalkOrder::DepthFirst, HrxWalkOrder::BreadthFirst] {
+            assert_eq!(tree.walk(order).collect::<Vec<_>>(), arch.walk(order).collect::<Vec<_>>(), "{}: {:?}", path.display(), order);
+            assert_eq!(tree.get("").unwrap().walk(order).collect::<Vec<_>>(),
+                       tree.clone().into_walk(order).collect::<Vec<_>>(),
+                       "{}: {:?}",
+                       path.display(),
+                       order);
+        }
+    }
+}
+
+#[test]
+fn nested() {
+    let arch = HrxArchive::from_str("<===> a/b/\n<===> a/b/c/file\n<===> a/other\n<===> z\n").unwrap();
+    let tree = arch.tree();
+
+    assert_eq!(tree.path(), "");
+    assert_eq!(tree.name(), "");
+    assert!(tree.is_dir());
+    assert!(tree.entry().is_none());
+    assert_eq!(tree.get("").unwrap().path(), "");
+
+    let b = tree.get("a/b").unwrap();
+    assert_eq!(b.entry(),
+               Some(&HrxEntry {
+                   comment: None,
+                   data: HrxEntryData::Directory,
+               }));
+    assert_eq!(b.get("c/file").unwrap().path(), "a/b/c/file");
+    assert_eq!(b.children().map(|c| c.name()).collect::<Vec<_>>(), vec!["c"]);
+    assert!(tree.get("a/b/").is_none());
+    assert!(tree.get("a/b/c/file/x").is_none());
+
+    assert_eq!(arch.children("a"), Some(vec!["a/b", "a/other"]));
+    assert_eq!(arch.children("a/b/c/file"), None);
+    assert_eq!(arch.children("a/b/c"), Some(vec!["a/b/c/file"]));
+    assert_eq!(arch.children("a/b/c/file/x"), None);
+}
+
+#[test]
+fn file_as_directory() {
+    // The entries can be modified freely, so the tree has to cope with the file-as-directory case
+    let mut arch = HrxArchive::new(NonZeroUsize::new(3).unwrap());
+    arch.entries.insert("a/b".parse().unwrap(),
+                        HrxEntry {
+                            comment: None,
+                            data: HrxEntryData::File { body: None },
+                        });
+    arch.entries.insert("a".parse().unwrap(),
+                        HrxEntry {
+                            comment: None,
+                            data: HrxEntryData::File { body: None },
+                        });
+
+    let tree = arch.tree();
+    let a = tree.get("a").unwrap();
+    assert!(!a.is_dir());
+    assert_eq!(a.children().map(|c| c.path()).collect::<Vec<_>>(), vec!["a/b"]);
+    assert_eq!(arch.children("a"), None);
+    assert!(!arch.is_dir("a"));
+}
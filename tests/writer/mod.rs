@@ -0,0 +1,80 @@
+use hrx::{ErroneousBodyPath, HrxEntryData, HrxArchive, HrxWriter, HrxEntry, HrxError};
+use std::num::NonZeroUsize;
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::io::Read;
+
+
+#[test]
+fn examples() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let arch = HrxArchive::from_str(&body).unwrap();
+
+        let mut writer = HrxWriter::new(vec![], arch.boundary_length());
+        for (p, e) in &arch.entries {
+            writer.add_entry(p, e).unwrap();
+        }
+        let out = writer.finish(arch.comment.as_deref()).unwrap();
+
+        let mut serialised = vec![];
+        arch.serialise(&mut serialised).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), String::from_utf8(serialised).unwrap(), "{}", path.display());
+    }
+}
+
+#[test]
+fn boundary_at_body_start() {
+    let mut writer = HrxWriter::new(vec![], NonZeroUsize::new(3).unwrap());
+    assert_eq!(writer.add_file(&"file".parse().unwrap(), Some("<===> not quite a comment"), Some("<===>\n")).unwrap_err().unwrap(),
+               HrxError::BodyContainsBoundary(vec![ErroneousBodyPath::EntryComment("file".to_string()), ErroneousBodyPath::EntryData("file".to_string())]));
+    assert_eq!(writer.add_dir(&"dir".parse().unwrap(), Some("comment\n<===>")).unwrap_err().unwrap(),
+               HrxError::BodyContainsBoundary(vec![ErroneousBodyPath::EntryComment("dir".to_string())]));
+
+    writer.add_file(&"file".parse().unwrap(), None, Some("<====> longer boundaries are fine")).unwrap();
+    assert_eq!(writer.finish(Some("<===>")).unwrap_err().unwrap(),
+               HrxError::BodyContainsBoundary(vec![ErroneousBodyPath::RootComment]));
+}
+
+#[test]
+fn directory_tree() {
+    let mut writer = HrxWriter::new(vec![], NonZeroUsize::new(3).unwrap());
+    writer.add_file(&"file".parse().unwrap(), None, None).unwrap();
+    writer.add_dir(&"dir/sub".parse().unwrap(), None).unwrap();
+
+    assert_eq!(writer.add_file(&"file/sub".parse().unwrap(), None, None).unwrap_err().unwrap(),
+               HrxError::FileAsDirectory("file".to_string(), "file/sub".to_string()));
+    assert_eq!(writer.add_dir(&"dir".parse().unwrap(), None).unwrap_err().unwrap(),
+               HrxError::DuplicateEntry("dir".to_string()));
+    assert_eq!(writer.add_file(&"dir/sub".parse().unwrap(), None, None).unwrap_err().unwrap(),
+               HrxError::DuplicateEntry("dir/sub".to_string()));
+
+    writer.add_file(&"dir/sub/file".parse().unwrap(), None, Some("contents")).unwrap();
+    assert_eq!(String::from_utf8(writer.finish(None).unwrap()).unwrap(),
+               "<===> file\n<===> dir/sub/\n<===> dir/sub/file\ncontents");
+}
+
+#[test]
+fn empty() {
+    let mut writer = HrxWriter::new(vec![], NonZeroUsize::new(3).unwrap());
+    writer.add_file(&"file".parse().unwrap(), Some(""), Some("")).unwrap();
+    writer.add_entry(&"other".parse().unwrap(),
+                   &HrxEntry {
+                       comment: Some(String::new()),
+                       data: HrxEntryData::File { body: Some("\n".to_string()) },
+                   })
+        .unwrap();
+    let out = String::from_utf8(writer.finish(Some("")).unwrap()).unwrap();
+
+    assert_eq!(out, "<===> file\n<===> other\n\n");
+    let arch = HrxArchive::from_str(&out).unwrap();
+    assert_eq!(arch.comment, None);
+    assert_eq!(arch.entries.values().map(|e| (e.comment.clone(), e.data.clone())).collect::<Vec<_>>(),
+               vec![(None, HrxEntryData::File { body: None }), (None, HrxEntryData::File { body: Some("\n".to_string()) })]);
+}
@@ -0,0 +1,157 @@
+use hrx::{UnrepresentableReason, UnrepresentablePolicy, ZipOptions, HrxArchive, HrxError};
+use zip::write::{FileOptions, ZipWriter};
+use zip::{CompressionMethod, ZipArchive};
+use std::fs::{self, File};
+use std::io::{Cursor, Write, Read};
+use std::str::FromStr;
+
+
+fn skip() -> ZipOptions {
+    ZipOptions { unrepresentable: UnrepresentablePolicy::Skip, ..ZipOptions::default() }
+}
+
+fn paths(arch: &HrxArchive) -> Vec<&str> {
+    arch.entries.keys().map(|k| k.as_ref()).collect()
+}
+
+fn to_zip(arch: &HrxArchive) -> Vec<u8> {
+    let mut zip = vec![];
+    arch.to_zip(&mut zip).unwrap();
+    zip
+}
+
+
+#[test]
+fn examples_round_trip() {
+    for entry in fs::read_dir("ext/hrx/example").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            continue;
+        }
+
+        let mut body = String::new();
+        File::open(&path).unwrap().read_to_string(&mut body).unwrap();
+        let mut arch = HrxArchive::from_str(&body).unwrap();
+
+        let zip = to_zip(&arch);
+        assert_eq!(zip, to_zip(&arch));
+
+        // Redundant explicit directories don't survive
+        let dirs: Vec<_> = arch.entries
+            .iter()
+            .filter(|&(p, e)| e.comment.is_none() && arch.entries.keys().any(|c| c.starts_with(p) && c != p))
+            .map(|(p, _)| p.clone())
+            .collect();
+        for dir in dirs {
+            arch.entries.remove(&dir);
+        }
+
+        let read = HrxArchive::from_zip(Cursor::new(zip), &ZipOptions::default()).unwrap();
+        assert_eq!(read.comment, arch.comment);
+        assert_eq!(read.entries, arch.entries);
+    }
+}
+
+#[test]
+fn comments() {
+    let arch = HrxArchive::from_str("<===>
+Compiled from SCSS
+<===> out/input.css
+ul li {}
+<===>
+Nothing in here
+<===> empty/
+<===>
+Test fixtures").unwrap();
+
+    let mut zip = ZipArchive::new(Cursor::new(to_zip(&arch))).unwrap();
+    assert_eq!(zip.comment(), b"Test fixtures");
+
+    let mut entries = vec![];
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).unwrap();
+        let mut body = String::new();
+        file.read_to_string(&mut body).unwrap();
+        entries.push((file.name().to_string(), file.comment().to_string(), file.unix_mode().unwrap(), body));
+    }
+    assert_eq!(entries,
+               vec![("out/".to_string(), "".to_string(), 0o040755, "".to_string()),
+                    ("out/input.css".to_string(), "Compiled from SCSS".to_string(), 0o100644, "ul li {}".to_string()),
+                    ("empty/".to_string(), "Nothing in here".to_string(), 0o040755, "".to_string())]);
+}
+
+#[test]
+fn directory_after_contents() {
+    let arch = HrxArchive::from_str("<===> a/file\nbody\n<===>\nNothing but the file\n<===> b/\n").unwrap();
+
+    // Move a/file into b/, which then comes after it; the names are stored in both the local and the central header
+    let mut zip = to_zip(&arch);
+    for i in 0..zip.len() - 6 {
+        if &zip[i..i + 6] == b"a/file" {
+            zip[i] = b'b';
+        }
+    }
+
+    let read = HrxArchive::from_zip(Cursor::new(zip), &ZipOptions::default()).unwrap();
+    assert_eq!(read, HrxArchive::from_str("<===> a/\n<===>\nNothing but the file\n<===> b/\n<===> b/file\nbody").unwrap());
+}
+
+#[test]
+fn deflated() {
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    zip.set_comment("Generated upstream");
+    zip.add_directory("dir/", FileOptions::default()).unwrap();
+    zip.start_file("dir/file.txt", FileOptions::default().compression_method(CompressionMethod::Deflated)).unwrap();
+    zip.write_all("Compressible, compressible, compressible.\n".as_bytes()).unwrap();
+    zip.add_directory("empty/", FileOptions::default()).unwrap();
+    let zip = zip.finish().unwrap();
+
+    let arch = HrxArchive::from_zip(zip, &ZipOptions::default()).unwrap();
+    assert_eq!(arch,
+               HrxArchive::from_str("<===> dir/file.txt
+Compressible, compressible, compressible.
+
+<===> empty/
+<===>
+Generated upstream")
+                   .unwrap());
+}
+
+#[test]
+fn unrepresentable() {
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    zip.start_file("file", FileOptions::default()).unwrap();
+    zip.add_symlink("symlink", "file", FileOptions::default()).unwrap();
+    let zip = zip.finish().unwrap().into_inner();
+
+    assert_eq!(HrxArchive::from_zip(Cursor::new(&zip), &ZipOptions::default()).unwrap_err().unwrap(),
+               HrxError::Unrepresentable("symlink".to_string(), UnrepresentableReason::Symlink));
+    let (arch, skipped) = HrxArchive::from_zip_with_skipped(Cursor::new(&zip), &skip()).unwrap();
+    assert_eq!(paths(&arch), vec!["file"]);
+    assert_eq!(skipped, vec![("symlink".to_string(), UnrepresentableReason::Symlink)]);
+
+    for &(name, body, reason) in &[("binary", &b"\xFF\xFE"[..], UnrepresentableReason::NonUtf8Contents),
+                                   ("back\\slash", &b""[..], UnrepresentableReason::InvalidPath)] {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        zip.start_file("file", FileOptions::default()).unwrap();
+        zip.start_file(name, FileOptions::default()).unwrap();
+        zip.write_all(body).unwrap();
+        let zip = zip.finish().unwrap().into_inner();
+
+        assert_eq!(HrxArchive::from_zip(Cursor::new(&zip), &ZipOptions::default()).unwrap_err().unwrap(),
+                   HrxError::Unrepresentable(name.to_string(), reason));
+        let (arch, skipped) = HrxArchive::from_zip_with_skipped(Cursor::new(&zip), &skip()).unwrap();
+        assert_eq!(paths(&arch), vec!["file"]);
+        assert_eq!(skipped, vec![(name.to_string(), reason)]);
+    }
+}
+
+#[test]
+fn boundary_length() {
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    zip.start_file("nested.hrx", FileOptions::default()).unwrap();
+    zip.write_all(b"<===> inner\n<====>\n").unwrap();
+    let zip = zip.finish().unwrap();
+
+    assert_eq!(HrxArchive::from_zip(zip, &ZipOptions::default()).unwrap().boundary_length().get(), 5);
+}